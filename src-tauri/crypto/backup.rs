@@ -0,0 +1,213 @@
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use super::key_derivation::{derive_key, derive_key_with_params, KdfParams};
+use crate::error::Result;
+
+/// Version of the `.upgem` container layout
+pub const BACKUP_FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"UPGEMBAK";
+
+// Header layout, all integers little-endian:
+//   magic (8) | format version (2) | memory KiB (4) | iterations (4) | parallelism (4)
+//   | salt (16) | nonce (12) | key check (32) | MAC (32)
+// followed by the ChaCha20-Poly1305 ciphertext. The MAC is HMAC-SHA256 over
+// everything before it plus the ciphertext.
+const KEY_CHECK_OFFSET: usize = 8 + 2 + 12 + 16 + 12;
+const MAC_OFFSET: usize = KEY_CHECK_OFFSET + 32;
+const HEADER_LEN: usize = MAC_OFFSET + 32;
+
+// Upper bounds on the stored KDF cost, so a crafted file cannot make a restore
+// allocate gigabytes or spin for minutes before failing
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+type HmacSha256 = Hmac<Sha256>;
+
+struct BackupKeys {
+    encryption: [u8; 32],
+    mac: [u8; 32],
+    check: [u8; 32],
+}
+
+fn backup_keys(passphrase: &str, salt: &[u8; 16], kdf: &KdfParams) -> Result<BackupKeys> {
+    let root = derive_key_with_params(passphrase, salt, kdf)?;
+    Ok(BackupKeys {
+        encryption: derive_key(&root, "backup-encryption"),
+        mac: derive_key(&root, "backup-mac"),
+        check: derive_key(&root, "backup-key-check"),
+    })
+}
+
+fn new_mac(key: &[u8; 32]) -> Result<HmacSha256> {
+    <HmacSha256 as Mac>::new_from_slice(key)
+        .map_err(|e| crate::error::SkillEdError::CryptoError(e.to_string()))
+}
+
+/// Encrypts `plaintext` into a self-describing `.upgem` container
+pub fn seal_backup(passphrase: &str, plaintext: &[u8], kdf: &KdfParams) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    let salt: [u8; 16] = rng.gen();
+    let nonce_bytes: [u8; 12] = rng.gen();
+    let keys = backup_keys(passphrase, &salt, kdf)?;
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&BACKUP_FORMAT_VERSION.to_le_bytes());
+    header.extend_from_slice(&kdf.memory_kib.to_le_bytes());
+    header.extend_from_slice(&kdf.iterations.to_le_bytes());
+    header.extend_from_slice(&kdf.parallelism.to_le_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce_bytes);
+
+    let mut check = new_mac(&keys.check)?;
+    check.update(&header);
+    header.extend_from_slice(&check.finalize().into_bytes());
+
+    let cipher = ChaCha20Poly1305::new_from_slice(&keys.encryption)
+        .map_err(|e| crate::error::SkillEdError::CryptoError(e.to_string()))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce_bytes), Payload { msg: plaintext, aad: &header })
+        .map_err(|e| crate::error::SkillEdError::CryptoError(format!("Encryption failed: {}", e)))?;
+
+    let mut mac = new_mac(&keys.mac)?;
+    mac.update(&header);
+    mac.update(&ciphertext);
+    header.extend_from_slice(&mac.finalize().into_bytes());
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Checks and decrypts a `.upgem` container. A wrong passphrase is reported as
+/// an auth error; any other mismatch means the file is damaged or was altered.
+pub fn open_backup(passphrase: &str, data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < MAGIC.len() || &data[..MAGIC.len()] != MAGIC {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Not a Skill-ED backup file".to_string(),
+        ));
+    }
+
+    if data.len() < HEADER_LEN {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Backup file is truncated".to_string(),
+        ));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().expect("4-byte slice"));
+
+    let version = u16::from_le_bytes([data[8], data[9]]);
+    if version != BACKUP_FORMAT_VERSION {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Unsupported backup format version {}",
+            version
+        )));
+    }
+
+    let kdf = KdfParams {
+        memory_kib: read_u32(10),
+        iterations: read_u32(14),
+        parallelism: read_u32(18),
+    };
+    if kdf.memory_kib > MAX_MEMORY_KIB
+        || !(1..=MAX_ITERATIONS).contains(&kdf.iterations)
+        || !(1..=MAX_PARALLELISM).contains(&kdf.parallelism)
+    {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Backup file has invalid key derivation parameters".to_string(),
+        ));
+    }
+
+    let salt: [u8; 16] = data[22..38].try_into().expect("16-byte slice");
+    let nonce = Nonce::from_slice(&data[38..KEY_CHECK_OFFSET]);
+    let keys = backup_keys(passphrase, &salt, &kdf)?;
+
+    let mut check = new_mac(&keys.check)?;
+    check.update(&data[..KEY_CHECK_OFFSET]);
+    if check.verify_slice(&data[KEY_CHECK_OFFSET..MAC_OFFSET]).is_err() {
+        return Err(crate::error::SkillEdError::AuthError(
+            "Incorrect backup passphrase".to_string(),
+        ));
+    }
+
+    let header = &data[..MAC_OFFSET];
+    let ciphertext = &data[HEADER_LEN..];
+
+    let mut mac = new_mac(&keys.mac)?;
+    mac.update(header);
+    mac.update(ciphertext);
+    if mac.verify_slice(&data[MAC_OFFSET..HEADER_LEN]).is_err() {
+        return Err(crate::error::SkillEdError::CryptoError(
+            "Backup file failed its integrity check".to_string(),
+        ));
+    }
+
+    let cipher = ChaCha20Poly1305::new_from_slice(&keys.encryption)
+        .map_err(|e| crate::error::SkillEdError::CryptoError(e.to_string()))?;
+    cipher
+        .decrypt(nonce, Payload { msg: ciphertext, aad: header })
+        .map_err(|e| crate::error::SkillEdError::CryptoError(format!("Decryption failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SkillEdError;
+
+    // Cheap parameters; the format does not depend on the cost
+    const TEST_KDF: KdfParams = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+    #[test]
+    fn test_seal_and_open() {
+        let plaintext = b"{\"schema_version\":1}";
+        let sealed = seal_backup("correct horse", plaintext, &TEST_KDF).unwrap();
+
+        assert_eq!(&sealed[..8], MAGIC);
+        assert_eq!(open_backup("correct horse", &sealed).unwrap(), plaintext.to_vec());
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let sealed = seal_backup("correct horse", b"data", &TEST_KDF).unwrap();
+        assert!(matches!(open_backup("battery staple", &sealed), Err(SkillEdError::AuthError(_))));
+    }
+
+    #[test]
+    fn test_tampered_ciphertext_and_header() {
+        let sealed = seal_backup("correct horse", b"some backup data", &TEST_KDF).unwrap();
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+        assert!(matches!(open_backup("correct horse", &tampered), Err(SkillEdError::CryptoError(_))));
+
+        let mut tampered = sealed.clone();
+        tampered[MAC_OFFSET] ^= 0x01;
+        assert!(matches!(open_backup("correct horse", &tampered), Err(SkillEdError::CryptoError(_))));
+
+        // Changing the stored KDF cost changes the derived key
+        let mut tampered = sealed;
+        tampered[14] = 2;
+        assert!(open_backup("correct horse", &tampered).is_err());
+    }
+
+    #[test]
+    fn test_truncated_file() {
+        let sealed = seal_backup("correct horse", b"some backup data", &TEST_KDF).unwrap();
+
+        assert!(matches!(
+            open_backup("correct horse", &sealed[..sealed.len() - 4]),
+            Err(SkillEdError::CryptoError(_))
+        ));
+        assert!(matches!(
+            open_backup("correct horse", &sealed[..HEADER_LEN - 1]),
+            Err(SkillEdError::ValidationError(_))
+        ));
+        assert!(matches!(open_backup("correct horse", b"{}"), Err(SkillEdError::ValidationError(_))));
+    }
+}
@@ -17,6 +17,40 @@ pub fn derive_master_key(password: &str, salt: &[u8; 16]) -> Result<[u8; 32]> {
     Ok(output)
 }
 
+/// Argon2id cost parameters, stored alongside anything encrypted with a derived
+/// key so it can be re-derived later even if the defaults change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Derives a 256-bit key from a passphrase with explicit Argon2id parameters
+pub fn derive_key_with_params(password: &str, salt: &[u8; 16], kdf: &KdfParams) -> Result<[u8; 32]> {
+    let mut output = [0u8; 32];
+
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| crate::error::SkillEdError::CryptoError(e.to_string()))?;
+
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+    argon2.hash_password_into(password.as_bytes(), salt, &mut output)
+        .map_err(|e| crate::error::SkillEdError::CryptoError(e.to_string()))?;
+
+    Ok(output)
+}
+
 /// Derives a domain-specific key from the master key using HKDF-like expansion
 pub fn derive_key(master_key: &[u8; 32], domain: &str) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -1,7 +1,9 @@
 mod encryption;
 mod hashing;
 mod key_derivation;
+mod backup;
 
 pub use encryption::{encrypt_data, decrypt_data};
 pub use hashing::{hash_password, verify_password};
-pub use key_derivation::{derive_key, derive_master_key};
+pub use key_derivation::{derive_key, derive_master_key, KdfParams};
+pub use backup::{open_backup, seal_backup};
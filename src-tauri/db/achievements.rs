@@ -0,0 +1,327 @@
+use crate::db::Achievement;
+use super::activity::record_activity;
+use crate::error::Result;
+use sqlx::{SqliteConnection, SqlitePool};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Tauri event emitted with the `Achievement` payload when one is unlocked
+pub const ACHIEVEMENT_UNLOCKED_EVENT: &str = "achievement-unlocked";
+
+/// Condition an achievement unlocks on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AchievementRule {
+    /// Best streak of any daily habit
+    DailyHabitStreak { days: i32 },
+    /// Hours practiced on a single skill
+    SkillHours { hours: f64 },
+    MilestonesCompleted { count: i64 },
+    HabitCompletions { count: i64 },
+    PracticeSessions { count: i64 },
+    TotalXp { xp: i64 },
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AchievementDefinition {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub rule: AchievementRule,
+}
+
+/// Every achievement the app knows about. Ids are persisted with unlocks, so they
+/// must never change once released.
+pub const ACHIEVEMENTS: &[AchievementDefinition] = &[
+    AchievementDefinition {
+        id: "first_habit",
+        title: "First Step",
+        description: "Complete a habit for the first time",
+        rule: AchievementRule::HabitCompletions { count: 1 },
+    },
+    AchievementDefinition {
+        id: "first_session",
+        title: "Warming Up",
+        description: "Finish your first practice session",
+        rule: AchievementRule::PracticeSessions { count: 1 },
+    },
+    AchievementDefinition {
+        id: "streak_7",
+        title: "On a Roll",
+        description: "Reach a 7-day streak on any habit",
+        rule: AchievementRule::DailyHabitStreak { days: 7 },
+    },
+    AchievementDefinition {
+        id: "streak_30",
+        title: "Unbreakable",
+        description: "Reach a 30-day streak on any habit",
+        rule: AchievementRule::DailyHabitStreak { days: 30 },
+    },
+    AchievementDefinition {
+        id: "skill_hours_10",
+        title: "Getting Serious",
+        description: "Practice a single skill for 10 hours",
+        rule: AchievementRule::SkillHours { hours: 10.0 },
+    },
+    AchievementDefinition {
+        id: "skill_hours_100",
+        title: "Dedicated",
+        description: "Practice a single skill for 100 hours",
+        rule: AchievementRule::SkillHours { hours: 100.0 },
+    },
+    AchievementDefinition {
+        id: "skill_hours_1000",
+        title: "Expert in the Making",
+        description: "Practice a single skill for 1,000 hours",
+        rule: AchievementRule::SkillHours { hours: 1000.0 },
+    },
+    AchievementDefinition {
+        id: "milestones_1",
+        title: "Goal Getter",
+        description: "Complete your first milestone",
+        rule: AchievementRule::MilestonesCompleted { count: 1 },
+    },
+    AchievementDefinition {
+        id: "milestones_10",
+        title: "Milestone Master",
+        description: "Complete 10 milestones",
+        rule: AchievementRule::MilestonesCompleted { count: 10 },
+    },
+    AchievementDefinition {
+        id: "xp_1000",
+        title: "Leveling Up",
+        description: "Earn 1,000 XP",
+        rule: AchievementRule::TotalXp { xp: 1000 },
+    },
+];
+
+/// The figures every rule is evaluated against
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct AchievementStats {
+    pub best_daily_streak: i32,
+    pub max_skill_hours: f64,
+    pub milestones_completed: i64,
+    pub habit_completions: i64,
+    pub practice_sessions: i64,
+    pub total_xp: i64,
+}
+
+impl AchievementRule {
+    pub fn is_met(&self, stats: &AchievementStats) -> bool {
+        match *self {
+            AchievementRule::DailyHabitStreak { days } => stats.best_daily_streak >= days,
+            AchievementRule::SkillHours { hours } => stats.max_skill_hours >= hours,
+            AchievementRule::MilestonesCompleted { count } => stats.milestones_completed >= count,
+            AchievementRule::HabitCompletions { count } => stats.habit_completions >= count,
+            AchievementRule::PracticeSessions { count } => stats.practice_sessions >= count,
+            AchievementRule::TotalXp { xp } => stats.total_xp >= xp,
+        }
+    }
+}
+
+type UnlockListener = Box<dyn Fn(&Achievement) + Send + Sync>;
+
+lazy_static! {
+    static ref UNLOCK_LISTENER: Mutex<Option<UnlockListener>> = Mutex::new(None);
+}
+
+/// Registers the callback told about each unlock once its write has committed
+pub fn set_unlock_listener(listener: impl Fn(&Achievement) + Send + Sync + 'static) {
+    *UNLOCK_LISTENER.lock() = Some(Box::new(listener));
+}
+
+pub(super) fn notify_unlocked(unlocked: &[Achievement]) {
+    if let Some(listener) = UNLOCK_LISTENER.lock().as_ref() {
+        for achievement in unlocked {
+            listener(achievement);
+        }
+    }
+}
+
+// ============ ACHIEVEMENT OPERATIONS ============
+
+/// All achievements, with `unlocked_at` set on the ones already earned
+pub async fn get_achievements(pool: &SqlitePool) -> Result<Vec<Achievement>> {
+    let unlocked: HashMap<String, String> = sqlx::query_as::<_, (String, String)>(
+        "SELECT achievement_id, unlocked_at FROM achievement_unlocks"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+    .into_iter()
+    .collect();
+
+    Ok(ACHIEVEMENTS
+        .iter()
+        .map(|definition| Achievement::from_definition(definition, unlocked.get(definition.id).cloned()))
+        .collect())
+}
+
+/// Unlocks every achievement whose rule is now met and returns the new unlocks.
+/// Runs inside the caller's transaction so an unlock never outlives a rolled-back write.
+pub(super) async fn check_achievements(conn: &mut SqliteConnection) -> Result<Vec<Achievement>> {
+    let unlocked: HashSet<String> = sqlx::query_scalar::<_, String>("SELECT achievement_id FROM achievement_unlocks")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .collect();
+
+    if ACHIEVEMENTS.iter().all(|definition| unlocked.contains(definition.id)) {
+        return Ok(Vec::new());
+    }
+
+    let stats = load_stats(&mut *conn).await?;
+    let now = Utc::now().to_rfc3339();
+    let mut newly_unlocked = Vec::new();
+
+    for definition in ACHIEVEMENTS {
+        if unlocked.contains(definition.id) || !definition.rule.is_met(&stats) {
+            continue;
+        }
+
+        sqlx::query("INSERT INTO achievement_unlocks (achievement_id, unlocked_at) VALUES (?, ?)")
+            .bind(definition.id)
+            .bind(&now)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        let achievement = Achievement::from_definition(definition, Some(now.clone()));
+        record_activity(
+            &mut *conn,
+            "achievement_unlocked",
+            "achievement",
+            definition.id,
+            format!("Unlocked {}", definition.title),
+            &achievement,
+        )
+        .await?;
+        newly_unlocked.push(achievement);
+    }
+
+    Ok(newly_unlocked)
+}
+
+async fn load_stats(conn: &mut SqliteConnection) -> Result<AchievementStats> {
+    sqlx::query_as::<_, AchievementStats>(
+        "SELECT
+            (SELECT COALESCE(MAX(best_streak), 0) FROM habits
+             WHERE frequency = 'daily' AND polarity = 'positive' AND deleted_at IS NULL) AS best_daily_streak,
+            (SELECT COALESCE(MAX(total_hours), 0.0) FROM skills WHERE deleted_at IS NULL) AS max_skill_hours,
+            (SELECT COUNT(*) FROM milestones WHERE is_completed = 1) AS milestones_completed,
+            (SELECT COUNT(*) FROM habit_completions WHERE completion_type = 'completion') AS habit_completions,
+            (SELECT COUNT(*) FROM practice_sessions
+             WHERE ended_at IS NOT NULL AND source_type = 'timer') AS practice_sessions,
+            (SELECT COALESCE(SUM(xp), 0) FROM xp_ledger) AS total_xp"
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    fn unlocked_ids(achievements: &[Achievement]) -> Vec<&str> {
+        achievements.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_achievement_ids_are_unique() {
+        let ids: HashSet<&str> = ACHIEVEMENTS.iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), ACHIEVEMENTS.len());
+    }
+
+    #[test]
+    fn test_rules_against_stats() {
+        let stats = AchievementStats {
+            best_daily_streak: 7,
+            max_skill_hours: 99.5,
+            milestones_completed: 10,
+            ..AchievementStats::default()
+        };
+
+        assert!(AchievementRule::DailyHabitStreak { days: 7 }.is_met(&stats));
+        assert!(!AchievementRule::DailyHabitStreak { days: 30 }.is_met(&stats));
+        assert!(!AchievementRule::SkillHours { hours: 100.0 }.is_met(&stats));
+        assert!(AchievementRule::MilestonesCompleted { count: 10 }.is_met(&stats));
+    }
+
+    #[tokio::test]
+    async fn test_seven_day_streak_unlocks_once() {
+        let pool = test_pool().await;
+        sqlx::query(
+            "INSERT INTO habits (id, name, frequency, polarity, streak_count, best_streak, created_at)
+             VALUES ('h1', 'Read', 'daily', 'positive', 7, 7, '2024-01-01T00:00:00+00:00')"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let first = check_achievements(&mut conn).await.unwrap();
+        assert_eq!(unlocked_ids(&first), vec!["streak_7"]);
+        assert!(check_achievements(&mut conn).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_skill_hours_and_milestones_unlock_from_writes() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        sqlx::query(
+            "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds)
+             VALUES ('s1', ?, '2024-01-01T00:00:00+00:00', '2024-01-05T04:00:00+00:00', 360000)"
+        )
+        .bind(&skill.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Recording progress recomputes hours from sessions, which crosses 100 hours
+        db::record_skill_progress(&pool, &skill.id, 10, 0.0).await.unwrap();
+        for i in 0..10 {
+            let milestone = db::create_milestone(&pool, format!("Goal {}", i), "2030-01-01".to_string(), None, None)
+                .await
+                .unwrap();
+            db::complete_milestone(&pool, &milestone.id).await.unwrap();
+        }
+
+        let achievements = get_achievements(&pool).await.unwrap();
+        let earned: Vec<&str> = achievements
+            .iter()
+            .filter(|a| a.unlocked_at.is_some())
+            .map(|a| a.id.as_str())
+            .collect();
+        assert!(earned.contains(&"skill_hours_100"));
+        assert!(earned.contains(&"milestones_10"));
+        assert!(!earned.contains(&"skill_hours_1000"));
+    }
+
+    #[tokio::test]
+    async fn test_only_timed_sessions_count_as_practice() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = db::create_habit(&pool, "Scales".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::link_habit_to_skill(&pool, &habit.id, &skill.id, 15, 0).await.unwrap();
+
+        // Logged hours and linked completions book sessions that were never timed
+        db::record_skill_progress(&pool, &skill.id, 10, 1.0).await.unwrap();
+        db::log_habit_completion(&pool, &habit.id).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(load_stats(&mut conn).await.unwrap().practice_sessions, 0);
+        drop(conn);
+
+        db::start_session(&pool, &skill.id, None).await.unwrap();
+        db::stop_session(&pool, None, None).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(load_stats(&mut conn).await.unwrap().practice_sessions, 1);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::db::{ActivityEvent, ActivityFilter};
+use crate::error::Result;
+use serde::Serialize;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use lazy_static::lazy_static;
+use tokio::sync::watch;
+
+lazy_static! {
+    /// Counts data changes committed since startup
+    static ref CHANGES: watch::Sender<u64> = watch::channel(0).0;
+}
+
+// ============ ACTIVITY LOG OPERATIONS ============
+
+/// Wakes on every data change. The signal is sent once the change has
+/// committed, so subscribers read the new data.
+pub fn subscribe_changes() -> watch::Receiver<u64> {
+    CHANGES.subscribe()
+}
+
+/// Signals subscribers; call it after the change's transaction has committed
+pub(super) fn notify_changes() {
+    CHANGES.send_modify(|count| *count += 1);
+}
+
+/// Appends an event describing a domain change. Call it with the connection of the
+/// transaction making the change, so the log never disagrees with the data.
+/// `snapshot` is the affected row as it looks after the change.
+pub(super) async fn record_activity<T: Serialize>(
+    conn: &mut SqliteConnection,
+    event_type: &str,
+    entity_type: &str,
+    entity_id: &str,
+    summary: String,
+    snapshot: &T,
+) -> Result<()> {
+    let mut event = ActivityEvent::new(event_type, entity_type, entity_id, summary);
+    event.payload = Some(serde_json::to_string(snapshot)?);
+
+    sqlx::query(
+        "INSERT INTO activity_events (id, event_type, entity_type, entity_id, summary, payload, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&event.id)
+    .bind(&event.event_type)
+    .bind(&event.entity_type)
+    .bind(&event.entity_id)
+    .bind(&event.summary)
+    .bind(&event.payload)
+    .bind(&event.created_at)
+    .execute(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Events matching `filter`, newest first
+pub async fn get_activity_feed(
+    pool: &SqlitePool,
+    filter: &ActivityFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ActivityEvent>> {
+    let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT id, event_type, entity_type, entity_id, summary, payload, created_at
+         FROM activity_events WHERE 1 = 1"
+    );
+
+    if !filter.event_types.is_empty() {
+        query.push(" AND event_type IN (");
+        let mut types = query.separated(", ");
+        for event_type in &filter.event_types {
+            types.push_bind(event_type);
+        }
+        types.push_unseparated(")");
+    }
+    if let Some(entity_type) = &filter.entity_type {
+        query.push(" AND entity_type = ").push_bind(entity_type);
+    }
+    if let Some(entity_id) = &filter.entity_id {
+        query.push(" AND entity_id = ").push_bind(entity_id);
+    }
+    if let Some(from) = &filter.from {
+        query.push(" AND created_at >= ").push_bind(parse_bound(from, false)?);
+    }
+    if let Some(to) = &filter.to {
+        query.push(" AND created_at < ").push_bind(parse_bound(to, true)?);
+    }
+
+    query.push(" ORDER BY created_at DESC, id ASC LIMIT ")
+        .push_bind(limit.max(0))
+        .push(" OFFSET ")
+        .push_bind(offset.max(0));
+
+    let events = query.build_query_as::<ActivityEvent>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(events)
+}
+
+/// Turns a filter bound into an RFC 3339 UTC timestamp. A plain `YYYY-MM-DD` date
+/// means a local calendar day, and an upper bound date includes the whole day.
+pub(super) fn parse_bound(value: &str, upper: bool) -> Result<String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let date = if upper { date + Duration::days(1) } else { date };
+        return Ok(local_day_start(date).to_rfc3339());
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc).to_rfc3339())
+        .map_err(|_| crate::error::SkillEdError::ValidationError(
+            format!("Invalid date '{}', expected YYYY-MM-DD or an RFC 3339 timestamp", value),
+        ))
+}
+
+pub(super) fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        crate::error::SkillEdError::ValidationError(format!("Invalid date '{}', expected YYYY-MM-DD", value))
+    })
+}
+
+/// The UTC instant a local calendar day begins
+pub(super) fn local_day_start(date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        // Midnight skipped by a DST change; the day starts an hour later
+        .unwrap_or_else(|| Local.from_local_datetime(&(midnight + Duration::hours(1))).unwrap())
+        .with_timezone(&Utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    #[test]
+    fn test_parse_bound_accepts_dates_and_timestamps() {
+        let start = parse_bound("2024-03-01", false).unwrap();
+        let end = parse_bound("2024-03-01", true).unwrap();
+        assert!(start < end);
+        assert_eq!(
+            parse_bound("2024-03-01T12:00:00Z", false).unwrap(),
+            "2024-03-01T12:00:00+00:00"
+        );
+        assert!(parse_bound("03/01/2024", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_writes_leave_an_event_trail() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        db::record_skill_progress(&pool, &skill.id, 20, 1.0).await.unwrap();
+        let habit = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::log_habit_completion(&pool, &habit.id).await.unwrap();
+
+        let all = get_activity_feed(&pool, &ActivityFilter::default(), 50, 0).await.unwrap();
+        let types: Vec<&str> = all.iter().map(|e| e.event_type.as_str()).collect();
+        for expected in ["skill_created", "progress_logged", "habit_created", "habit_completed"] {
+            assert!(types.contains(&expected), "missing {}", expected);
+        }
+
+        let filter = ActivityFilter {
+            event_types: vec!["progress_logged".to_string()],
+            ..ActivityFilter::default()
+        };
+        let progress = get_activity_feed(&pool, &filter, 50, 0).await.unwrap();
+        assert_eq!(progress.len(), 1);
+        assert_eq!(progress[0].entity_id, skill.id);
+
+        let page = get_activity_feed(&pool, &ActivityFilter::default(), 2, 2).await.unwrap();
+        assert_eq!(page.len(), 2);
+        assert!(sqlx::query("UPDATE activity_events SET summary = ''").execute(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_committed_writes_signal_changes() {
+        let pool = test_pool().await;
+        let mut changes = subscribe_changes();
+        changes.borrow_and_update();
+
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        assert!(changes.has_changed().unwrap());
+
+        changes.borrow_and_update();
+        db::delete_skill(&pool, &skill.id).await.unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert!(db::get_skill(&pool, &skill.id).await.unwrap().unwrap().deleted_at.is_some());
+    }
+}
@@ -0,0 +1,373 @@
+use crate::crypto::{self, KdfParams};
+use crate::db::{BackupRun, BackupSchedule, Export, ImportSummary, VaultExport};
+use crate::error::Result;
+use super::exports::{record_export, write_export};
+use super::operations::{local_date, period_index};
+use super::preferences::{get_preference, set_preference};
+use super::transfer::{build_export, import_data};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// File extension of encrypted backups
+pub const BACKUP_EXTENSION: &str = "upgem";
+
+/// Default directory for scheduled backups, under the app data directory
+pub const BACKUPS_DIR: &str = "backups";
+
+const BACKUP_SCHEDULE_KEY: &str = "backup_schedule";
+const BACKUP_FILE_PREFIX: &str = "skill-ed-backup-";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// Decompressed exports larger than this are rejected rather than read into memory
+const MAX_BACKUP_SIZE: u64 = 512 * 1024 * 1024;
+
+// ============ BACKUP OPERATIONS ============
+
+/// Writes the full export, gzip-compressed and encrypted under `passphrase`, to
+/// the exports directory as a `.upgem` file
+pub async fn create_backup(pool: &SqlitePool, data_dir: &Path, passphrase: &str) -> Result<Export> {
+    create_backup_with_kdf(pool, data_dir, passphrase, &KdfParams::default()).await
+}
+
+pub(super) async fn create_backup_with_kdf(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    passphrase: &str,
+    kdf: &KdfParams,
+) -> Result<Export> {
+    validate_backup_passphrase(passphrase)?;
+
+    let document = build_export(pool).await?;
+    let sealed = seal_export(&document, passphrase, kdf)?;
+
+    write_export(pool, data_dir, "backup", &backup_file_name(Utc::now()), &sealed).await
+}
+
+/// Decrypts a `.upgem` file and imports it with the given mode ("merge" or "replace")
+pub async fn restore_backup(pool: &SqlitePool, path: &Path, passphrase: &str, mode: &str) -> Result<ImportSummary> {
+    let data = std::fs::read(path)?;
+    let document = open_export(&data, passphrase)?;
+
+    import_data(pool, &document, mode).await
+}
+
+fn seal_export(document: &VaultExport, passphrase: &str, kdf: &KdfParams) -> Result<Vec<u8>> {
+    let json = serde_json::to_vec(document)?;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json)?;
+    let compressed = encoder.finish()?;
+
+    crypto::seal_backup(passphrase, &compressed, kdf)
+}
+
+fn open_export(data: &[u8], passphrase: &str) -> Result<VaultExport> {
+    let compressed = crypto::open_backup(passphrase, data)?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .take(MAX_BACKUP_SIZE + 1)
+        .read_to_end(&mut json)?;
+    if json.len() as u64 > MAX_BACKUP_SIZE {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Backup is too large to restore".to_string(),
+        ));
+    }
+
+    Ok(serde_json::from_slice(&json)?)
+}
+
+pub fn validate_backup_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < 8 {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Backup passphrase must be at least 8 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn backup_file_name(at: DateTime<Utc>) -> String {
+    format!("{}{}.{}", BACKUP_FILE_PREFIX, at.format(BACKUP_TIMESTAMP_FORMAT), BACKUP_EXTENSION)
+}
+
+/// When a backup file was taken, from its name; None for files we did not write
+fn backup_file_time(file_name: &str) -> Option<DateTime<Utc>> {
+    let stamp = file_name
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(&format!(".{}", BACKUP_EXTENSION))?;
+    NaiveDateTime::parse_from_str(stamp, BACKUP_TIMESTAMP_FORMAT)
+        .ok()
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+// ============ SCHEDULED BACKUP OPERATIONS ============
+
+pub async fn get_backup_schedule(pool: &SqlitePool) -> Result<BackupSchedule> {
+    Ok(get_preference(pool, BACKUP_SCHEDULE_KEY).await?.unwrap_or_default())
+}
+
+pub async fn update_backup_schedule(pool: &SqlitePool, schedule: BackupSchedule) -> Result<BackupSchedule> {
+    if !["daily", "weekly"].contains(&schedule.cadence.as_str()) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Invalid backup cadence. Must be 'daily' or 'weekly'".to_string(),
+        ));
+    }
+    if schedule.keep_daily == 0 && schedule.keep_weekly == 0 {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Retention must keep at least one daily or weekly backup".to_string(),
+        ));
+    }
+    if let Some(directory) = &schedule.directory {
+        if !Path::new(directory).is_absolute() {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Backup directory must be an absolute path".to_string(),
+            ));
+        }
+    }
+
+    set_preference(pool, BACKUP_SCHEDULE_KEY, &schedule).await?;
+    Ok(schedule)
+}
+
+pub fn backup_directory(schedule: &BackupSchedule, data_dir: &Path) -> PathBuf {
+    match &schedule.directory {
+        Some(directory) => PathBuf::from(directory),
+        None => data_dir.join(BACKUPS_DIR),
+    }
+}
+
+/// When the last successful scheduled backup ran
+pub async fn last_scheduled_backup(pool: &SqlitePool) -> Result<Option<String>> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT created_at FROM exports WHERE export_type = 'scheduled_backup' ORDER BY created_at DESC LIMIT 1"
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+/// A backup is due once the local day (or Monday-based week) of the last one has passed
+pub fn backup_due(cadence: &str, last_run: Option<&str>, now: DateTime<Utc>) -> Result<bool> {
+    let Some(last_run) = last_run else {
+        return Ok(true);
+    };
+
+    let today = now.with_timezone(&Local).date_naive();
+    Ok(period_index(cadence, today) > period_index(cadence, local_date(last_run)?))
+}
+
+/// Takes a backup into the scheduled backup directory, records it, then rotates
+/// out old backups there according to the retention settings
+pub async fn run_scheduled_backup(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    schedule: &BackupSchedule,
+    passphrase: &str,
+) -> Result<BackupRun> {
+    run_scheduled_backup_with_kdf(pool, data_dir, schedule, passphrase, &KdfParams::default()).await
+}
+
+async fn run_scheduled_backup_with_kdf(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    schedule: &BackupSchedule,
+    passphrase: &str,
+    kdf: &KdfParams,
+) -> Result<BackupRun> {
+    validate_backup_passphrase(passphrase)?;
+
+    let directory = backup_directory(schedule, data_dir);
+    std::fs::create_dir_all(&directory)?;
+
+    let document = build_export(pool).await?;
+    let sealed = seal_export(&document, passphrase, kdf)?;
+    let path = directory.join(backup_file_name(Utc::now()));
+    std::fs::write(&path, sealed)?;
+
+    let export = record_export(pool, "scheduled_backup", &path).await?;
+    let removed = rotate_backups(&directory, schedule)?;
+
+    Ok(BackupRun { export, removed })
+}
+
+/// Logs a failed scheduled backup in the export history
+pub async fn record_backup_failure(pool: &SqlitePool, directory: &Path) -> Result<Export> {
+    record_export(pool, "scheduled_backup_failed", directory).await
+}
+
+/// Deletes backups in `directory` that the retention policy no longer covers.
+/// Only files named like our own backups are considered.
+fn rotate_backups(directory: &Path, schedule: &BackupSchedule) -> Result<Vec<String>> {
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let taken_at = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(backup_file_time);
+        if let Some(taken_at) = taken_at {
+            backups.push((path, taken_at.with_timezone(&Local).naive_local()));
+        }
+    }
+
+    let mut removed = Vec::new();
+    for path in backups_to_remove(backups, schedule.keep_daily, schedule.keep_weekly) {
+        std::fs::remove_file(&path)?;
+        removed.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(removed)
+}
+
+/// Grandfather-father-son selection: keeps the newest backup of each of the
+/// `keep_daily` most recent days and of each of the `keep_weekly` most recent
+/// weeks that have one, and returns everything else
+fn backups_to_remove(mut backups: Vec<(PathBuf, NaiveDateTime)>, keep_daily: u32, keep_weekly: u32) -> Vec<PathBuf> {
+    backups.sort_by_key(|(_, taken_at)| std::cmp::Reverse(*taken_at));
+
+    let mut days: HashSet<NaiveDate> = HashSet::new();
+    let mut weeks: HashSet<i64> = HashSet::new();
+    let mut remove = Vec::new();
+
+    for (path, taken_at) in backups {
+        let day = taken_at.date();
+        let week = period_index("weekly", day);
+
+        let keep_for_day = days.len() < keep_daily as usize && days.insert(day);
+        let keep_for_week = weeks.len() < keep_weekly as usize && weeks.insert(week);
+
+        // Older backups from a day or week already covered are not kept for it
+        days.insert(day);
+        weeks.insert(week);
+        if !keep_for_day && !keep_for_week {
+            remove.push(path);
+        }
+    }
+
+    remove
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+    use crate::error::SkillEdError;
+
+    const TEST_KDF: KdfParams = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+
+    #[tokio::test]
+    async fn test_backup_round_trip() {
+        let source = test_pool().await;
+        db::create_skill(&source, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let dir = std::env::temp_dir().join(format!("backup-test-{}", uuid::Uuid::new_v4()));
+
+        let export = create_backup_with_kdf(&source, &dir, "long passphrase", &TEST_KDF).await.unwrap();
+        assert!(export.file_path.ends_with(".upgem"));
+        let contents = std::fs::read(&export.file_path).unwrap();
+        assert!(!String::from_utf8_lossy(&contents).contains("Piano"));
+
+        let target = test_pool().await;
+        let path = Path::new(&export.file_path);
+        assert!(matches!(
+            restore_backup(&target, path, "wrong passphrase", "replace").await,
+            Err(SkillEdError::AuthError(_))
+        ));
+        restore_backup(&target, path, "long passphrase", "replace").await.unwrap();
+        assert_eq!(
+            build_export(&target).await.unwrap().tables,
+            build_export(&source).await.unwrap().tables
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_backup_due_by_cadence() {
+        let now = Utc::now();
+        let earlier_today = now.to_rfc3339();
+        let week_ago = (now - chrono::Duration::days(7)).to_rfc3339();
+
+        assert!(backup_due("daily", None, now).unwrap());
+        assert!(!backup_due("daily", Some(&earlier_today), now).unwrap());
+        assert!(backup_due("daily", Some(&week_ago), now).unwrap());
+        assert!(!backup_due("weekly", Some(&earlier_today), now).unwrap());
+        assert!(backup_due("weekly", Some(&week_ago), now).unwrap());
+    }
+
+    #[test]
+    fn test_grandfather_father_son_rotation() {
+        // Two backups a day for three weeks, Monday 2024-03-04 through Sunday 2024-03-24
+        let start = at("2024-03-04 09:00");
+        let backups: Vec<(PathBuf, NaiveDateTime)> = (0..21)
+            .flat_map(|day| [0, 8].map(|hours| start + chrono::Duration::days(day) + chrono::Duration::hours(hours)))
+            .map(|taken_at| (PathBuf::from(taken_at.to_string()), taken_at))
+            .collect();
+
+        let removed = backups_to_remove(backups.clone(), 3, 3);
+        let kept: Vec<NaiveDateTime> = backups
+            .iter()
+            .filter(|(path, _)| !removed.contains(path))
+            .map(|(_, taken_at)| *taken_at)
+            .collect();
+
+        // The evening backups of the last three days, plus the newest backup of each
+        // of the two weeks before; the current week is already covered
+        let mut expected = vec![
+            at("2024-03-10 17:00"),
+            at("2024-03-22 17:00"),
+            at("2024-03-23 17:00"),
+            at("2024-03-24 17:00"),
+            at("2024-03-17 17:00"),
+        ];
+        expected.sort();
+        let mut kept_sorted = kept;
+        kept_sorted.sort();
+        assert_eq!(kept_sorted, expected);
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_backup_rotates_old_files() {
+        let pool = test_pool().await;
+        let dir = std::env::temp_dir().join(format!("scheduled-backup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Two stale backups from the same old day, and a file that is not ours
+        std::fs::write(dir.join("skill-ed-backup-20200101-080000.upgem"), b"old").unwrap();
+        std::fs::write(dir.join("skill-ed-backup-20200101-200000.upgem"), b"old").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"keep me").unwrap();
+
+        let schedule = BackupSchedule {
+            enabled: true,
+            directory: Some(dir.to_string_lossy().to_string()),
+            keep_daily: 2,
+            keep_weekly: 0,
+            ..BackupSchedule::default()
+        };
+        let run = run_scheduled_backup_with_kdf(&pool, &dir, &schedule, "long passphrase", &TEST_KDF)
+            .await
+            .unwrap();
+
+        assert_eq!(run.removed.len(), 1);
+        assert!(run.removed[0].ends_with("skill-ed-backup-20200101-080000.upgem"));
+        assert!(dir.join("notes.txt").exists());
+        assert!(Path::new(&run.export.file_path).exists());
+        assert!(last_scheduled_backup(&pool).await.unwrap().is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_backup_rejects_short_passphrase() {
+        let pool = test_pool().await;
+        let dir = std::env::temp_dir();
+        assert!(create_backup_with_kdf(&pool, &dir, "short", &TEST_KDF).await.is_err());
+    }
+}
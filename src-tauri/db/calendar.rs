@@ -0,0 +1,270 @@
+use crate::db::{CalendarFeed, Export, Milestone};
+use crate::error::Result;
+use super::exports::write_export;
+use super::operations::{local_date, parse_timestamp};
+use super::preferences::{get_preference, set_preference};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+/// Default file name of the calendar feed in the app data directory
+pub const CALENDAR_FEED_FILE: &str = "skill-ed.ics";
+
+const CALENDAR_FEED_KEY: &str = "calendar_feed";
+
+// Content lines longer than this many octets are folded (RFC 5545 3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+// ============ CALENDAR OPERATIONS ============
+
+/// Renders milestones as all-day events plus to-dos, and active positive habits
+/// as all-day events repeating at their frequency from the day they were created
+pub async fn build_calendar(pool: &SqlitePool) -> Result<String> {
+    let milestones = sqlx::query_as::<_, Milestone>(
+        "SELECT id, skill_id, title, description, target_date, completion_date, is_completed, created_at
+         FROM milestones ORDER BY target_date ASC, created_at ASC"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let habits = sqlx::query_as::<_, (String, String, Option<String>, String, String)>(
+        "SELECT id, name, description, frequency, created_at FROM habits
+         WHERE deleted_at IS NULL AND is_archived = 0 AND polarity = 'positive'
+         ORDER BY sort_order ASC, created_at DESC"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let stamp = ics_timestamp(Utc::now());
+    let mut calendar = IcsWriter::default();
+    calendar.line("BEGIN:VCALENDAR");
+    calendar.line("VERSION:2.0");
+    calendar.line("PRODID:-//Skill-ED//Skill-ED//EN");
+    calendar.line("CALSCALE:GREGORIAN");
+    calendar.line("X-WR-CALNAME:Skill-ED");
+
+    for milestone in &milestones {
+        let Some(day) = milestone_day(&milestone.target_date) else {
+            continue;
+        };
+        let description = milestone.description.as_deref().unwrap_or("");
+
+        calendar.line("BEGIN:VEVENT");
+        calendar.line(&format!("UID:milestone-{}@skill-ed", milestone.id));
+        calendar.line(&format!("DTSTAMP:{}", stamp));
+        calendar.line(&format!("DTSTART;VALUE=DATE:{}", ics_date(day)));
+        calendar.line(&format!("DTEND;VALUE=DATE:{}", ics_date(day + Duration::days(1))));
+        calendar.line(&format!("SUMMARY:{}", escape_text(&format!("Milestone: {}", milestone.title))));
+        if !description.is_empty() {
+            calendar.line(&format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        calendar.line("TRANSP:TRANSPARENT");
+        calendar.line("END:VEVENT");
+
+        calendar.line("BEGIN:VTODO");
+        calendar.line(&format!("UID:milestone-todo-{}@skill-ed", milestone.id));
+        calendar.line(&format!("DTSTAMP:{}", stamp));
+        calendar.line(&format!("DUE;VALUE=DATE:{}", ics_date(day)));
+        calendar.line(&format!("SUMMARY:{}", escape_text(&milestone.title)));
+        if !description.is_empty() {
+            calendar.line(&format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if milestone.is_completed {
+            calendar.line("STATUS:COMPLETED");
+            if let Some(completed_at) = milestone.completion_date.as_deref().and_then(|value| parse_timestamp(value).ok()) {
+                calendar.line(&format!("COMPLETED:{}", ics_timestamp(completed_at)));
+            }
+        } else {
+            calendar.line("STATUS:NEEDS-ACTION");
+        }
+        calendar.line("END:VTODO");
+    }
+
+    for (id, name, description, frequency, created_at) in &habits {
+        let rule = match frequency.as_str() {
+            "weekly" => "WEEKLY",
+            "monthly" => "MONTHLY",
+            _ => "DAILY",
+        };
+        let start = local_date(created_at)?;
+
+        calendar.line("BEGIN:VEVENT");
+        calendar.line(&format!("UID:habit-{}@skill-ed", id));
+        calendar.line(&format!("DTSTAMP:{}", stamp));
+        calendar.line(&format!("DTSTART;VALUE=DATE:{}", ics_date(start)));
+        calendar.line(&format!("DTEND;VALUE=DATE:{}", ics_date(start + Duration::days(1))));
+        calendar.line(&format!("RRULE:FREQ={}", rule));
+        calendar.line(&format!("SUMMARY:{}", escape_text(&format!("Habit: {}", name))));
+        if let Some(description) = description.as_deref().filter(|value| !value.is_empty()) {
+            calendar.line(&format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        calendar.line("TRANSP:TRANSPARENT");
+        calendar.line("END:VEVENT");
+    }
+
+    calendar.line("END:VCALENDAR");
+    Ok(calendar.finish())
+}
+
+/// Writes the calendar to the exports directory and records it
+pub async fn export_calendar(pool: &SqlitePool, data_dir: &Path) -> Result<Export> {
+    let calendar = build_calendar(pool).await?;
+    let file_name = format!("skill-ed-calendar-{}.ics", Utc::now().format("%Y%m%d-%H%M%S"));
+
+    write_export(pool, data_dir, "ics", &file_name, calendar.as_bytes()).await
+}
+
+pub async fn get_calendar_feed(pool: &SqlitePool) -> Result<CalendarFeed> {
+    Ok(get_preference(pool, CALENDAR_FEED_KEY).await?.unwrap_or_default())
+}
+
+/// Saves the feed settings and, when enabled, writes the file right away
+pub async fn update_calendar_feed(pool: &SqlitePool, data_dir: &Path, feed: CalendarFeed) -> Result<CalendarFeed> {
+    if let Some(path) = &feed.path {
+        if !Path::new(path).is_absolute() {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Calendar file path must be an absolute path".to_string(),
+            ));
+        }
+    }
+
+    set_preference(pool, CALENDAR_FEED_KEY, &feed).await?;
+    if feed.enabled {
+        write_calendar_feed(pool, data_dir, &feed).await?;
+    }
+    Ok(feed)
+}
+
+pub fn calendar_feed_path(feed: &CalendarFeed, data_dir: &Path) -> PathBuf {
+    match &feed.path {
+        Some(path) => PathBuf::from(path),
+        None => data_dir.join(CALENDAR_FEED_FILE),
+    }
+}
+
+/// Regenerates the feed file. It is written next to the target and renamed over
+/// it, so a calendar app polling the file never reads half of it.
+pub async fn write_calendar_feed(pool: &SqlitePool, data_dir: &Path, feed: &CalendarFeed) -> Result<PathBuf> {
+    let path = calendar_feed_path(feed, data_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let calendar = build_calendar(pool).await?;
+    let temp_path = path.with_extension("ics.tmp");
+    std::fs::write(&temp_path, calendar)?;
+    std::fs::rename(&temp_path, &path)?;
+
+    Ok(path)
+}
+
+/// Builds CRLF-terminated content lines, folding long ones
+#[derive(Default)]
+struct IcsWriter {
+    out: String,
+}
+
+impl IcsWriter {
+    fn line(&mut self, line: &str) {
+        let mut octets = 0;
+        for c in line.chars() {
+            // Continuation lines start with a space, which counts toward their length
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                self.out.push_str("\r\n ");
+                octets = 1;
+            }
+            self.out.push(c);
+            octets += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Escapes a TEXT property value (RFC 5545 3.3.11)
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+fn milestone_day(target_date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(target_date, "%Y-%m-%d").ok()
+}
+
+fn ics_date(day: NaiveDate) -> String {
+    day.format("%Y%m%d").to_string()
+}
+
+fn ics_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape_text("a,b;c\\d\nnext"), "a\\,b\\;c\\\\d\\nnext");
+
+        let mut writer = IcsWriter::default();
+        writer.line(&format!("SUMMARY:{}", "é".repeat(50)));
+        let out = writer.finish();
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(lines.iter().map(|line| line.trim_start()).collect::<String>(), format!("SUMMARY:{}", "é".repeat(50)));
+    }
+
+    #[tokio::test]
+    async fn test_calendar_contains_milestones_and_habits() {
+        let pool = test_pool().await;
+        db::create_milestone(&pool, "Recital, spring".to_string(), "2030-06-01".to_string(), None, None)
+            .await
+            .unwrap();
+        let habit = db::create_habit(&pool, "Scales".to_string(), "weekly".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::create_habit(&pool, "Snacking".to_string(), "daily".to_string(), "negative".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let calendar = build_calendar(&pool).await.unwrap();
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20300601\r\nDTEND;VALUE=DATE:20300602\r\n"));
+        assert!(calendar.contains("SUMMARY:Milestone: Recital\\, spring\r\n"));
+        assert!(calendar.contains("DUE;VALUE=DATE:20300601\r\n"));
+        assert!(calendar.contains(&format!("UID:habit-{}@skill-ed\r\n", habit.id)));
+        assert!(calendar.contains("RRULE:FREQ=WEEKLY\r\n"));
+        assert!(!calendar.contains("Snacking"));
+    }
+
+    #[tokio::test]
+    async fn test_feed_file_is_written_when_enabled() {
+        let pool = test_pool().await;
+        let dir = std::env::temp_dir().join(format!("calendar-test-{}", uuid::Uuid::new_v4()));
+
+        update_calendar_feed(&pool, &dir, CalendarFeed { enabled: true, path: None }).await.unwrap();
+        let contents = std::fs::read_to_string(dir.join(CALENDAR_FEED_FILE)).unwrap();
+        assert!(contents.contains("BEGIN:VCALENDAR"));
+        assert!(!dir.join("skill-ed.ics.tmp").exists());
+
+        let relative = CalendarFeed { enabled: true, path: Some("calendar.ics".to_string()) };
+        assert!(update_calendar_feed(&pool, &dir, relative).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
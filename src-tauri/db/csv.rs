@@ -0,0 +1,504 @@
+use crate::db::{CsvFilter, Export, Habit};
+use crate::error::Result;
+use super::activity::parse_bound;
+use super::exports::write_export;
+use super::operations::{parse_timestamp, with_clean_streak, HABIT_COLUMNS};
+use chrono::{Local, Utc};
+use sqlx::SqlitePool;
+use std::path::Path;
+
+/// Entities `export_csv` accepts; "timeseries" is the combined long-format export
+pub const CSV_ENTITIES: &[&str] = &["skills", "skill_progress", "habits", "habit_completions", "milestones", "timeseries"];
+
+const SKILL_HEADERS: &[&str] = &[
+    "id", "name", "category", "description", "proficiency_level", "total_hours",
+    "level_mode", "parent_id", "is_archived", "created_at", "updated_at",
+];
+const SKILL_PROGRESS_HEADERS: &[&str] = &["id", "skill_id", "skill_name", "proficiency_level", "hours_practiced", "recorded_at"];
+const HABIT_HEADERS: &[&str] = &[
+    "id", "name", "description", "frequency", "polarity", "streak_count",
+    "best_streak", "is_archived", "created_at", "last_completed",
+];
+const HABIT_COMPLETION_HEADERS: &[&str] = &["id", "habit_id", "habit_name", "completion_type", "completed_at"];
+const MILESTONE_HEADERS: &[&str] = &[
+    "id", "skill_id", "skill_name", "title", "description", "target_date",
+    "is_completed", "completion_date", "created_at",
+];
+const TIMESERIES_HEADERS: &[&str] = &["date", "entity_type", "entity_id", "entity_name", "metric", "value"];
+
+type SkillRow = (String, String, String, Option<String>, i32, f64, String, Option<String>, bool, String, String);
+type MilestoneRow = (String, Option<String>, Option<String>, String, Option<String>, String, bool, Option<String>, String);
+
+// ============ CSV EXPORT OPERATIONS ============
+
+/// Renders one entity as CSV (RFC 4180: CRLF line endings, quoted fields where
+/// needed). Timestamps are written as ISO 8601 local time without an offset.
+pub async fn build_csv(pool: &SqlitePool, entity: &str, filter: &CsvFilter) -> Result<String> {
+    let from = filter.from.as_deref().map(|value| parse_bound(value, false)).transpose()?;
+    let to = filter.to.as_deref().map(|value| parse_bound(value, true)).transpose()?;
+
+    let (headers, rows) = match entity {
+        "skills" => (SKILL_HEADERS, skill_rows(pool, filter, &from, &to).await?),
+        "skill_progress" => (SKILL_PROGRESS_HEADERS, skill_progress_rows(pool, filter, &from, &to).await?),
+        "habits" => (HABIT_HEADERS, habit_rows(pool, filter, &from, &to).await?),
+        "habit_completions" => (HABIT_COMPLETION_HEADERS, habit_completion_rows(pool, filter, &from, &to).await?),
+        "milestones" => (MILESTONE_HEADERS, milestone_rows(pool, filter).await?),
+        "timeseries" => (TIMESERIES_HEADERS, timeseries_rows(pool, filter, &from, &to).await?),
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(format!(
+                "Invalid CSV entity. Must be one of: {}",
+                CSV_ENTITIES.join(", ")
+            )))
+        }
+    };
+
+    let mut out = String::new();
+    push_record(&mut out, headers.iter().map(|header| header.to_string()));
+    for row in rows {
+        push_record(&mut out, row);
+    }
+    Ok(out)
+}
+
+/// Writes the CSV for `entity` to the exports directory and records it
+pub async fn export_csv(pool: &SqlitePool, data_dir: &Path, entity: &str, filter: &CsvFilter) -> Result<Export> {
+    let csv = build_csv(pool, entity, filter).await?;
+    let file_name = format!("skill-ed-{}-{}.csv", entity.replace('_', "-"), Utc::now().format("%Y%m%d-%H%M%S"));
+
+    write_export(pool, data_dir, &format!("csv_{}", entity), &file_name, csv.as_bytes()).await
+}
+
+async fn skill_rows(pool: &SqlitePool, filter: &CsvFilter, from: &Option<String>, to: &Option<String>) -> Result<Vec<Vec<String>>> {
+    let skills = sqlx::query_as::<_, SkillRow>(
+        "SELECT id, name, category, description, proficiency_level, total_hours, level_mode, parent_id,
+                is_archived, created_at, updated_at
+         FROM skills
+         WHERE deleted_at IS NULL AND (?1 OR is_archived = 0)
+           AND (?2 IS NULL OR created_at >= ?2) AND (?3 IS NULL OR created_at < ?3)
+           AND (?4 IS NULL OR id = ?4)
+         ORDER BY sort_order ASC, created_at ASC"
+    )
+    .bind(filter.include_archived)
+    .bind(from)
+    .bind(to)
+    .bind(&filter.skill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    skills
+        .into_iter()
+        .map(|(id, name, category, description, level, hours, level_mode, parent_id, archived, created_at, updated_at)| {
+            Ok(vec![
+                id,
+                text(&name),
+                text(&category),
+                text(description.as_deref().unwrap_or("")),
+                level.to_string(),
+                number(hours),
+                level_mode,
+                parent_id.unwrap_or_default(),
+                archived.to_string(),
+                local_timestamp(&created_at)?,
+                local_timestamp(&updated_at)?,
+            ])
+        })
+        .collect()
+}
+
+async fn skill_progress_rows(pool: &SqlitePool, filter: &CsvFilter, from: &Option<String>, to: &Option<String>) -> Result<Vec<Vec<String>>> {
+    let entries = sqlx::query_as::<_, (String, String, String, i32, f64, String)>(
+        "SELECT sp.id, sp.skill_id, s.name, sp.proficiency_level, sp.hours_practiced, sp.recorded_at
+         FROM skill_progress sp
+         JOIN skills s ON s.id = sp.skill_id
+         WHERE s.deleted_at IS NULL AND (?1 OR s.is_archived = 0)
+           AND (?2 IS NULL OR sp.recorded_at >= ?2) AND (?3 IS NULL OR sp.recorded_at < ?3)
+           AND (?4 IS NULL OR sp.skill_id = ?4)
+         ORDER BY sp.recorded_at ASC, sp.id ASC"
+    )
+    .bind(filter.include_archived)
+    .bind(from)
+    .bind(to)
+    .bind(&filter.skill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    entries
+        .into_iter()
+        .map(|(id, skill_id, skill_name, level, hours, recorded_at)| {
+            Ok(vec![id, skill_id, text(&skill_name), level.to_string(), number(hours), local_timestamp(&recorded_at)?])
+        })
+        .collect()
+}
+
+async fn habit_rows(pool: &SqlitePool, filter: &CsvFilter, from: &Option<String>, to: &Option<String>) -> Result<Vec<Vec<String>>> {
+    let query_str = format!(
+        "SELECT {} FROM habits
+         WHERE deleted_at IS NULL AND (?1 OR is_archived = 0)
+           AND (?2 IS NULL OR created_at >= ?2) AND (?3 IS NULL OR created_at < ?3)
+           AND (?4 IS NULL OR id = ?4)
+         ORDER BY sort_order ASC, created_at ASC",
+        HABIT_COLUMNS
+    );
+    let habits = sqlx::query_as::<_, Habit>(&query_str)
+    .bind(filter.include_archived)
+    .bind(from)
+    .bind(to)
+    .bind(&filter.habit_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    habits
+        .into_iter()
+        .map(|habit| {
+            // Negative habits report their running clean streak, as the habit list does
+            let habit = with_clean_streak(habit)?;
+            Ok(vec![
+                habit.id,
+                text(&habit.name),
+                text(habit.description.as_deref().unwrap_or("")),
+                habit.frequency,
+                habit.polarity,
+                habit.streak_count.to_string(),
+                habit.best_streak.to_string(),
+                habit.is_archived.to_string(),
+                local_timestamp(&habit.created_at)?,
+                habit.last_completed.as_deref().map(local_timestamp).transpose()?.unwrap_or_default(),
+            ])
+        })
+        .collect()
+}
+
+async fn habit_completion_rows(pool: &SqlitePool, filter: &CsvFilter, from: &Option<String>, to: &Option<String>) -> Result<Vec<Vec<String>>> {
+    let completions = sqlx::query_as::<_, (String, String, String, String, String)>(
+        "SELECT hc.id, hc.habit_id, h.name, hc.completion_type, hc.completed_at
+         FROM habit_completions hc
+         JOIN habits h ON h.id = hc.habit_id
+         WHERE h.deleted_at IS NULL AND (?1 OR h.is_archived = 0)
+           AND (?2 IS NULL OR hc.completed_at >= ?2) AND (?3 IS NULL OR hc.completed_at < ?3)
+           AND (?4 IS NULL OR hc.habit_id = ?4)
+         ORDER BY hc.completed_at ASC, hc.id ASC"
+    )
+    .bind(filter.include_archived)
+    .bind(from)
+    .bind(to)
+    .bind(&filter.habit_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    completions
+        .into_iter()
+        .map(|(id, habit_id, habit_name, completion_type, completed_at)| {
+            Ok(vec![id, habit_id, text(&habit_name), completion_type, local_timestamp(&completed_at)?])
+        })
+        .collect()
+}
+
+/// Milestones are filtered on their target date, compared as a calendar day
+async fn milestone_rows(pool: &SqlitePool, filter: &CsvFilter) -> Result<Vec<Vec<String>>> {
+    let milestones = sqlx::query_as::<_, MilestoneRow>(
+        "SELECT m.id, m.skill_id, s.name, m.title, m.description, m.target_date, m.is_completed,
+                m.completion_date, m.created_at
+         FROM milestones m
+         LEFT JOIN skills s ON s.id = m.skill_id
+         WHERE (?1 IS NULL OR m.target_date >= ?1)
+           AND (?2 IS NULL OR m.target_date <= ?2)
+           AND (?3 IS NULL OR m.skill_id = ?3)
+         ORDER BY m.target_date ASC, m.created_at ASC"
+    )
+    .bind(filter.from.as_deref().map(date_part))
+    .bind(filter.to.as_deref().map(date_part))
+    .bind(&filter.skill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    milestones
+        .into_iter()
+        .map(|(id, skill_id, skill_name, title, description, target_date, completed, completion_date, created_at)| {
+            Ok(vec![
+                id,
+                skill_id.unwrap_or_default(),
+                text(skill_name.as_deref().unwrap_or("")),
+                text(&title),
+                text(description.as_deref().unwrap_or("")),
+                target_date,
+                completed.to_string(),
+                completion_date.as_deref().map(local_timestamp).transpose()?.unwrap_or_default(),
+                local_timestamp(&created_at)?,
+            ])
+        })
+        .collect()
+}
+
+/// One row per local day, entity and metric: practice minutes and the day's
+/// highest proficiency per skill, completions and relapses per habit. A skill
+/// filter alone leaves out habit series, and a habit filter alone skill series.
+async fn timeseries_rows(pool: &SqlitePool, filter: &CsvFilter, from: &Option<String>, to: &Option<String>) -> Result<Vec<Vec<String>>> {
+    let points = sqlx::query_as::<_, (String, String, String, String, String, f64)>(
+        "SELECT date(ps.started_at, 'localtime') AS day, 'skill' AS entity_type, s.id AS entity_id, s.name,
+                'practice_minutes' AS metric, SUM(ps.duration_seconds) / 60.0
+         FROM practice_sessions ps
+         JOIN skills s ON s.id = ps.skill_id
+         WHERE ps.ended_at IS NOT NULL AND s.deleted_at IS NULL AND (?1 OR s.is_archived = 0)
+           AND (?2 IS NULL OR ps.started_at >= ?2) AND (?3 IS NULL OR ps.started_at < ?3)
+           AND (?4 IS NULL OR s.id = ?4) AND (?5 IS NULL OR ?4 IS NOT NULL)
+         GROUP BY day, s.id
+         UNION ALL
+         SELECT date(sp.recorded_at, 'localtime') AS day, 'skill', s.id, s.name,
+                'proficiency_level', CAST(MAX(sp.proficiency_level) AS REAL)
+         FROM skill_progress sp
+         JOIN skills s ON s.id = sp.skill_id
+         WHERE s.deleted_at IS NULL AND (?1 OR s.is_archived = 0)
+           AND (?2 IS NULL OR sp.recorded_at >= ?2) AND (?3 IS NULL OR sp.recorded_at < ?3)
+           AND (?4 IS NULL OR s.id = ?4) AND (?5 IS NULL OR ?4 IS NOT NULL)
+         GROUP BY day, s.id
+         UNION ALL
+         SELECT date(hc.completed_at, 'localtime') AS day, 'habit', h.id, h.name,
+                CASE hc.completion_type WHEN 'relapse' THEN 'relapses' ELSE 'completions' END,
+                CAST(COUNT(*) AS REAL)
+         FROM habit_completions hc
+         JOIN habits h ON h.id = hc.habit_id
+         WHERE h.deleted_at IS NULL AND (?1 OR h.is_archived = 0)
+           AND (?2 IS NULL OR hc.completed_at >= ?2) AND (?3 IS NULL OR hc.completed_at < ?3)
+           AND (?5 IS NULL OR h.id = ?5) AND (?4 IS NULL OR ?5 IS NOT NULL)
+         GROUP BY day, h.id, hc.completion_type
+         ORDER BY day ASC, entity_type DESC, entity_id ASC, metric ASC"
+    )
+    .bind(filter.include_archived)
+    .bind(from)
+    .bind(to)
+    .bind(&filter.skill_id)
+    .bind(&filter.habit_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(points
+        .into_iter()
+        .map(|(day, entity_type, entity_id, name, metric, value)| {
+            vec![day, entity_type, entity_id, text(&name), metric, number(value)]
+        })
+        .collect())
+}
+
+/// Splits CSV text into records, handling quoted fields, doubled quotes,
+/// embedded line breaks, CRLF or LF endings and a leading byte order mark.
+/// Blank lines are dropped.
+pub(super) fn parse_csv(input: &str) -> Result<Vec<Vec<String>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|value| !value.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "CSV has an unterminated quoted field".to_string(),
+        ));
+    }
+
+    record.push(field);
+    if record.iter().any(|value| !value.is_empty()) {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn push_record(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    let record: Vec<String> = fields.into_iter().map(|field| escape_field(&field)).collect();
+    out.push_str(&record.join(","));
+    out.push_str("\r\n");
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break
+fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Free text typed by the user. A leading apostrophe keeps spreadsheets from
+/// evaluating it as a formula.
+fn text(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Whole numbers without a fractional part, everything else as is
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+fn local_timestamp(value: &str) -> Result<String> {
+    Ok(parse_timestamp(value)?.with_timezone(&Local).format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+/// A stored date or timestamp as a local calendar day
+fn local_day(value: &str) -> String {
+    match parse_timestamp(value) {
+        Ok(timestamp) => timestamp.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+        Err(_) => date_part(value).to_string(),
+    }
+}
+
+fn date_part(value: &str) -> &str {
+    value.get(..10).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape_field("plain"), "plain");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(text("=SUM(A1:A9)"), "'=SUM(A1:A9)");
+        assert_eq!(number(3.0), "3");
+        assert_eq!(number(1.25), "1.25");
+    }
+
+    #[test]
+    fn test_parse_csv_round_trips_escaping() {
+        let mut out = String::new();
+        push_record(&mut out, ["a,b".to_string(), "say \"hi\"".to_string(), "two\nlines".to_string()]);
+        push_record(&mut out, ["".to_string(), "x".to_string(), "".to_string()]);
+
+        let records = parse_csv(&format!("\u{feff}{}\n", out)).unwrap();
+        assert_eq!(records, vec![vec!["a,b", "say \"hi\"", "two\nlines"], vec!["", "x", ""]]);
+        assert!(parse_csv("\"open").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_skills_csv_has_stable_headers() {
+        let pool = test_pool().await;
+        db::create_skill(&pool, "Piano, Jazz".to_string(), "Music".to_string(), Some("Voicings \"shell\"".to_string()))
+            .await
+            .unwrap();
+
+        let csv = build_csv(&pool, "skills", &CsvFilter::default()).await.unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], SKILL_HEADERS.join(","));
+        assert!(lines[1].contains(",\"Piano, Jazz\",Music,\"Voicings \"\"shell\"\"\",0,0,manual,,false,"));
+        assert_eq!(lines.len(), 3); // header, one row, trailing empty line
+
+        assert!(build_csv(&pool, "settings", &CsvFilter::default()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_habits_csv_reports_clean_streak() {
+        let pool = test_pool().await;
+        let habit = db::create_habit(&pool, "Smoking".to_string(), "daily".to_string(), "negative".to_string(), None, None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE habits SET created_at = ? WHERE id = ?")
+            .bind((Utc::now() - chrono::Duration::days(3)).to_rfc3339())
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let csv = build_csv(&pool, "habits", &CsvFilter::default()).await.unwrap();
+        let row: Vec<&str> = csv.split("\r\n").nth(1).unwrap().split(',').collect();
+
+        assert_eq!((row[5], row[6]), ("3", "3"));
+    }
+
+    #[tokio::test]
+    async fn test_timeseries_is_long_format() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = db::create_habit(&pool, "Scales".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        for (id, started_at, ended_at, seconds) in [
+            ("p1", "2024-03-04T12:00:00+00:00", "2024-03-04T12:30:00+00:00", 1800),
+            ("p2", "2024-03-04T13:00:00+00:00", "2024-03-04T13:15:00+00:00", 900),
+        ] {
+            sqlx::query(
+                "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds) VALUES (?, ?, ?, ?, ?)"
+            )
+            .bind(id)
+            .bind(&skill.id)
+            .bind(started_at)
+            .bind(ended_at)
+            .bind(seconds)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at) VALUES ('c1', ?, '2024-03-04T12:00:00+00:00')")
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let filter = CsvFilter { from: Some("2024-03-01".to_string()), to: Some("2024-03-31".to_string()), ..CsvFilter::default() };
+        let csv = build_csv(&pool, "timeseries", &filter).await.unwrap();
+        let day = local_day("2024-03-04T12:00:00+00:00");
+
+        assert_eq!(
+            csv,
+            format!(
+                "date,entity_type,entity_id,entity_name,metric,value\r\n\
+                 {day},skill,{skill},Piano,practice_minutes,45\r\n\
+                 {day},habit,{habit},Scales,completions,1\r\n",
+                day = day,
+                skill = skill.id,
+                habit = habit.id
+            )
+        );
+
+        let skill_only = CsvFilter { skill_id: Some(skill.id.clone()), ..filter };
+        let csv = build_csv(&pool, "timeseries", &skill_only).await.unwrap();
+        assert!(!csv.contains("completions"));
+    }
+}
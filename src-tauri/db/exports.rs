@@ -0,0 +1,62 @@
+use crate::db::Export;
+use crate::error::Result;
+use sqlx::{Executor, Sqlite, SqlitePool};
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of the app data directory that exports are written to
+pub const EXPORTS_DIR: &str = "exports";
+
+// ============ EXPORT HISTORY OPERATIONS ============
+
+/// Writes `contents` to `exports/<file_name>` under `data_dir` and records it
+pub async fn write_export(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    export_type: &str,
+    file_name: &str,
+    contents: &[u8],
+) -> Result<Export> {
+    let path = exports_dir(data_dir)?.join(file_name);
+    std::fs::write(&path, contents)?;
+
+    record_export(pool, export_type, &path).await
+}
+
+pub async fn record_export<'e, E>(executor: E, export_type: &str, path: &Path) -> Result<Export>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let export = Export::new(export_type, path.to_string_lossy().to_string());
+
+    sqlx::query("INSERT INTO exports (id, export_type, created_at, file_path) VALUES (?, ?, ?, ?)")
+        .bind(&export.id)
+        .bind(&export.export_type)
+        .bind(&export.created_at)
+        .bind(&export.file_path)
+        .execute(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(export)
+}
+
+/// Export history, newest first
+pub async fn get_exports(pool: &SqlitePool) -> Result<Vec<Export>> {
+    let exports = sqlx::query_as::<_, Export>(
+        "SELECT id, export_type, created_at, file_path FROM exports ORDER BY created_at DESC"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(exports)
+}
+
+/// The exports directory under `data_dir`, created if missing
+pub fn exports_dir(data_dir: &Path) -> Result<PathBuf> {
+    let dir = data_dir.join(EXPORTS_DIR);
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
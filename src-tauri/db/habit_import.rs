@@ -0,0 +1,707 @@
+use crate::db::{Habit, HabitImportPreview, HabitImportReport};
+use crate::error::Result;
+use super::activity::{notify_changes, record_activity};
+use super::csv::parse_csv;
+use super::operations::{get_habit, local_date, next_streak, parse_timestamp, top_sort_order};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Sources `import_habits` understands
+pub const HABIT_IMPORT_SOURCES: &[&str] = &["loop_csv", "loop_sqlite", "habitica", "generic_csv"];
+
+/// A habit and its history as read from another app, before it is matched
+/// against existing habits
+#[derive(Debug, Clone, PartialEq)]
+struct ImportedHabit {
+    name: String,
+    description: Option<String>,
+    frequency: String,
+    polarity: String,
+    entries: Vec<ImportedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ImportedEntry {
+    completed_at: String,
+    completion_type: &'static str,
+}
+
+impl ImportedHabit {
+    fn new(name: &str, frequency: &str, polarity: &str) -> Self {
+        Self {
+            name: name.trim().to_string(),
+            description: None,
+            frequency: frequency.to_string(),
+            polarity: polarity.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, completed_at: DateTime<Utc>) {
+        let completion_type = if self.polarity == "negative" { "relapse" } else { "completion" };
+        self.entries.push(ImportedEntry {
+            completed_at: completed_at.to_rfc3339(),
+            completion_type,
+        });
+    }
+}
+
+// ============ HABIT IMPORT OPERATIONS ============
+
+/// Imports habits and their history from another app's export at `path`.
+/// Habits are matched to existing ones by name (ignoring case); entries on a day
+/// that already has one of the same kind are skipped. Imported history does not
+/// award XP. With `dry_run` nothing is written and the report is a preview.
+pub async fn import_habits(pool: &SqlitePool, source: &str, path: &Path, dry_run: bool) -> Result<HabitImportReport> {
+    let (habits, warnings) = match source {
+        "loop_csv" => parse_loop_csv(path)?,
+        "loop_sqlite" => read_loop_sqlite(path).await?,
+        "habitica" => parse_habitica(&std::fs::read_to_string(path)?)?,
+        "generic_csv" => parse_generic_csv(&std::fs::read_to_string(path)?)?,
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(format!(
+                "Invalid import source. Must be one of: {}",
+                HABIT_IMPORT_SOURCES.join(", ")
+            )))
+        }
+    };
+
+    apply_import(pool, source, merge_by_name(habits), warnings, dry_run).await
+}
+
+async fn apply_import(
+    pool: &SqlitePool,
+    source: &str,
+    habits: Vec<ImportedHabit>,
+    warnings: Vec<String>,
+    dry_run: bool,
+) -> Result<HabitImportReport> {
+    let mut sort_order = top_sort_order(pool, "habits").await?;
+    let mut report = HabitImportReport {
+        source: source.to_string(),
+        dry_run,
+        habits: Vec::new(),
+        habits_created: 0,
+        entries_imported: 0,
+        duplicates_skipped: 0,
+        warnings: Vec::new(),
+    };
+
+    // A dry run goes through the same steps and rolls back
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    for imported in habits {
+        let existing = sqlx::query_scalar::<_, String>(
+            "SELECT id FROM habits WHERE name = ? COLLATE NOCASE AND deleted_at IS NULL"
+        )
+        .bind(&imported.name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        let (habit, is_new) = match existing {
+            Some(id) => {
+                let habit = get_habit(&mut *tx, &id)
+                    .await?
+                    .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", id)))?;
+                (habit, false)
+            }
+            None => {
+                let habit = insert_habit(&mut tx, &imported, sort_order).await?;
+                sort_order -= 1;
+                report.habits_created += 1;
+                (habit, true)
+            }
+        };
+
+        let mut seen: HashSet<(NaiveDate, String)> = HashSet::new();
+        let existing_entries = sqlx::query_as::<_, (String, String)>(
+            "SELECT completed_at, completion_type FROM habit_completions WHERE habit_id = ?"
+        )
+        .bind(&habit.id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+        for (completed_at, completion_type) in existing_entries {
+            seen.insert((local_date(&completed_at)?, completion_type));
+        }
+
+        let mut preview = HabitImportPreview {
+            habit_name: habit.name.clone(),
+            habit_id: (!is_new).then(|| habit.id.clone()),
+            frequency: habit.frequency.clone(),
+            polarity: habit.polarity.clone(),
+            entries_found: imported.entries.len() as i64,
+            entries_new: 0,
+            duplicates: 0,
+        };
+
+        for entry in &imported.entries {
+            // Entries follow the matched habit's polarity
+            let completion_type = if habit.polarity == "negative" { "relapse" } else { entry.completion_type };
+            if !seen.insert((local_date(&entry.completed_at)?, completion_type.to_string())) {
+                preview.duplicates += 1;
+                continue;
+            }
+
+            sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at, completion_type) VALUES (?, ?, ?, ?)")
+                .bind(uuid::Uuid::new_v4().to_string())
+                .bind(&habit.id)
+                .bind(&entry.completed_at)
+                .bind(completion_type)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+            preview.entries_new += 1;
+        }
+
+        if preview.entries_new > 0 {
+            recompute_streaks(&mut tx, &habit).await?;
+            let habit = get_habit(&mut *tx, &habit.id)
+                .await?
+                .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit.id)))?;
+            record_activity(
+                &mut tx,
+                "habit_imported",
+                "habit",
+                &habit.id,
+                format!("Imported {} entries for habit {}", preview.entries_new, habit.name),
+                &habit,
+            )
+            .await?;
+        }
+
+        report.entries_imported += preview.entries_new;
+        report.duplicates_skipped += preview.duplicates;
+        report.habits.push(preview);
+    }
+
+    if dry_run {
+        tx.rollback().await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    } else {
+        tx.commit().await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+        notify_changes();
+    }
+
+    report.warnings = warnings;
+    Ok(report)
+}
+
+async fn insert_habit(conn: &mut SqliteConnection, imported: &ImportedHabit, sort_order: i32) -> Result<Habit> {
+    let mut habit = Habit::new(imported.name.clone(), imported.frequency.clone());
+    habit.polarity = imported.polarity.clone();
+    habit.description = imported.description.clone();
+    habit.sort_order = sort_order;
+
+    // Backdated to the first imported entry so clean streaks and reviews cover the history
+    if let Some(first) = imported.entries.iter().map(|entry| entry.completed_at.as_str()).min() {
+        if first < habit.created_at.as_str() {
+            habit.created_at = first.to_string();
+        }
+    }
+
+    sqlx::query(
+        "INSERT INTO habits (id, name, description, frequency, polarity, streak_count, best_streak, created_at, last_completed, sort_order)
+         VALUES (?, ?, ?, ?, ?, 0, 0, ?, NULL, ?)"
+    )
+    .bind(&habit.id)
+    .bind(&habit.name)
+    .bind(&habit.description)
+    .bind(&habit.frequency)
+    .bind(&habit.polarity)
+    .bind(&habit.created_at)
+    .bind(habit.sort_order)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::from_name_write(e, "habits", "habit", &habit.name))?;
+
+    record_activity(conn, "habit_created", "habit", &habit.id, format!("Created habit {}", habit.name), &habit).await?;
+
+    Ok(habit)
+}
+
+/// Rebuilds a habit's streak fields from its whole history, since imported
+/// entries can land before the latest existing one
+async fn recompute_streaks(conn: &mut SqliteConnection, habit: &Habit) -> Result<()> {
+    let entries = sqlx::query_scalar::<_, String>(
+        "SELECT completed_at FROM habit_completions WHERE habit_id = ? AND completion_type = ? ORDER BY completed_at ASC"
+    )
+    .bind(&habit.id)
+    .bind(if habit.polarity == "negative" { "relapse" } else { "completion" })
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let (streak, best) = if habit.polarity == "negative" {
+        // Longest clean stretch between relapses; the running one is counted live
+        let mut clean_since = parse_timestamp(&habit.created_at)?;
+        let mut best = 0;
+        for completed_at in &entries {
+            let relapse = parse_timestamp(completed_at)?;
+            best = best.max((relapse - clean_since).num_days().max(0));
+            clean_since = relapse;
+        }
+        (0, best)
+    } else {
+        let mut streak = 0;
+        let mut best = 0;
+        let mut last = None;
+        for completed_at in &entries {
+            let day = local_date(completed_at)?;
+            streak = next_streak(&habit.frequency, last, day, streak);
+            best = best.max(streak);
+            last = Some(day);
+        }
+        (streak, best as i64)
+    };
+
+    sqlx::query(
+        "UPDATE habits SET last_completed = ?, streak_count = ?, best_streak = MAX(best_streak, ?) WHERE id = ?"
+    )
+    .bind(entries.last())
+    .bind(streak)
+    .bind(best)
+    .bind(&habit.id)
+    .execute(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Combines habits that share a name (ignoring case), keeping the first one's settings
+fn merge_by_name(habits: Vec<ImportedHabit>) -> Vec<ImportedHabit> {
+    let mut merged: BTreeMap<String, ImportedHabit> = BTreeMap::new();
+    for habit in habits.into_iter().filter(|habit| !habit.name.is_empty()) {
+        match merged.get_mut(&habit.name.to_lowercase()) {
+            Some(existing) => existing.entries.extend(habit.entries),
+            None => {
+                merged.insert(habit.name.to_lowercase(), habit);
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+// ============ SOURCE PARSERS ============
+
+/// Loop Habit Tracker's CSV export: `Checkmarks.csv` has a Date column and one
+/// column per habit, and `Habits.csv` alongside it describes each habit. `path`
+/// is the unzipped export directory or the Checkmarks.csv file. Only manual
+/// checkmarks (2) are imported; automatic ones (1) just fill in the frequency.
+fn parse_loop_csv(path: &Path) -> Result<(Vec<ImportedHabit>, Vec<String>)> {
+    let directory = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+    let checkmarks_path = if path.is_dir() { path.join("Checkmarks.csv") } else { path.to_path_buf() };
+    let checkmarks = std::fs::read_to_string(checkmarks_path)?;
+    let habits_csv = std::fs::read_to_string(directory.join("Habits.csv")).ok();
+
+    parse_loop_checkmarks(&checkmarks, habits_csv.as_deref())
+}
+
+fn parse_loop_checkmarks(checkmarks: &str, habits_csv: Option<&str>) -> Result<(Vec<ImportedHabit>, Vec<String>)> {
+    let mut warnings = Vec::new();
+    let records = parse_csv(checkmarks)?;
+    let Some((header, rows)) = records.split_first() else {
+        return Ok((Vec::new(), warnings));
+    };
+
+    let mut habits: Vec<ImportedHabit> = header
+        .iter()
+        .skip(1)
+        .map(|name| ImportedHabit::new(name, "daily", "positive"))
+        .collect();
+
+    if let Some(habits_csv) = habits_csv {
+        apply_loop_habit_details(&mut habits, habits_csv)?;
+    }
+
+    for row in rows {
+        let Some(date) = row.first().and_then(|value| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()) else {
+            warnings.push(format!("Skipped Checkmarks.csv row with invalid date '{}'", row.first().map(String::as_str).unwrap_or("")));
+            continue;
+        };
+
+        for (habit, value) in habits.iter_mut().zip(row.iter().skip(1)) {
+            if value.trim() == "2" {
+                habit.push(local_noon(date));
+            }
+        }
+    }
+
+    Ok((habits, warnings))
+}
+
+/// Descriptions and frequencies from Loop's Habits.csv, matched by name
+fn apply_loop_habit_details(habits: &mut [ImportedHabit], habits_csv: &str) -> Result<()> {
+    let records = parse_csv(habits_csv)?;
+    let Some((header, rows)) = records.split_first() else {
+        return Ok(());
+    };
+
+    let column = |name: &str| header.iter().position(|value| value.trim().eq_ignore_ascii_case(name));
+    let (Some(name_col), num_col, den_col) = (column("Name"), column("NumRepetitions"), column("Interval")) else {
+        return Ok(());
+    };
+    let description_col = column("Description").or_else(|| column("Question"));
+
+    for row in rows {
+        let Some(habit) = row.get(name_col).and_then(|name| habits.iter_mut().find(|habit| habit.name == name.trim())) else {
+            continue;
+        };
+
+        let field = |col: Option<usize>| col.and_then(|col| row.get(col)).map(|value| value.trim());
+        if let Some(description) = field(description_col).filter(|value| !value.is_empty()) {
+            habit.description = Some(description.to_string());
+        }
+        if let (Some(num), Some(den)) = (field(num_col), field(den_col)) {
+            if let (Ok(num), Ok(den)) = (num.parse::<i64>(), den.parse::<i64>()) {
+                habit.frequency = loop_frequency(num, den).to_string();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loop Habit Tracker's SQLite backup (`.db`): `Habits` and `Repetitions` tables.
+/// Repetitions are stamped at UTC midnight of the day they count for.
+async fn read_loop_sqlite(path: &Path) -> Result<(Vec<ImportedHabit>, Vec<String>)> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let source = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| crate::error::SkillEdError::ValidationError(format!("Cannot open Loop backup: {}", e)))?;
+
+    let habit_rows = sqlx::query_as::<_, (i64, String, Option<String>, Option<i64>, Option<i64>)>(
+        "SELECT id, name, description, freq_num, freq_den FROM Habits ORDER BY position"
+    )
+    .fetch_all(&source)
+    .await
+    .map_err(|e| crate::error::SkillEdError::ValidationError(format!("Not a Loop Habit Tracker backup: {}", e)))?;
+
+    // Boolean habits store manual checks as 2; older backups have no value
+    let repetitions = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT habit, timestamp FROM Repetitions WHERE value IS NULL OR value = 2 ORDER BY timestamp"
+    )
+    .fetch_all(&source)
+    .await
+    .map_err(|e| crate::error::SkillEdError::ValidationError(format!("Not a Loop Habit Tracker backup: {}", e)))?;
+
+    source.close().await;
+
+    let mut warnings = Vec::new();
+    let mut habits: BTreeMap<i64, ImportedHabit> = habit_rows
+        .into_iter()
+        .map(|(id, name, description, num, den)| {
+            let mut habit = ImportedHabit::new(&name, loop_frequency(num.unwrap_or(1), den.unwrap_or(1)), "positive");
+            habit.description = description.filter(|value| !value.is_empty());
+            (id, habit)
+        })
+        .collect();
+
+    for (habit_id, timestamp) in repetitions {
+        let day = Utc.timestamp_millis_opt(timestamp).single().map(|at| at.date_naive());
+        match (habits.get_mut(&habit_id), day) {
+            (Some(habit), Some(day)) => habit.push(local_noon(day)),
+            _ => warnings.push(format!("Skipped repetition for unknown habit {} at {}", habit_id, timestamp)),
+        }
+    }
+
+    Ok((habits.into_values().collect(), warnings))
+}
+
+/// Habitica's JSON data export. Dailies become positive habits completed on the
+/// days their history marks as completed. Habitica "habits" (+/- counters) become
+/// positive habits scored up, or negative habits when they can only be scored down.
+fn parse_habitica(json: &str) -> Result<(Vec<ImportedHabit>, Vec<String>)> {
+    let document: Value = serde_json::from_str(json)?;
+    let tasks = document.get("tasks").unwrap_or(&document);
+    let mut habits = Vec::new();
+    let mut warnings = Vec::new();
+
+    for daily in tasks.get("dailys").and_then(Value::as_array).into_iter().flatten() {
+        let frequency = match daily.get("frequency").and_then(Value::as_str) {
+            Some("weekly") => "weekly",
+            Some("monthly") | Some("yearly") => "monthly",
+            _ => "daily",
+        };
+        let mut habit = habitica_habit(daily, frequency, "positive");
+
+        let mut previous_value: Option<f64> = None;
+        for entry in habitica_history(daily) {
+            let value = entry.get("value").and_then(Value::as_f64);
+            // Older exports have no completed flag; a rising value means it was done
+            let completed = match entry.get("completed").and_then(Value::as_bool) {
+                Some(completed) => completed,
+                None => matches!((previous_value, value), (Some(before), Some(after)) if after > before),
+            };
+            previous_value = value;
+
+            if completed {
+                match habitica_date(entry) {
+                    Some(at) => habit.push(at),
+                    None => warnings.push(format!("Skipped history entry with no date for '{}'", habit.name)),
+                }
+            }
+        }
+        habits.push(habit);
+    }
+
+    for task in tasks.get("habits").and_then(Value::as_array).into_iter().flatten() {
+        let up = task.get("up").and_then(Value::as_bool).unwrap_or(true);
+        let down = task.get("down").and_then(Value::as_bool).unwrap_or(false);
+        let negative = down && !up;
+        let mut habit = habitica_habit(task, "daily", if negative { "negative" } else { "positive" });
+
+        for entry in habitica_history(task) {
+            let scored = entry
+                .get(if negative { "scoredDown" } else { "scoredUp" })
+                .and_then(Value::as_i64)
+                .unwrap_or(0);
+            if scored > 0 {
+                if let Some(at) = habitica_date(entry) {
+                    habit.push(at);
+                }
+            }
+        }
+        habits.push(habit);
+    }
+
+    Ok((habits, warnings))
+}
+
+fn habitica_habit(task: &Value, frequency: &str, polarity: &str) -> ImportedHabit {
+    let mut habit = ImportedHabit::new(task.get("text").and_then(Value::as_str).unwrap_or(""), frequency, polarity);
+    habit.description = task
+        .get("notes")
+        .and_then(Value::as_str)
+        .filter(|notes| !notes.is_empty())
+        .map(str::to_string);
+    habit
+}
+
+fn habitica_history(task: &Value) -> impl Iterator<Item = &Value> {
+    task.get("history").and_then(Value::as_array).into_iter().flatten()
+}
+
+/// History dates are milliseconds since the epoch, or ISO strings in newer exports
+fn habitica_date(entry: &Value) -> Option<DateTime<Utc>> {
+    match entry.get("date")? {
+        Value::Number(millis) => Utc.timestamp_millis_opt(millis.as_f64()? as i64).single(),
+        Value::String(text) => parse_timestamp(text).ok(),
+        _ => None,
+    }
+}
+
+/// A CSV with `date`, `habit` and optional `value` columns, in any order. Dates
+/// are YYYY-MM-DD or RFC 3339; rows with a value of 0 or less are skipped.
+/// Habits that do not exist yet are created as daily positive habits.
+fn parse_generic_csv(input: &str) -> Result<(Vec<ImportedHabit>, Vec<String>)> {
+    let records = parse_csv(input)?;
+    let Some((header, rows)) = records.split_first() else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+
+    let column = |name: &str| header.iter().position(|value| value.trim().eq_ignore_ascii_case(name));
+    let (Some(date_col), Some(habit_col)) = (column("date"), column("habit")) else {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "CSV must have 'date' and 'habit' columns".to_string(),
+        ));
+    };
+    let value_col = column("value");
+
+    let mut habits: Vec<ImportedHabit> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (index, row) in rows.iter().enumerate() {
+        let line = index + 2;
+        let name = row.get(habit_col).map(|value| value.trim()).unwrap_or("");
+        let date = row.get(date_col).map(|value| value.trim()).unwrap_or("");
+        if name.is_empty() {
+            warnings.push(format!("Line {}: missing habit name", line));
+            continue;
+        }
+
+        let value = row.get(value_col.unwrap_or(usize::MAX)).map(|value| value.trim()).unwrap_or("");
+        if !value.is_empty() {
+            match value.parse::<f64>() {
+                Ok(number) if number > 0.0 => {}
+                Ok(_) => continue,
+                Err(_) => {
+                    warnings.push(format!("Line {}: invalid value '{}'", line, value));
+                    continue;
+                }
+            }
+        }
+
+        let completed_at = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(day) => local_noon(day),
+            Err(_) => match parse_timestamp(date) {
+                Ok(at) => at,
+                Err(_) => {
+                    warnings.push(format!("Line {}: invalid date '{}'", line, date));
+                    continue;
+                }
+            },
+        };
+
+        let position = match habits.iter().position(|habit| habit.name.eq_ignore_ascii_case(name)) {
+            Some(position) => position,
+            None => {
+                habits.push(ImportedHabit::new(name, "daily", "positive"));
+                habits.len() - 1
+            }
+        };
+        habits[position].push(completed_at);
+    }
+
+    Ok((habits, warnings))
+}
+
+fn loop_frequency(repetitions: i64, interval: i64) -> &'static str {
+    match interval.checked_div(repetitions.max(1)).unwrap_or(1) {
+        0 | 1 => "daily",
+        2..=7 => "weekly",
+        _ => "monthly",
+    }
+}
+
+/// Day-only sources are stored at local noon so the day stays put across time zones
+fn local_noon(day: NaiveDate) -> DateTime<Utc> {
+    let noon = day.and_hms_opt(12, 0, 0).expect("noon is a valid time");
+    Local
+        .from_local_datetime(&noon)
+        .earliest()
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&noon))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_loop_checkmarks() {
+        let checkmarks = "Date,Meditate,Run\n2024-03-06,2,0\n2024-03-05,1,2\n2024-03-04,2,-1\n";
+        let habits_csv = "Position,Name,Question,Description,NumRepetitions,Interval,Color\n\
+                          001,Meditate,,Ten minutes,1,1,#FF0000\n002,Run,,,3,7,#00FF00\n";
+
+        let (habits, warnings) = parse_loop_checkmarks(checkmarks, Some(habits_csv)).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(habits[0].entries.len(), 2);
+        assert_eq!(habits[0].description.as_deref(), Some("Ten minutes"));
+        assert_eq!(habits[1].frequency, "weekly");
+        assert_eq!(local_date(&habits[1].entries[0].completed_at).unwrap(), day("2024-03-05"));
+    }
+
+    #[test]
+    fn test_parse_habitica() {
+        let json = r#"{"tasks": {
+            "dailys": [{"text": "Stretch", "notes": "", "frequency": "weekly", "history": [
+                {"date": 1709553600000, "value": 1.0, "completed": true},
+                {"date": 1709640000000, "value": 0.5, "completed": false}
+            ]}],
+            "habits": [{"text": "Snacking", "up": false, "down": true, "history": [
+                {"date": "2024-03-04T20:00:00.000Z", "value": -1.0, "scoredUp": 0, "scoredDown": 2}
+            ]}]
+        }}"#;
+
+        let (habits, _) = parse_habitica(json).unwrap();
+        assert_eq!((habits[0].frequency.as_str(), habits[0].entries.len()), ("weekly", 1));
+        assert_eq!(habits[1].polarity, "negative");
+        assert_eq!(habits[1].entries[0].completion_type, "relapse");
+    }
+
+    #[test]
+    fn test_parse_generic_csv() {
+        let input = "habit,date,value\nRead,2024-03-04,1\nread,2024-03-05,\nRead,2024-03-06,0\n,2024-03-07,1\nRead,March 8,1\n";
+        let (habits, warnings) = parse_generic_csv(input).unwrap();
+
+        assert_eq!(habits.len(), 1);
+        assert_eq!(habits[0].entries.len(), 2);
+        assert_eq!(warnings.len(), 2);
+        assert!(parse_generic_csv("when,what\n").is_err());
+    }
+
+    #[test]
+    fn test_loop_frequency() {
+        assert_eq!(loop_frequency(1, 1), "daily");
+        assert_eq!(loop_frequency(1, 7), "weekly");
+        assert_eq!(loop_frequency(3, 7), "weekly");
+        assert_eq!(loop_frequency(1, 30), "monthly");
+    }
+
+    #[tokio::test]
+    async fn test_read_loop_sqlite() {
+        let path = std::env::temp_dir().join(format!("loop-{}.db", uuid::Uuid::new_v4()));
+        let options = SqliteConnectOptions::new().filename(&path).create_if_missing(true);
+        let loop_db = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE Habits (id INTEGER PRIMARY KEY, name TEXT, description TEXT, freq_num INTEGER, freq_den INTEGER, position INTEGER);
+             CREATE TABLE Repetitions (id INTEGER PRIMARY KEY, habit INTEGER, timestamp INTEGER, value INTEGER);
+             INSERT INTO Habits VALUES (1, 'Floss', '', 1, 1, 0);
+             INSERT INTO Repetitions (habit, timestamp, value) VALUES (1, 1709510400000, 2), (1, 1709596800000, 3), (9, 1709596800000, 2);"
+        )
+        .execute(&loop_db)
+        .await
+        .unwrap();
+        loop_db.close().await;
+
+        let (habits, warnings) = read_loop_sqlite(&path).await.unwrap();
+        assert_eq!(habits[0].name, "Floss");
+        assert_eq!(habits[0].entries.len(), 1); // the skip (3) is not a completion
+        assert_eq!(local_date(&habits[0].entries[0].completed_at).unwrap(), day("2024-03-04"));
+        assert_eq!(warnings.len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_duplicates_and_dry_run_writes_nothing() {
+        let pool = test_pool().await;
+        let existing = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at) VALUES ('c1', ?, ?)")
+            .bind(&existing.id)
+            .bind(local_noon(day("2024-03-04")).to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (habits, warnings) = parse_generic_csv(
+            "date,habit\n2024-03-04,read\n2024-03-05,Read\n2024-03-06,Read\n2024-03-06,Write\n"
+        )
+        .unwrap();
+
+        let preview = apply_import(&pool, "generic_csv", merge_by_name(habits.clone()), warnings.clone(), true)
+            .await
+            .unwrap();
+        assert_eq!((preview.habits_created, preview.entries_imported, preview.duplicates_skipped), (1, 3, 1));
+        assert_eq!(db::get_habit_completions(&pool, &existing.id).await.unwrap().len(), 1);
+
+        let report = apply_import(&pool, "generic_csv", merge_by_name(habits.clone()), warnings.clone(), false)
+            .await
+            .unwrap();
+        assert_eq!(report.entries_imported, 3);
+        let read = db::get_habit(&pool, &existing.id).await.unwrap().unwrap();
+        assert_eq!((read.streak_count, read.best_streak), (3, 3));
+
+        // Importing the same file again adds nothing
+        let again = apply_import(&pool, "generic_csv", merge_by_name(habits), warnings, false).await.unwrap();
+        assert_eq!((again.habits_created, again.entries_imported, again.duplicates_skipped), (0, 0, 4));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::db::{ActivityHeatmap, HeatmapDay, HeatmapFilter};
+use crate::error::Result;
+use super::activity::{parse_bound, parse_date};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+
+/// Number of non-empty intensity buckets, as in a contribution calendar
+pub const HEATMAP_LEVELS: i64 = 4;
+
+const HEATMAP_SOURCES: [&str; 3] = ["habit_completions", "skill_progress", "practice_sessions"];
+
+// ============ HEATMAP OPERATIONS ============
+
+/// Per-local-day activity between `from` and `to` (inclusive `YYYY-MM-DD` dates).
+/// Only days with activity are returned; the rows are aggregated in SQLite.
+pub async fn get_activity_heatmap(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+    filter: &HeatmapFilter,
+) -> Result<ActivityHeatmap> {
+    let from_date = parse_date(from)?;
+    let to_date = parse_date(to)?;
+    if from_date > to_date {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Heatmap start date must not be after its end date".to_string(),
+        ));
+    }
+    if let Some(source) = filter.sources.iter().find(|s| !HEATMAP_SOURCES.contains(&s.as_str())) {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Unknown heatmap source '{}'. Must be one of: {}",
+            source,
+            HEATMAP_SOURCES.join(", ")
+        )));
+    }
+
+    // Bounds are compared against the stored UTC timestamps so the indexes apply;
+    // grouping then happens on the local calendar day
+    let start = parse_bound(from, false)?;
+    let end = parse_bound(to, true)?;
+    let includes = |source: &str| filter.sources.is_empty() || filter.sources.iter().any(|s| s == source);
+
+    let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT day,
+                SUM(habit_completions) AS habit_completions,
+                SUM(progress_entries) AS progress_entries,
+                SUM(practice_sessions) AS practice_sessions,
+                SUM(practice_minutes) AS practice_minutes
+         FROM ("
+    );
+    let mut first = true;
+
+    if includes("habit_completions") {
+        query.push(
+            "SELECT date(c.completed_at, 'localtime') AS day, COUNT(*) AS habit_completions,
+                    0 AS progress_entries, 0 AS practice_sessions, 0 AS practice_minutes
+             FROM habit_completions c JOIN habits h ON h.id = c.habit_id
+             WHERE c.completion_type = 'completion' AND h.deleted_at IS NULL
+               AND c.completed_at >= "
+        );
+        query.push_bind(&start).push(" AND c.completed_at < ").push_bind(&end);
+        push_id_filter(&mut query, "c.habit_id", &filter.habit_ids);
+        query.push(" GROUP BY day");
+        first = false;
+    }
+
+    if includes("skill_progress") {
+        if !first {
+            query.push(" UNION ALL ");
+        }
+        // Entries written by a stopped session or a linked habit completion are
+        // already counted as that session or completion
+        query.push(
+            "SELECT date(p.recorded_at, 'localtime') AS day, 0, COUNT(*), 0, 0
+             FROM skill_progress p JOIN skills s ON s.id = p.skill_id
+             WHERE p.source_type = 'manual' AND s.deleted_at IS NULL AND p.recorded_at >= "
+        );
+        query.push_bind(&start).push(" AND p.recorded_at < ").push_bind(&end);
+        push_id_filter(&mut query, "p.skill_id", &filter.skill_ids);
+        query.push(" GROUP BY day");
+        first = false;
+    }
+
+    if includes("practice_sessions") {
+        if !first {
+            query.push(" UNION ALL ");
+        }
+        // Only timed sessions count as sessions; the ones standing in for logged
+        // hours or linked habit completions still add their minutes
+        query.push(
+            "SELECT date(ps.started_at, 'localtime') AS day, 0, 0,
+                    SUM(CASE WHEN ps.source_type = 'timer' THEN 1 ELSE 0 END), SUM(ps.duration_seconds) / 60
+             FROM practice_sessions ps JOIN skills s ON s.id = ps.skill_id
+             WHERE ps.ended_at IS NOT NULL AND s.deleted_at IS NULL AND ps.started_at >= "
+        );
+        query.push_bind(&start).push(" AND ps.started_at < ").push_bind(&end);
+        push_id_filter(&mut query, "ps.skill_id", &filter.skill_ids);
+        query.push(" GROUP BY day");
+    }
+
+    query.push(") GROUP BY day ORDER BY day ASC");
+
+    let rows = query.build_query_as::<(String, i64, i64, i64, i64)>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let mut days: Vec<HeatmapDay> = rows
+        .into_iter()
+        .map(|(date, habit_completions, progress_entries, practice_sessions, practice_minutes)| HeatmapDay {
+            date,
+            habit_completions,
+            progress_entries,
+            practice_sessions,
+            practice_minutes,
+            total: habit_completions + progress_entries + practice_sessions,
+            intensity: 0,
+        })
+        .collect();
+
+    let max_total = days.iter().map(|d| d.total).max().unwrap_or(0);
+    for day in &mut days {
+        day.intensity = intensity(day.total, max_total);
+    }
+
+    Ok(ActivityHeatmap {
+        from: from_date.to_string(),
+        to: to_date.to_string(),
+        max_total,
+        days,
+    })
+}
+
+/// Buckets a day's total into 0 (nothing) through `HEATMAP_LEVELS`, relative to
+/// the busiest day in range
+fn intensity(total: i64, max_total: i64) -> i64 {
+    if total <= 0 || max_total <= 0 {
+        return 0;
+    }
+
+    // Ceiling division keeps every active day at level 1 or above
+    (total * HEATMAP_LEVELS + max_total - 1) / max_total
+}
+
+fn push_id_filter(query: &mut QueryBuilder<Sqlite>, column: &str, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+
+    query.push(format!(" AND {} IN (", column));
+    let mut separated = query.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(")");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    #[test]
+    fn test_intensity_buckets() {
+        assert_eq!(intensity(0, 10), 0);
+        assert_eq!(intensity(1, 10), 1);
+        assert_eq!(intensity(5, 10), 2);
+        assert_eq!(intensity(8, 10), 4);
+        assert_eq!(intensity(10, 10), HEATMAP_LEVELS);
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_groups_sources_by_day() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+
+        // Noon UTC falls on the same calendar day in practically every timezone
+        let completions = [
+            ("c1", "2024-03-10T12:00:00+00:00"),
+            ("c2", "2024-03-10T12:30:00+00:00"),
+            ("c3", "2024-03-11T12:00:00+00:00"),
+        ];
+        for (id, completed_at) in completions {
+            sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(&habit.id)
+                .bind(completed_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds)
+             VALUES ('s1', ?, '2024-03-10T11:00:00+00:00', '2024-03-10T11:30:00+00:00', 1800)"
+        )
+        .bind(&skill.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let heatmap = get_activity_heatmap(&pool, "2024-03-01", "2024-03-31", &HeatmapFilter::default()).await.unwrap();
+        assert_eq!(heatmap.days.len(), 2);
+        let busiest = &heatmap.days[0];
+        assert_eq!(busiest.date, "2024-03-10");
+        assert_eq!((busiest.habit_completions, busiest.practice_sessions, busiest.practice_minutes), (2, 1, 30));
+        assert_eq!(busiest.intensity, HEATMAP_LEVELS);
+        assert_eq!(heatmap.days[1].intensity, 2);
+
+        let habits_only = HeatmapFilter {
+            sources: vec!["habit_completions".to_string()],
+            ..HeatmapFilter::default()
+        };
+        let heatmap = get_activity_heatmap(&pool, "2024-03-11", "2024-03-11", &habits_only).await.unwrap();
+        assert_eq!(heatmap.days.len(), 1);
+        assert_eq!(heatmap.days[0].total, 1);
+
+        assert!(get_activity_heatmap(&pool, "2024-03-31", "2024-03-01", &habits_only).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_counts_work_once() {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = db::create_habit(&pool, "Scales".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::link_habit_to_skill(&pool, &habit.id, &skill.id, 15, 0).await.unwrap();
+
+        // A timed session (session + progress), a linked completion (completion +
+        // session + progress) and logged hours (progress + session)
+        db::start_session(&pool, &skill.id, None).await.unwrap();
+        db::stop_session(&pool, None, None).await.unwrap();
+        db::log_habit_completion(&pool, &habit.id).await.unwrap();
+        db::record_skill_progress(&pool, &skill.id, 0, 0.5).await.unwrap();
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let heatmap = get_activity_heatmap(&pool, &today, &today, &HeatmapFilter::default()).await.unwrap();
+        let day = &heatmap.days[0];
+        assert_eq!((day.habit_completions, day.progress_entries, day.practice_sessions), (1, 1, 1));
+        assert_eq!(day.total, 3);
+        assert!(day.practice_minutes >= 45);
+    }
+}
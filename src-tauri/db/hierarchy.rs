@@ -0,0 +1,393 @@
+use crate::db::{Skill, SkillPrerequisite, SkillTreeNode};
+use crate::error::Result;
+use super::operations::SKILL_COLUMNS;
+use super::xp::get_skill_levels;
+use sqlx::SqlitePool;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+
+// ============ SUB-SKILL OPERATIONS ============
+
+/// Moves a skill under `parent_id`, or back to the top level when `parent_id` is `None`
+pub async fn set_skill_parent(pool: &SqlitePool, skill_id: &str, parent_id: Option<&str>) -> Result<()> {
+    if let Some(parent_id) = parent_id {
+        if parent_id == skill_id {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "A skill cannot be its own parent".to_string(),
+            ));
+        }
+
+        let skills = get_tree_skills(pool).await?;
+        let parents: HashMap<&str, Option<&str>> = skills
+            .iter()
+            .map(|s| (s.id.as_str(), s.parent_id.as_deref()))
+            .collect();
+
+        if !parents.contains_key(parent_id) {
+            return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", parent_id)));
+        }
+        if is_ancestor(&parents, skill_id, parent_id) {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "A skill cannot be moved under one of its own sub-skills".to_string(),
+            ));
+        }
+    }
+
+    let result = sqlx::query("UPDATE skills SET parent_id = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(parent_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(skill_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)));
+    }
+
+    Ok(())
+}
+
+/// Returns `root_id` with all of its sub-skills, rolling hours and proficiency up to each parent
+pub async fn get_skill_tree(pool: &SqlitePool, root_id: &str) -> Result<SkillTreeNode> {
+    let skills = get_tree_skills(pool).await?;
+
+    build_tree(&skills, root_id)
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", root_id)))
+}
+
+// ============ PREREQUISITE OPERATIONS ============
+
+/// Records that `skill_id` requires `prerequisite_id` at `min_proficiency`.
+/// Edges that would close a cycle in the learning path are rejected.
+pub async fn add_skill_prerequisite(
+    pool: &SqlitePool,
+    skill_id: &str,
+    prerequisite_id: &str,
+    min_proficiency: i32,
+) -> Result<SkillPrerequisite> {
+    if !(0..=100).contains(&min_proficiency) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Minimum proficiency must be between 0 and 100".to_string(),
+        ));
+    }
+    if skill_id == prerequisite_id {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "A skill cannot be its own prerequisite".to_string(),
+        ));
+    }
+
+    let skills = get_tree_skills(pool).await?;
+    for id in [skill_id, prerequisite_id] {
+        if !skills.iter().any(|s| s.id == id) {
+            return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", id)));
+        }
+    }
+
+    let edges = get_all_prerequisites(pool).await?;
+    if creates_cycle(&edges, skill_id, prerequisite_id) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "This prerequisite would create a cycle in the learning path".to_string(),
+        ));
+    }
+
+    let prerequisite = SkillPrerequisite {
+        skill_id: skill_id.to_string(),
+        prerequisite_id: prerequisite_id.to_string(),
+        min_proficiency,
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    sqlx::query(
+        "INSERT INTO skill_prerequisites (skill_id, prerequisite_id, min_proficiency, created_at)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(skill_id, prerequisite_id) DO UPDATE SET min_proficiency = excluded.min_proficiency"
+    )
+    .bind(&prerequisite.skill_id)
+    .bind(&prerequisite.prerequisite_id)
+    .bind(prerequisite.min_proficiency)
+    .bind(&prerequisite.created_at)
+    .execute(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(prerequisite)
+}
+
+pub async fn remove_skill_prerequisite(pool: &SqlitePool, skill_id: &str, prerequisite_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM skill_prerequisites WHERE skill_id = ? AND prerequisite_id = ?")
+        .bind(skill_id)
+        .bind(prerequisite_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn get_skill_prerequisites(pool: &SqlitePool, skill_id: &str) -> Result<Vec<SkillPrerequisite>> {
+    let prerequisites = sqlx::query_as::<_, SkillPrerequisite>(
+        "SELECT skill_id, prerequisite_id, min_proficiency, created_at
+         FROM skill_prerequisites WHERE skill_id = ? ORDER BY created_at ASC"
+    )
+    .bind(skill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(prerequisites)
+}
+
+/// Lists skills that have not been started yet and whose prerequisites are all met.
+/// A prerequisite is met by its effective level, so a skill in computed mode counts
+/// with the level its practice time has earned.
+pub async fn get_unlockable_skills(pool: &SqlitePool) -> Result<Vec<Skill>> {
+    let skills = get_tree_skills(pool).await?;
+    let edges = get_all_prerequisites(pool).await?;
+    let levels: HashMap<String, i32> = get_skill_levels(pool)
+        .await?
+        .into_iter()
+        .map(|level| (level.skill_id, level.level))
+        .collect();
+
+    Ok(unlockable_skills(&skills, &edges, &levels))
+}
+
+async fn get_all_prerequisites(pool: &SqlitePool) -> Result<Vec<SkillPrerequisite>> {
+    sqlx::query_as::<_, SkillPrerequisite>(
+        "SELECT skill_id, prerequisite_id, min_proficiency, created_at FROM skill_prerequisites"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+/// All skills that are not in the trash, including archived ones
+async fn get_tree_skills(pool: &SqlitePool) -> Result<Vec<Skill>> {
+    let query_str = format!(
+        "SELECT {} FROM skills WHERE deleted_at IS NULL ORDER BY sort_order ASC, created_at DESC",
+        SKILL_COLUMNS
+    );
+
+    sqlx::query_as::<_, Skill>(&query_str)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+// ============ GRAPH HELPERS ============
+
+/// Whether `ancestor` appears on the parent chain of `node` (or is `node` itself)
+fn is_ancestor(parents: &HashMap<&str, Option<&str>>, ancestor: &str, node: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut current = Some(node);
+
+    while let Some(id) = current {
+        if id == ancestor {
+            return true;
+        }
+        if !visited.insert(id) {
+            return false;
+        }
+        current = parents.get(id).copied().flatten();
+    }
+
+    false
+}
+
+/// Whether adding the edge "`skill_id` requires `prerequisite_id`" closes a cycle,
+/// i.e. `skill_id` is already reachable from `prerequisite_id`
+fn creates_cycle(edges: &[SkillPrerequisite], skill_id: &str, prerequisite_id: &str) -> bool {
+    let mut requires: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in edges {
+        requires.entry(edge.skill_id.as_str()).or_default().push(edge.prerequisite_id.as_str());
+    }
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![prerequisite_id];
+
+    while let Some(id) = stack.pop() {
+        if id == skill_id {
+            return true;
+        }
+        if visited.insert(id) {
+            if let Some(next) = requires.get(id) {
+                stack.extend(next.iter().copied());
+            }
+        }
+    }
+
+    false
+}
+
+/// `levels` holds the effective level of every skill outside the trash
+fn unlockable_skills(skills: &[Skill], edges: &[SkillPrerequisite], levels: &HashMap<String, i32>) -> Vec<Skill> {
+    skills
+        .iter()
+        .filter(|s| !s.is_archived && s.proficiency_level == 0 && s.total_hours == 0.0)
+        .filter(|s| {
+            let mut requirements = edges.iter().filter(|e| e.skill_id == s.id).peekable();
+            requirements.peek().is_some()
+                && requirements.all(|e| {
+                    // A prerequisite that was moved to the trash no longer blocks anything
+                    levels
+                        .get(&e.prerequisite_id)
+                        .map(|level| *level >= e.min_proficiency)
+                        .unwrap_or(true)
+                })
+        })
+        .cloned()
+        .collect()
+}
+
+fn build_tree(skills: &[Skill], root_id: &str) -> Option<SkillTreeNode> {
+    let mut children: HashMap<&str, Vec<&Skill>> = HashMap::new();
+    for skill in skills {
+        if let Some(parent_id) = skill.parent_id.as_deref() {
+            children.entry(parent_id).or_default().push(skill);
+        }
+    }
+
+    let root = skills.iter().find(|s| s.id == root_id)?;
+    let mut visited = HashSet::new();
+    Some(build_node(root, &children, &mut visited))
+}
+
+fn build_node<'a>(
+    skill: &'a Skill,
+    children: &HashMap<&str, Vec<&'a Skill>>,
+    visited: &mut HashSet<&'a str>,
+) -> SkillTreeNode {
+    visited.insert(skill.id.as_str());
+
+    let mut child_nodes = Vec::new();
+    for child in children.get(skill.id.as_str()).into_iter().flatten() {
+        if !visited.contains(child.id.as_str()) {
+            child_nodes.push(build_node(child, children, visited));
+        }
+    }
+
+    let rollup_hours = skill.total_hours + child_nodes.iter().map(|c| c.rollup_hours).sum::<f64>();
+
+    // Weight each level by the hours behind it; fall back to a plain average
+    // while nothing in the subtree has been practised yet
+    let rollup_proficiency = if rollup_hours > 0.0 {
+        let weighted = skill.proficiency_level as f64 * skill.total_hours
+            + child_nodes
+                .iter()
+                .map(|c| c.rollup_proficiency as f64 * c.rollup_hours)
+                .sum::<f64>();
+        (weighted / rollup_hours).round() as i32
+    } else {
+        let levels: Vec<i32> = std::iter::once(skill.proficiency_level)
+            .chain(child_nodes.iter().map(|c| c.rollup_proficiency))
+            .collect();
+        (levels.iter().sum::<i32>() as f64 / levels.len() as f64).round() as i32
+    };
+
+    SkillTreeNode {
+        skill: skill.clone(),
+        rollup_hours,
+        rollup_proficiency,
+        children: child_nodes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::operations::{create_skill, record_skill_progress, set_skill_level_mode};
+    use crate::db::xp::get_skill_level;
+
+    fn skill(id: &str, parent_id: Option<&str>, level: i32, hours: f64) -> Skill {
+        let mut skill = Skill::new(id.to_string(), "Music".to_string());
+        skill.id = id.to_string();
+        skill.parent_id = parent_id.map(str::to_string);
+        skill.proficiency_level = level;
+        skill.total_hours = hours;
+        skill
+    }
+
+    fn requires(skill_id: &str, prerequisite_id: &str, min_proficiency: i32) -> SkillPrerequisite {
+        SkillPrerequisite {
+            skill_id: skill_id.to_string(),
+            prerequisite_id: prerequisite_id.to_string(),
+            min_proficiency,
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_tree_rolls_up_hours_and_proficiency() {
+        let skills = vec![
+            skill("guitar", None, 0, 0.0),
+            skill("fingerpicking", Some("guitar"), 60, 30.0),
+            skill("travis", Some("fingerpicking"), 20, 10.0),
+            skill("piano", None, 90, 100.0),
+        ];
+
+        let tree = build_tree(&skills, "guitar").unwrap();
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].children[0].skill.id, "travis");
+        assert_eq!(tree.rollup_hours, 40.0);
+        assert_eq!(tree.rollup_proficiency, 50);
+    }
+
+    #[test]
+    fn test_parent_cycle_detection() {
+        let parents: HashMap<&str, Option<&str>> =
+            [("guitar", None), ("fingerpicking", Some("guitar")), ("travis", Some("fingerpicking"))]
+                .into_iter()
+                .collect();
+
+        assert!(is_ancestor(&parents, "guitar", "travis"));
+        assert!(!is_ancestor(&parents, "travis", "guitar"));
+    }
+
+    #[test]
+    fn test_prerequisite_cycle_detection() {
+        let edges = vec![requires("travis", "fingerpicking", 50), requires("fingerpicking", "chords", 50)];
+
+        assert!(creates_cycle(&edges, "chords", "travis"));
+        assert!(!creates_cycle(&edges, "travis", "chords"));
+    }
+
+    #[test]
+    fn test_unlockable_requires_all_prerequisites_met() {
+        let skills = vec![
+            skill("chords", None, 70, 20.0),
+            skill("fingerpicking", None, 30, 5.0),
+            skill("strumming", None, 0, 0.0),
+            skill("travis", None, 0, 0.0),
+        ];
+        let edges = vec![
+            requires("strumming", "chords", 50),
+            requires("travis", "chords", 50),
+            requires("travis", "fingerpicking", 50),
+        ];
+
+        let levels = skills.iter().map(|s| (s.id.clone(), s.proficiency_level)).collect();
+
+        let unlockable: Vec<String> = unlockable_skills(&skills, &edges, &levels).into_iter().map(|s| s.id).collect();
+        assert_eq!(unlockable, vec!["strumming".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_computed_prerequisite_unlocks_by_practice() {
+        let pool = crate::db::test_pool().await;
+        let chords = create_skill(&pool, "Chords".to_string(), "Music".to_string(), None).await.unwrap();
+        let strumming = create_skill(&pool, "Strumming".to_string(), "Music".to_string(), None).await.unwrap();
+        record_skill_progress(&pool, &chords.id, 0, 30.0).await.unwrap();
+
+        let computed_level = get_skill_level(&pool, &chords.id).await.unwrap().computed_level;
+        assert!(computed_level > 0);
+        add_skill_prerequisite(&pool, &strumming.id, &chords.id, computed_level).await.unwrap();
+
+        // The entered level is still 0
+        assert!(get_unlockable_skills(&pool).await.unwrap().is_empty());
+
+        set_skill_level_mode(&pool, &chords.id, "computed").await.unwrap();
+        let unlockable: Vec<String> = get_unlockable_skills(&pool).await.unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(unlockable, vec![strumming.id]);
+    }
+}
@@ -1,13 +1,46 @@
 mod models;
 mod schema;
 mod operations;
+mod hierarchy;
+mod taxonomy;
+mod preferences;
+mod xp;
+mod achievements;
+mod activity;
+mod heatmap;
+mod stats;
+mod exports;
+mod review;
+mod transfer;
+mod backup;
+mod csv;
+mod habit_import;
+mod calendar;
+mod reminders;
+mod tray;
 
 pub use models::*;
 pub use schema::*;
 pub use operations::*;
+pub use hierarchy::*;
+pub use taxonomy::*;
+pub use xp::*;
+pub use achievements::*;
+pub use activity::*;
+pub use heatmap::*;
+pub use stats::*;
+pub use exports::*;
+pub use review::*;
+pub use transfer::*;
+pub use backup::*;
+pub use csv::*;
+pub use habit_import::*;
+pub use calendar::*;
+pub use reminders::*;
+pub use tray::*;
 
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteConnectOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use crate::error::Result;
 use log::info;
@@ -16,6 +49,7 @@ use parking_lot::Mutex;
 
 lazy_static! {
     static ref DB_POOL: Mutex<Option<SqlitePool>> = Mutex::new(None);
+    static ref APP_DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
 }
 
 /// Initializes the SQLite database with all required tables
@@ -32,7 +66,8 @@ pub async fn init_db(app_data_dir: &Path) -> Result<()> {
         db_path.to_string_lossy().replace("\\", "/")
     );
 
-    let connect_options = SqliteConnectOptions::from_str(&connection_string)?
+    let connect_options = SqliteConnectOptions::from_str(&connection_string)
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
         .create_if_missing(true);
 
     let pool = SqlitePoolOptions::new()
@@ -47,8 +82,15 @@ pub async fn init_db(app_data_dir: &Path) -> Result<()> {
         .await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
+    // Drop skills and habits whose restore window has passed
+    let purged = operations::purge_expired_trash(&pool).await?;
+    if purged > 0 {
+        info!("Purged {} expired items from the trash", purged);
+    }
+
     // Store pool in static
     *DB_POOL.lock() = Some(pool);
+    *APP_DATA_DIR.lock() = Some(app_data_dir.to_path_buf());
 
     info!("Database initialized at: {}", db_path.display());
 
@@ -72,9 +114,53 @@ pub async fn get_pool(app_data_dir: &Path) -> Result<SqlitePool> {
     Ok(pool)
 }
 
+/// Gets the directory the database lives in; exports and backups are written below it
+pub fn get_app_data_dir() -> Result<PathBuf> {
+    APP_DATA_DIR.lock()
+        .clone()
+        .ok_or_else(|| crate::error::SkillEdError::DatabaseError("Database not initialized".to_string()))
+}
+
+/// In-memory database with the full schema, for tests that need real tables
+#[cfg(test)]
+pub(crate) async fn test_pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+
+    sqlx::query(schema::CREATE_TABLES_SQL)
+        .execute(&pool)
+        .await
+        .expect("Failed to create tables");
+
+    pool
+}
+
 /// Gets the global database pool
 pub fn get_db_pool() -> Result<SqlitePool> {
     DB_POOL.lock()
         .clone()
         .ok_or_else(|| crate::error::SkillEdError::DatabaseError("Database not initialized".to_string()))
 }
+
+/// Checkpoints the write-ahead log into the database file and closes the global
+/// pool, waiting for open connections to be returned. Later `get_db_pool` calls
+/// fail. Does nothing when the pool is already closed.
+pub async fn close_db_pool() -> Result<()> {
+    let Some(pool) = DB_POOL.lock().take() else {
+        return Ok(());
+    };
+
+    // A no-op unless the database is in WAL mode
+    let checkpoint = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(&pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()));
+
+    pool.close().await;
+    info!("Database closed");
+
+    checkpoint.map(|_| ())
+}
@@ -15,6 +15,12 @@ pub struct Skill {
     pub updated_at: String,
     pub encrypted_notes: Option<String>,
     pub nonce_notes: Option<String>,
+    pub sort_order: i32,
+    pub is_archived: bool,
+    pub deleted_at: Option<String>, // set while the skill is in the trash
+    pub parent_id: Option<String>,
+    pub category_id: Option<String>, // `category` mirrors this category's name
+    pub level_mode: String, // "manual" (proficiency_level as entered), "computed" (from the skill level curve)
 }
 
 impl Skill {
@@ -31,10 +37,89 @@ impl Skill {
             updated_at: now,
             encrypted_notes: None,
             nonce_notes: None,
+            sort_order: 0,
+            is_archived: false,
+            deleted_at: None,
+            parent_id: None,
+            category_id: None,
+            level_mode: "manual".to_string(),
         }
     }
 }
 
+/// Prerequisite edge between two skills: `skill_id` unlocks once
+/// `prerequisite_id` reaches `min_proficiency`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SkillPrerequisite {
+    pub skill_id: String,
+    pub prerequisite_id: String,
+    pub min_proficiency: i32,
+    pub created_at: String,
+}
+
+/// A skill with its sub-skills and the hours and proficiency rolled up from them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillTreeNode {
+    pub skill: Skill,
+    pub rollup_hours: f64,
+    pub rollup_proficiency: i32, // hours-weighted across the subtree
+    pub children: Vec<SkillTreeNode>,
+}
+
+/// Partial update for a skill. Only fields that were set are written.
+#[derive(Debug, Clone, Default)]
+pub struct SkillUpdate {
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub description: Option<Option<String>>, // Some(None) clears it
+    pub proficiency_level: Option<i32>,
+    pub total_hours: Option<f64>,
+}
+
+impl SkillUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(Some(description.into()));
+        self
+    }
+
+    pub fn clear_description(mut self) -> Self {
+        self.description = Some(None);
+        self
+    }
+
+    pub fn proficiency_level(mut self, level: i32) -> Self {
+        self.proficiency_level = Some(level);
+        self
+    }
+
+    pub fn total_hours(mut self, hours: f64) -> Self {
+        self.total_hours = Some(hours);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.category.is_none()
+            && self.description.is_none()
+            && self.proficiency_level.is_none()
+            && self.total_hours.is_none()
+    }
+}
+
 /// Represents a tracked habit
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Habit {
@@ -42,10 +127,15 @@ pub struct Habit {
     pub name: String,
     pub description: Option<String>,
     pub frequency: String, // "daily", "weekly", "monthly"
+    pub polarity: String, // "positive" (build a habit), "negative" (break a habit)
     pub streak_count: i32,
     pub best_streak: i32,
     pub created_at: String,
     pub last_completed: Option<String>,
+    pub sort_order: i32,
+    pub is_archived: bool,
+    pub deleted_at: Option<String>, // set while the habit is in the trash
+    pub category_id: Option<String>,
 }
 
 impl Habit {
@@ -56,20 +146,70 @@ impl Habit {
             name,
             description: None,
             frequency,
+            polarity: "positive".to_string(),
             streak_count: 0,
             best_streak: 0,
             created_at: now,
             last_completed: None,
+            sort_order: 0,
+            is_archived: false,
+            deleted_at: None,
+            category_id: None,
         }
     }
 }
 
+/// Partial update for a habit. Only fields that were set are written.
+#[derive(Debug, Clone, Default)]
+pub struct HabitUpdate {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>, // Some(None) clears it
+    pub frequency: Option<String>,
+    pub category: Option<String>,
+}
+
+impl HabitUpdate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(Some(description.into()));
+        self
+    }
+
+    pub fn clear_description(mut self) -> Self {
+        self.description = Some(None);
+        self
+    }
+
+    pub fn frequency(mut self, frequency: impl Into<String>) -> Self {
+        self.frequency = Some(frequency.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.description.is_none() && self.frequency.is_none() && self.category.is_none()
+    }
+}
+
 /// Represents a habit completion record
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct HabitCompletion {
     pub id: String,
     pub habit_id: String,
     pub completed_at: String,
+    pub completion_type: String, // "completion", "relapse"
 }
 
 impl HabitCompletion {
@@ -78,10 +218,40 @@ impl HabitCompletion {
             id: Uuid::new_v4().to_string(),
             habit_id,
             completed_at: Utc::now().to_rfc3339(),
+            completion_type: "completion".to_string(),
         }
     }
 }
 
+/// Links a habit to a skill; every completion of the habit adds
+/// `minutes_per_completion` of practice and `xp_per_completion` XP to the skill
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct HabitSkillLink {
+    pub habit_id: String,
+    pub skill_id: String,
+    pub minutes_per_completion: i32,
+    pub xp_per_completion: i32,
+    pub created_at: String,
+}
+
+/// Clean-streak summary for a negative habit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanStreakStats {
+    pub habit_id: String,
+    pub last_relapse: Option<String>,
+    pub seconds_since_last_relapse: i64,
+    pub current_clean_days: i64,
+    pub longest_clean_days: i64,
+    pub total_relapses: i64,
+}
+
+/// Number of relapses in a week or month, keyed by the period's first day
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RelapseTrendPoint {
+    pub period_start: String,
+    pub relapse_count: i64,
+}
+
 /// Represents skill progress over time
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct SkillProgress {
@@ -90,6 +260,8 @@ pub struct SkillProgress {
     pub proficiency_level: i32,
     pub hours_practiced: f64,
     pub recorded_at: String,
+    pub source_type: String, // manual, practice_session, habit_completion
+    pub source_id: Option<String>,
 }
 
 impl SkillProgress {
@@ -100,10 +272,562 @@ impl SkillProgress {
             proficiency_level,
             hours_practiced,
             recorded_at: Utc::now().to_rfc3339(),
+            source_type: "manual".to_string(),
+            source_id: None,
+        }
+    }
+}
+
+/// Represents a timed practice session for a skill.
+/// A session with no `ended_at` is the currently running timer.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PracticeSession {
+    pub id: String,
+    pub skill_id: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_seconds: i64,
+    pub focus_rating: Option<i32>, // 1-5
+    pub notes: Option<String>,
+    pub tags: Option<String>, // comma-separated
+    pub source_type: String, // timer, progress, habit_completion
+    pub source_id: Option<String>,
+}
+
+impl PracticeSession {
+    pub fn start(skill_id: String, tags: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            skill_id,
+            started_at: Utc::now().to_rfc3339(),
+            ended_at: None,
+            duration_seconds: 0,
+            focus_rating: None,
+            notes: None,
+            tags,
+            source_type: "timer".to_string(),
+            source_id: None,
+        }
+    }
+}
+
+/// A category for skills and habits. Names are unique ignoring case.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>, // hex, e.g. "#4f46e5"
+    pub icon: Option<String>,
+    pub created_at: String,
+}
+
+/// A tag shared by skills, habits and milestones. Names are unique ignoring case.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+/// Narrows a skill or habit list. Empty sets do not filter; a row matches when it
+/// is in one of the categories and carries at least one of the tags.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListFilter {
+    #[serde(default)]
+    pub category_ids: Vec<String>,
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+}
+
+/// Curve mapping accumulated points (XP or hours) to levels. Level 1 starts at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LevelCurve {
+    /// Every level costs the same
+    Linear { per_level: f64 },
+    /// Reaching level n costs `base * (n - 1)^exponent` in total
+    Power { base: f64, exponent: f64 },
+    /// The first level costs `base`, each following level `growth` times the previous one
+    Exponential { base: f64, growth: f64 },
+}
+
+impl LevelCurve {
+    /// Total points needed to reach `level`
+    pub fn threshold(&self, level: i32) -> f64 {
+        let n = (level.max(1) - 1) as f64;
+        match *self {
+            LevelCurve::Linear { per_level } => per_level * n,
+            LevelCurve::Power { base, exponent } => base * n.powf(exponent),
+            LevelCurve::Exponential { base, growth } => {
+                if (growth - 1.0).abs() < f64::EPSILON {
+                    base * n
+                } else {
+                    base * (growth.powf(n) - 1.0) / (growth - 1.0)
+                }
+            }
         }
     }
 }
 
+/// Where a point total sits on a level curve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelProgress {
+    pub level: i32,
+    pub points: f64,
+    pub level_start: f64,
+    pub next_level_at: Option<f64>, // `None` at the maximum level
+    pub to_next_level: Option<f64>,
+    pub progress_percent: f64,
+}
+
+/// XP bonus applied once a streak reaches `min_streak` periods
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StreakMultiplier {
+    pub min_streak: i32,
+    pub multiplier: f64,
+}
+
+/// How XP is earned and turned into profile levels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XpConfig {
+    pub level_curve: LevelCurve,
+    pub xp_per_practice_hour: i64,
+    pub xp_per_habit_completion: i64,
+    pub xp_per_milestone: i64,
+    pub streak_multipliers: Vec<StreakMultiplier>,
+}
+
+impl Default for XpConfig {
+    fn default() -> Self {
+        Self {
+            level_curve: LevelCurve::Power { base: 100.0, exponent: 1.5 },
+            xp_per_practice_hour: 60,
+            xp_per_habit_completion: 10,
+            xp_per_milestone: 100,
+            streak_multipliers: vec![
+                StreakMultiplier { min_streak: 3, multiplier: 1.1 },
+                StreakMultiplier { min_streak: 7, multiplier: 1.25 },
+                StreakMultiplier { min_streak: 30, multiplier: 1.5 },
+            ],
+        }
+    }
+}
+
+impl XpConfig {
+    /// The highest multiplier whose threshold `streak` has reached, or 1.0
+    pub fn streak_multiplier(&self, streak: i32) -> f64 {
+        self.streak_multipliers
+            .iter()
+            .filter(|m| streak >= m.min_streak)
+            .map(|m| m.multiplier)
+            .fold(1.0, f64::max)
+    }
+}
+
+/// One immutable entry in the XP ledger
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct XpEvent {
+    pub id: String,
+    pub source_type: String, // "habit_completion", "practice_session", "milestone"
+    pub source_id: String,
+    pub skill_id: Option<String>,
+    pub base_xp: i64,
+    pub multiplier: f64,
+    pub xp: i64, // base_xp * multiplier, rounded
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+impl XpEvent {
+    pub fn new(
+        source_type: &str,
+        source_id: String,
+        skill_id: Option<String>,
+        base_xp: i64,
+        multiplier: f64,
+        reason: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            source_type: source_type.to_string(),
+            source_id,
+            skill_id,
+            base_xp,
+            multiplier,
+            xp: (base_xp as f64 * multiplier).round() as i64,
+            reason: Some(reason),
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// How computed skill levels are derived
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillLevelConfig {
+    pub basis: String, // "hours" (practice time), "xp" (skill XP from the ledger)
+    pub curve: LevelCurve,
+    pub max_level: i32,
+}
+
+impl Default for SkillLevelConfig {
+    fn default() -> Self {
+        Self::mastery()
+    }
+}
+
+impl SkillLevelConfig {
+    /// 100 levels on a quadratic curve that tops out at 10,000 hours of practice
+    pub fn mastery() -> Self {
+        Self {
+            basis: "hours".to_string(),
+            curve: LevelCurve::Power { base: 10_000.0 / 99f64.powi(2), exponent: 2.0 },
+            max_level: 100,
+        }
+    }
+
+    /// RPG-style progression on skill XP where each level costs 10% more than the last
+    pub fn rpg() -> Self {
+        Self {
+            basis: "xp".to_string(),
+            curve: LevelCurve::Exponential { base: 60.0, growth: 1.1 },
+            max_level: 100,
+        }
+    }
+}
+
+/// A skill's level in both modes, and how far it is from the next computed level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillLevel {
+    pub skill_id: String,
+    pub level_mode: String,
+    pub level: i32, // manual_level or computed_level, depending on level_mode
+    pub manual_level: i32,
+    pub computed_level: i32,
+    pub total_hours: f64,
+    pub total_xp: i64,
+    pub hours_to_next_level: Option<f64>, // `None` at the maximum level
+    pub progress_percent: f64,
+}
+
+/// The user's overall level, summed from the XP ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileLevel {
+    pub total_xp: i64,
+    pub progress: LevelProgress,
+}
+
+/// An achievement and, once earned, when it was unlocked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Achievement {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub unlocked_at: Option<String>,
+}
+
+impl Achievement {
+    pub fn from_definition(definition: &super::AchievementDefinition, unlocked_at: Option<String>) -> Self {
+        Self {
+            id: definition.id.to_string(),
+            title: definition.title.to_string(),
+            description: definition.description.to_string(),
+            unlocked_at,
+        }
+    }
+}
+
+/// One entry in the activity log
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ActivityEvent {
+    pub id: String,
+    pub event_type: String, // e.g. "skill_created", "progress_logged", "habit_completed", "milestone_completed"
+    pub entity_type: String, // "skill", "habit", "milestone", "practice_session", "achievement"
+    pub entity_id: String,
+    pub summary: String,
+    pub payload: Option<String>, // JSON snapshot of the entity after the change
+    pub created_at: String,
+}
+
+impl ActivityEvent {
+    pub fn new(event_type: &str, entity_type: &str, entity_id: &str, summary: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            event_type: event_type.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+            summary,
+            payload: None,
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Narrows the activity feed. Dates are `YYYY-MM-DD` local days or RFC 3339
+/// timestamps; a `to` date includes that whole day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityFilter {
+    pub event_types: Vec<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Narrows the activity heatmap. Empty lists do not filter; `sources` takes
+/// "habit_completions", "skill_progress" and "practice_sessions".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeatmapFilter {
+    pub sources: Vec<String>,
+    pub skill_ids: Vec<String>,
+    pub habit_ids: Vec<String>,
+}
+
+/// Activity on one local calendar day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapDay {
+    pub date: String, // YYYY-MM-DD, local time
+    pub habit_completions: i64,
+    pub progress_entries: i64,
+    pub practice_sessions: i64,
+    pub practice_minutes: i64,
+    pub total: i64,
+    pub intensity: i64, // 0 (none) to 4 (busiest days in range)
+}
+
+/// Days with activity in a date range; days without any are left out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityHeatmap {
+    pub from: String,
+    pub to: String,
+    pub max_total: i64,
+    pub days: Vec<HeatmapDay>,
+}
+
+/// Hours practiced on a skill during one week or month
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SkillHoursPoint {
+    pub skill_id: String,
+    pub skill_name: String,
+    pub period_start: String, // YYYY-MM-DD; a Monday for weekly periods
+    pub hours: f64,
+}
+
+/// How many of a habit's periods in a range had at least one completion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitCompletionRate {
+    pub habit_id: String,
+    pub habit_name: String,
+    pub frequency: String,
+    pub periods_expected: i64,
+    pub periods_completed: i64,
+    pub completion_rate: f64, // 0.0-1.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayActivity {
+    pub weekday: i32, // 0 = Monday
+    pub name: String,
+    pub habit_completions: i64,
+    pub practice_sessions: i64,
+    pub practice_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayStats {
+    pub days: Vec<WeekdayActivity>,
+    pub best_day: Option<String>, // `None` when there is no activity in range
+    pub worst_day: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourActivity {
+    pub hour: i32, // 0-23, local time
+    pub habit_completions: i64,
+    pub practice_sessions: i64,
+    pub practice_minutes: i64,
+}
+
+/// One day of a trend line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub date: String,
+    pub value: f64,
+    pub moving_average: f64,
+}
+
+/// Change in a skill's proficiency between its first and last progress entry in a range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProficiencyVelocity {
+    pub skill_id: String,
+    pub entries: i64,
+    pub from_level: Option<i32>,
+    pub to_level: Option<i32>,
+    pub days: f64,
+    pub hours_practiced: f64,
+    pub points_per_week: f64,
+    pub points_per_hour: Option<f64>, // `None` without practice in range
+}
+
+/// A file written by an export, report or backup
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Export {
+    pub id: String,
+    pub export_type: String, // e.g. "review_markdown", "review_html"
+    pub created_at: String,
+    pub file_path: String,
+}
+
+impl Export {
+    pub fn new(export_type: &str, file_path: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            export_type: export_type.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            file_path,
+        }
+    }
+}
+
+/// A habit's results over a review period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitReview {
+    pub habit_id: String,
+    pub habit_name: String,
+    pub frequency: String,
+    pub periods_hit: i64,
+    pub periods_missed: i64, // only periods that are already over
+    pub streak_at_start: i32,
+    pub streak_at_end: i32,
+    pub streak_lost: i32, // longest streak broken during the period, 0 if none
+}
+
+/// Everything a weekly or monthly review covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewReport {
+    pub period: String, // "weekly", "monthly"
+    pub from: String,
+    pub to: String,
+    pub generated_at: String,
+    pub total_hours: f64,
+    pub skill_hours: Vec<SkillHoursPoint>,
+    pub habits: Vec<HabitReview>,
+    pub milestones_completed: Vec<Milestone>,
+    pub milestones_overdue: Vec<Milestone>,
+    pub xp_earned: i64,
+}
+
+/// A review report with its rendered forms, and the exports written when saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewDocument {
+    pub report: ReviewReport,
+    pub markdown: String,
+    pub html: String,
+    pub exports: Vec<Export>,
+}
+
+/// A full dump of the vault's data tables, keyed by table name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultExport {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub tables: std::collections::BTreeMap<String, Vec<serde_json::Map<String, serde_json::Value>>>,
+}
+
+/// Rows written and skipped for one table during an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableImportSummary {
+    pub table: String,
+    pub inserted: i64,
+    pub skipped: i64, // rows whose id already existed, when merging
+    pub merged: i64,  // rows matched by name to an existing row, when merging
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub mode: String, // "merge", "replace"
+    pub schema_version: u32,
+    pub tables: Vec<TableImportSummary>,
+}
+
+/// What importing one habit from another app adds, or would add in a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitImportPreview {
+    pub habit_name: String,
+    pub habit_id: Option<String>, // the existing habit the history is merged into
+    pub frequency: String,
+    pub polarity: String,
+    pub entries_found: i64,
+    pub entries_new: i64,
+    pub duplicates: i64, // entries on a day that already has one of the same kind
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitImportReport {
+    pub source: String, // "loop_csv", "loop_sqlite", "habitica", "generic_csv"
+    pub dry_run: bool,
+    pub habits: Vec<HabitImportPreview>,
+    pub habits_created: i64,
+    pub entries_imported: i64,
+    pub duplicates_skipped: i64,
+    pub warnings: Vec<String>,
+}
+
+/// Where the subscribable calendar file is kept up to date. Without a `path`
+/// it is `skill-ed.ics` in the app data directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarFeed {
+    pub enabled: bool,
+    pub path: Option<String>,
+}
+
+/// Narrows a CSV export. Dates are YYYY-MM-DD local days or RFC 3339
+/// timestamps and apply to each entity's main date column.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvFilter {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub skill_id: Option<String>,
+    pub habit_id: Option<String>,
+    pub include_archived: bool,
+}
+
+/// Automatic backup settings. Backups go to `directory`, or `backups` under the
+/// app data directory when unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub cadence: String, // "daily", "weekly"
+    pub directory: Option<String>,
+    pub keep_daily: u32,  // newest backup of each of the last N days
+    pub keep_weekly: u32, // newest backup of each of the last M weeks
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cadence: "daily".to_string(),
+            directory: None,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+/// A completed scheduled backup and the old backups rotation removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRun {
+    pub export: Export,
+    pub removed: Vec<String>,
+}
+
 /// Represents user authentication data
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AuthUser {
@@ -114,6 +838,82 @@ pub struct AuthUser {
     pub last_login: Option<String>,
 }
 
+/// A reminder rule for a habit or milestone. `time_of_day` is local HH:MM and
+/// `weekdays` lists the days it applies, 0 = Monday through 6 = Sunday.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub entity_type: String, // "habit", "milestone"
+    pub entity_id: String,
+    pub time_of_day: String,
+    pub weekdays: Vec<u32>,
+    pub only_if_not_done: bool,
+    pub is_enabled: bool,
+    pub snoozed_until: Option<String>,
+    pub last_fired_at: Option<String>,
+    pub created_at: String,
+}
+
+impl Reminder {
+    pub fn new(entity_type: String, entity_id: String, time_of_day: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            entity_type,
+            entity_id,
+            time_of_day,
+            weekdays: (0..7).collect(),
+            only_if_not_done: true,
+            is_enabled: true,
+            snoozed_until: None,
+            last_fired_at: None,
+            created_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Reminder fields to change; `None` leaves a field as it is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReminderUpdate {
+    pub time_of_day: Option<String>,
+    pub weekdays: Option<Vec<u32>>,
+    pub only_if_not_done: Option<bool>,
+    pub is_enabled: Option<bool>,
+}
+
+/// A reminder whose time has come. `suppressed` is set when the habit is
+/// already done for the period, so it is marked as handled without a notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DueReminder {
+    pub reminder: Reminder,
+    pub title: String,
+    pub body: String,
+    pub suppressed: bool,
+}
+
+/// What the system tray shows: habits still open for their current period and
+/// the practice timer. `timer_skill` is the running session's skill or, when no
+/// timer runs, the most recently practiced skill to start one for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraySummary {
+    pub due_habits: Vec<Habit>,
+    pub running_session: Option<PracticeSession>,
+    pub timer_skill: Option<Skill>,
+}
+
+/// Window behaviour tied to the tray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Closing the main window hides it to the tray instead of quitting
+    pub minimize_to_tray: bool,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self { minimize_to_tray: true }
+    }
+}
+
 /// Represents a milestone or goal
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Milestone {
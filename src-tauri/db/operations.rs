@@ -1,7 +1,25 @@
-use crate::db::{Skill, Habit, HabitCompletion, SkillProgress, Milestone};
+use crate::db::{
+    Skill, SkillUpdate, Habit, HabitUpdate, HabitCompletion, SkillProgress, Milestone,
+    CleanStreakStats, RelapseTrendPoint, PracticeSession, ListFilter, HabitSkillLink, XpEvent,
+};
+use super::xp::{append_xp, get_xp_config};
+use super::achievements::{check_achievements, notify_unlocked};
+use super::activity::{notify_changes, record_activity};
+use super::taxonomy::ensure_category;
 use crate::error::Result;
-use sqlx::SqlitePool;
-use chrono::Utc;
+use sqlx::{Executor, QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+
+/// Number of days a trashed skill or habit can still be restored before it is purged
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+pub(super) const SKILL_COLUMNS: &str = "id, name, description, category, proficiency_level, total_hours, created_at, updated_at, encrypted_notes, nonce_notes, sort_order, is_archived, deleted_at, parent_id, category_id, level_mode";
+
+const SESSION_COLUMNS: &str = "id, skill_id, started_at, ended_at, duration_seconds, focus_rating, notes, tags, source_type, source_id";
+
+const PROGRESS_COLUMNS: &str = "id, skill_id, proficiency_level, hours_practiced, recorded_at, source_type, source_id";
+
+pub(super) const HABIT_COLUMNS: &str = "id, name, description, frequency, polarity, streak_count, best_streak, created_at, last_completed, sort_order, is_archived, deleted_at, category_id";
 
 // ============ SKILL OPERATIONS ============
 
@@ -11,173 +29,716 @@ pub async fn create_skill(
     category: String,
     description: Option<String>,
 ) -> Result<Skill> {
-    let skill = Skill::new(name, category);
+    let mut skill = Skill::new(name, category);
+    skill.description = description;
+    skill.sort_order = top_sort_order(pool, "skills").await?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // "music" and "Music" resolve to the same category
+    let category = ensure_category(&mut tx, &skill.category).await?;
+    skill.category = category.name;
+    skill.category_id = Some(category.id);
     
     let query = sqlx::query(
-        "INSERT INTO skills (id, name, description, category, proficiency_level, total_hours, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO skills (id, name, description, category, category_id, proficiency_level, total_hours, created_at, updated_at, sort_order)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&skill.id)
     .bind(&skill.name)
-    .bind(&description)
+    .bind(&skill.description)
     .bind(&skill.category)
+    .bind(&skill.category_id)
     .bind(skill.proficiency_level)
     .bind(skill.total_hours)
     .bind(&skill.created_at)
-    .bind(&skill.updated_at);
+    .bind(&skill.updated_at)
+    .bind(skill.sort_order);
 
-    query.execute(pool).await
+    query.execute(&mut *tx).await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "skills", "skill", &skill.name))?;
+
+    record_activity(&mut tx, "skill_created", "skill", &skill.id, format!("Created skill {}", skill.name), &skill).await?;
+
+    tx.commit().await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
 
     Ok(skill)
 }
 
+/// Lists active skills in the user's order, leaving out archived and trashed ones
 pub async fn get_all_skills(pool: &SqlitePool) -> Result<Vec<Skill>> {
-    let skills = sqlx::query_as::<_, Skill>(
-        "SELECT id, name, description, category, proficiency_level, total_hours, created_at, updated_at, encrypted_notes, nonce_notes
-         FROM skills ORDER BY created_at DESC"
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    get_skills_filtered(pool, &ListFilter::default()).await
+}
+
+/// Lists active skills matching the category and tag sets in `filter`
+pub async fn get_skills_filtered(pool: &SqlitePool, filter: &ListFilter) -> Result<Vec<Skill>> {
+    let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+        "SELECT {} FROM skills WHERE is_archived = 0 AND deleted_at IS NULL",
+        SKILL_COLUMNS
+    ));
+    push_list_filter(&mut query, filter, "skill_tags", "skill_id");
+    query.push(" ORDER BY sort_order ASC, created_at DESC");
+
+    let skills = query.build_query_as::<Skill>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     Ok(skills)
 }
 
-pub async fn get_skill(pool: &SqlitePool, skill_id: &str) -> Result<Option<Skill>> {
-    let skill = sqlx::query_as::<_, Skill>(
-        "SELECT id, name, description, category, proficiency_level, total_hours, created_at, updated_at, encrypted_notes, nonce_notes
-         FROM skills WHERE id = ?"
-    )
-    .bind(skill_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+pub async fn get_archived_skills(pool: &SqlitePool) -> Result<Vec<Skill>> {
+    let query_str = format!(
+        "SELECT {} FROM skills WHERE is_archived = 1 AND deleted_at IS NULL
+         ORDER BY sort_order ASC, created_at DESC",
+        SKILL_COLUMNS
+    );
+
+    let skills = sqlx::query_as::<_, Skill>(&query_str)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(skills)
+}
+
+/// Lists trashed skills that can still be restored, most recently deleted first
+pub async fn get_trashed_skills(pool: &SqlitePool) -> Result<Vec<Skill>> {
+    let query_str = format!(
+        "SELECT {} FROM skills WHERE deleted_at IS NOT NULL AND deleted_at >= ?
+         ORDER BY deleted_at DESC",
+        SKILL_COLUMNS
+    );
+
+    let skills = sqlx::query_as::<_, Skill>(&query_str)
+        .bind(trash_cutoff())
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(skills)
+}
+
+pub async fn get_skill<'e, E>(executor: E, skill_id: &str) -> Result<Option<Skill>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let query_str = format!("SELECT {} FROM skills WHERE id = ?", SKILL_COLUMNS);
+
+    let skill = sqlx::query_as::<_, Skill>(&query_str)
+        .bind(skill_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     Ok(skill)
 }
 
-pub async fn update_skill(
-    pool: &SqlitePool,
-    skill_id: &str,
-    name: Option<String>,
-    category: Option<String>,
-    description: Option<String>,
-    proficiency_level: Option<i32>,
-    total_hours: Option<f64>,
-) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
-    
-    let mut query_str = String::from("UPDATE skills SET updated_at = ?");
-    let mut args: Vec<String> = vec![now];
+/// Applies a partial update to a skill. A change to the proficiency level or the
+/// total hours is also written to the progress history, and an hours change is
+/// booked as an adjustment session so the total stays derivable from sessions.
+pub async fn update_skill(pool: &SqlitePool, skill_id: &str, update: SkillUpdate) -> Result<Skill> {
+    if let Some(name) = &update.name {
+        if name.trim().is_empty() {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Skill name cannot be empty".to_string(),
+            ));
+        }
+    }
+    if let Some(level) = update.proficiency_level {
+        if !(0..=100).contains(&level) {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Proficiency level must be between 0 and 100".to_string(),
+            ));
+        }
+    }
+    if let Some(hours) = update.total_hours {
+        if !hours.is_finite() || hours < 0.0 {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Total hours must be a non-negative number".to_string(),
+            ));
+        }
+    }
+
+    let skill = get_skill(pool, skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)))?;
 
-    if let Some(n) = name {
-        query_str.push_str(", name = ?");
-        args.push(n);
+    let new_level = update.proficiency_level.unwrap_or(skill.proficiency_level);
+    let hours_delta = update.total_hours.map(|h| h - skill.total_hours).unwrap_or(0.0);
+    // Hours are the sum of practice sessions; a decrease would need a negative session
+    if hours_delta <= -1.0 / 3600.0 {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Total hours cannot be lowered below the {:.2} hours of recorded practice",
+            skill.total_hours
+        )));
     }
-    if let Some(c) = category {
-        query_str.push_str(", category = ?");
-        args.push(c);
+    let level_changed = new_level != skill.proficiency_level;
+    let hours_changed = hours_delta.abs() >= 1.0 / 3600.0;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let category = match &update.category {
+        Some(name) => Some(ensure_category(&mut tx, name).await?),
+        None => None,
+    };
+
+    let new_name = update.name.clone().unwrap_or_default();
+    let mut query: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE skills SET updated_at = ");
+    query.push_bind(Utc::now().to_rfc3339());
+    if let Some(name) = update.name {
+        query.push(", name = ").push_bind(name);
     }
-    if let Some(d) = description {
-        query_str.push_str(", description = ?");
-        args.push(d);
+    if let Some(category) = category {
+        query.push(", category = ").push_bind(category.name);
+        query.push(", category_id = ").push_bind(category.id);
     }
-    if let Some(p) = proficiency_level {
-        query_str.push_str(", proficiency_level = ?");
-        args.push(p.to_string());
+    if let Some(description) = update.description {
+        query.push(", description = ").push_bind(description);
     }
-    if let Some(h) = total_hours {
-        query_str.push_str(", total_hours = ?");
-        args.push(h.to_string());
+    if let Some(level) = update.proficiency_level {
+        query.push(", proficiency_level = ").push_bind(level);
     }
+    query.push(" WHERE id = ").push_bind(skill_id);
 
-    query_str.push_str(" WHERE id = ?");
-    args.push(skill_id.to_string());
+    query.build()
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "skills", "skill", &new_name))?;
+
+    if level_changed || hours_changed {
+        let progress = SkillProgress::new(skill_id.to_string(), new_level, if hours_changed { hours_delta } else { 0.0 });
+        insert_skill_progress(&mut tx, &progress).await?;
 
-    // Execute dynamic query
-    let mut query = sqlx::query(&query_str);
-    for arg in args {
-        query = query.bind(arg);
+        if hours_changed {
+            let mut adjustment = PracticeSession::start(skill_id.to_string(), None);
+            adjustment.started_at = progress.recorded_at.clone();
+            adjustment.ended_at = Some(progress.recorded_at.clone());
+            adjustment.duration_seconds = (hours_delta * 3600.0).round() as i64;
+            adjustment.notes = Some("Manual adjustment".to_string());
+            adjustment.source_type = "progress".to_string();
+            adjustment.source_id = Some(progress.id.clone());
+            insert_practice_session(&mut tx, &adjustment).await?;
+        }
+
+        recompute_skill_hours(&mut tx, skill_id).await?;
     }
 
-    query.execute(pool)
+    let updated = get_skill(&mut *tx, skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)))?;
+    record_activity(&mut tx, "skill_updated", "skill", skill_id, format!("Updated skill {}", updated.name), &updated).await?;
+
+    let unlocked = check_achievements(&mut tx).await?;
+
+    tx.commit().await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+    notify_unlocked(&unlocked);
 
-    Ok(())
+    Ok(updated)
 }
 
+/// Moves a skill to the trash; its history is kept until the trash is purged
 pub async fn delete_skill(pool: &SqlitePool, skill_id: &str) -> Result<()> {
-    sqlx::query("DELETE FROM skills WHERE id = ?")
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    move_to_trash(&mut tx, "skills", skill_id).await?;
+    record_skill_activity(&mut tx, skill_id, "skill_deleted", "Moved to the trash:").await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+pub async fn restore_skill(pool: &SqlitePool, skill_id: &str) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    restore_from_trash(&mut tx, "skills", skill_id).await?;
+    record_skill_activity(&mut tx, skill_id, "skill_restored", "Restored skill").await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+pub async fn set_skill_archived(pool: &SqlitePool, skill_id: &str, archived: bool) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    set_archived(&mut tx, "skills", skill_id, archived).await?;
+    if archived {
+        record_skill_activity(&mut tx, skill_id, "skill_archived", "Archived skill").await?;
+    } else {
+        record_skill_activity(&mut tx, skill_id, "skill_unarchived", "Unarchived skill").await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+/// Logs `event_type` for a skill, with the skill's name appended to `summary`
+async fn record_skill_activity(conn: &mut SqliteConnection, skill_id: &str, event_type: &str, summary: &str) -> Result<()> {
+    let skill = get_skill(&mut *conn, skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)))?;
+
+    record_activity(conn, event_type, "skill", skill_id, format!("{} {}", summary, skill.name), &skill).await
+}
+
+/// Switches between the hand-entered proficiency ("manual") and the level
+/// computed from practice ("computed"). The manual value is kept either way.
+pub async fn set_skill_level_mode(pool: &SqlitePool, skill_id: &str, mode: &str) -> Result<()> {
+    if !["manual", "computed"].contains(&mode) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Level mode must be 'manual' or 'computed'".to_string(),
+        ));
+    }
+
+    let result = sqlx::query("UPDATE skills SET level_mode = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(mode)
+        .bind(Utc::now().to_rfc3339())
         .bind(skill_id)
         .execute(pool)
         .await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)));
+    }
+
     Ok(())
 }
 
+/// Stores a user-defined order; `skill_ids` lists skills from first to last
+pub async fn reorder_skills(pool: &SqlitePool, skill_ids: &[String]) -> Result<()> {
+    reorder(pool, "skills", skill_ids).await
+}
+
 // ============ HABIT OPERATIONS ============
 
 pub async fn create_habit(
     pool: &SqlitePool,
     name: String,
     frequency: String,
+    polarity: String,
+    category: Option<String>,
     description: Option<String>,
 ) -> Result<Habit> {
-    let habit = Habit::new(name, frequency);
+    let mut habit = Habit::new(name, frequency);
+    habit.polarity = polarity;
+    habit.description = description;
+    habit.sort_order = top_sort_order(pool, "habits").await?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if let Some(category) = category {
+        habit.category_id = Some(ensure_category(&mut tx, &category).await?.id);
+    }
 
     sqlx::query(
-        "INSERT INTO habits (id, name, description, frequency, streak_count, best_streak, created_at, last_completed)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO habits (id, name, description, frequency, polarity, streak_count, best_streak, created_at, last_completed, sort_order, category_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&habit.id)
     .bind(&habit.name)
-    .bind(&description)
+    .bind(&habit.description)
     .bind(&habit.frequency)
+    .bind(&habit.polarity)
     .bind(habit.streak_count)
     .bind(habit.best_streak)
     .bind(&habit.created_at)
     .bind(&habit.last_completed)
-    .execute(pool)
+    .bind(habit.sort_order)
+    .bind(&habit.category_id)
+    .execute(&mut *tx)
     .await
-    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    .map_err(|e| crate::error::SkillEdError::from_name_write(e, "habits", "habit", &habit.name))?;
+
+    record_activity(&mut tx, "habit_created", "habit", &habit.id, format!("Created habit {}", habit.name), &habit).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
 
     Ok(habit)
 }
 
+/// Lists active habits in the user's order, leaving out archived and trashed ones
 pub async fn get_all_habits(pool: &SqlitePool) -> Result<Vec<Habit>> {
-    let habits = sqlx::query_as::<_, Habit>(
-        "SELECT id, name, description, frequency, streak_count, best_streak, created_at, last_completed
-         FROM habits ORDER BY created_at DESC"
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    get_habits_filtered(pool, &ListFilter::default()).await
+}
+
+/// Lists active habits matching the category and tag sets in `filter`
+pub async fn get_habits_filtered(pool: &SqlitePool, filter: &ListFilter) -> Result<Vec<Habit>> {
+    let mut query: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+        "SELECT {} FROM habits WHERE is_archived = 0 AND deleted_at IS NULL",
+        HABIT_COLUMNS
+    ));
+    push_list_filter(&mut query, filter, "habit_tags", "habit_id");
+    query.push(" ORDER BY sort_order ASC, created_at DESC");
 
-    Ok(habits)
+    let habits = query.build_query_as::<Habit>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    habits.into_iter().map(with_clean_streak).collect()
 }
 
-pub async fn get_habit(pool: &SqlitePool, habit_id: &str) -> Result<Option<Habit>> {
-    let habit = sqlx::query_as::<_, Habit>(
-        "SELECT id, name, description, frequency, streak_count, best_streak, created_at, last_completed
-         FROM habits WHERE id = ?"
-    )
-    .bind(habit_id)
-    .fetch_optional(pool)
-    .await
-    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+pub async fn get_archived_habits(pool: &SqlitePool) -> Result<Vec<Habit>> {
+    let query_str = format!(
+        "SELECT {} FROM habits WHERE is_archived = 1 AND deleted_at IS NULL
+         ORDER BY sort_order ASC, created_at DESC",
+        HABIT_COLUMNS
+    );
 
-    Ok(habit)
+    let habits = sqlx::query_as::<_, Habit>(&query_str)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    habits.into_iter().map(with_clean_streak).collect()
 }
 
-pub async fn delete_habit(pool: &SqlitePool, habit_id: &str) -> Result<()> {
-    sqlx::query("DELETE FROM habits WHERE id = ?")
+/// Lists trashed habits that can still be restored, most recently deleted first
+pub async fn get_trashed_habits(pool: &SqlitePool) -> Result<Vec<Habit>> {
+    let query_str = format!(
+        "SELECT {} FROM habits WHERE deleted_at IS NOT NULL AND deleted_at >= ?
+         ORDER BY deleted_at DESC",
+        HABIT_COLUMNS
+    );
+
+    let habits = sqlx::query_as::<_, Habit>(&query_str)
+        .bind(trash_cutoff())
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    habits.into_iter().map(with_clean_streak).collect()
+}
+
+pub async fn get_habit<'e, E>(executor: E, habit_id: &str) -> Result<Option<Habit>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let query_str = format!("SELECT {} FROM habits WHERE id = ?", HABIT_COLUMNS);
+
+    let habit = sqlx::query_as::<_, Habit>(&query_str)
         .bind(habit_id)
-        .execute(pool)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    habit.map(with_clean_streak).transpose()
+}
+
+/// Applies a partial update to a habit
+pub async fn update_habit(pool: &SqlitePool, habit_id: &str, update: HabitUpdate) -> Result<Habit> {
+    if let Some(name) = &update.name {
+        if name.trim().is_empty() {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Habit name cannot be empty".to_string(),
+            ));
+        }
+    }
+    if let Some(frequency) = &update.frequency {
+        if !["daily", "weekly", "monthly"].contains(&frequency.as_str()) {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Invalid frequency. Must be 'daily', 'weekly', or 'monthly'".to_string(),
+            ));
+        }
+    }
+
+    if !update.is_empty() {
+        let mut tx = pool.begin().await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        let category = match &update.category {
+            Some(name) => Some(ensure_category(&mut tx, name).await?),
+            None => None,
+        };
+
+        let new_name = update.name.clone().unwrap_or_default();
+        let mut query: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE habits SET ");
+        let mut fields = query.separated(", ");
+        if let Some(name) = update.name {
+            fields.push("name = ").push_bind_unseparated(name);
+        }
+        if let Some(description) = update.description {
+            fields.push("description = ").push_bind_unseparated(description);
+        }
+        if let Some(frequency) = update.frequency {
+            fields.push("frequency = ").push_bind_unseparated(frequency);
+        }
+        if let Some(category) = category {
+            fields.push("category_id = ").push_bind_unseparated(category.id);
+        }
+        query.push(" WHERE id = ").push_bind(habit_id);
+
+        query.build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| crate::error::SkillEdError::from_name_write(e, "habits", "habit", &new_name))?;
+
+        record_habit_activity(&mut tx, habit_id, "habit_updated", "Updated habit").await?;
+
+        tx.commit().await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+        notify_changes();
+    }
+
+    get_habit(pool, habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))
+}
+
+/// Moves a habit to the trash; its completions are kept until the trash is purged
+pub async fn delete_habit(pool: &SqlitePool, habit_id: &str) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    move_to_trash(&mut tx, "habits", habit_id).await?;
+    record_habit_activity(&mut tx, habit_id, "habit_deleted", "Moved to the trash:").await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+pub async fn restore_habit(pool: &SqlitePool, habit_id: &str) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    restore_from_trash(&mut tx, "habits", habit_id).await?;
+    record_habit_activity(&mut tx, habit_id, "habit_restored", "Restored habit").await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+pub async fn set_habit_archived(pool: &SqlitePool, habit_id: &str, archived: bool) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    set_archived(&mut tx, "habits", habit_id, archived).await?;
+    if archived {
+        record_habit_activity(&mut tx, habit_id, "habit_archived", "Archived habit").await?;
+    } else {
+        record_habit_activity(&mut tx, habit_id, "habit_unarchived", "Unarchived habit").await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+/// Logs `event_type` for a habit, with the habit's name appended to `summary`
+async fn record_habit_activity(conn: &mut SqliteConnection, habit_id: &str, event_type: &str, summary: &str) -> Result<()> {
+    let habit = get_habit(&mut *conn, habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))?;
+
+    record_activity(conn, event_type, "habit", habit_id, format!("{} {}", summary, habit.name), &habit).await
+}
+
+/// Stores a user-defined order; `habit_ids` lists habits from first to last
+pub async fn reorder_habits(pool: &SqlitePool, habit_ids: &[String]) -> Result<()> {
+    reorder(pool, "habits", habit_ids).await
+}
+
+// ============ ARCHIVE, TRASH AND ORDERING HELPERS ============
+
+/// Permanently removes skills and habits that have been in the trash longer than
+/// `TRASH_RETENTION_DAYS`. Their history goes with them through the cascades.
+pub async fn purge_expired_trash(pool: &SqlitePool) -> Result<u64> {
+    let cutoff = trash_cutoff();
+    let mut purged = 0;
+
+    for table in ["skills", "habits"] {
+        let query_str = format!("DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at < ?", table);
+        purged += sqlx::query(&query_str)
+            .bind(&cutoff)
+            .execute(pool)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+            .rows_affected();
+    }
+
+    Ok(purged)
+}
+
+/// Appends the category and tag conditions of `filter` to a list query
+fn push_list_filter(query: &mut QueryBuilder<Sqlite>, filter: &ListFilter, tag_table: &str, tag_column: &str) {
+    if !filter.category_ids.is_empty() {
+        query.push(" AND category_id IN (");
+        let mut ids = query.separated(", ");
+        for id in &filter.category_ids {
+            ids.push_bind(id.clone());
+        }
+        query.push(")");
+    }
+
+    if !filter.tag_ids.is_empty() {
+        query.push(format!(" AND id IN (SELECT {} FROM {} WHERE tag_id IN (", tag_column, tag_table));
+        let mut ids = query.separated(", ");
+        for id in &filter.tag_ids {
+            ids.push_bind(id.clone());
+        }
+        query.push("))");
+    }
+}
+
+fn trash_cutoff() -> String {
+    (Utc::now() - Duration::days(TRASH_RETENTION_DAYS)).to_rfc3339()
+}
+
+/// Sort order that places a new row above every existing one, keeping newest-first
+/// as the default for lists the user has not reordered
+pub(super) async fn top_sort_order(pool: &SqlitePool, table: &str) -> Result<i32> {
+    let query_str = format!("SELECT COALESCE(MIN(sort_order), 0) - 1 FROM {}", table);
+
+    sqlx::query_scalar::<_, i32>(&query_str)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+async fn move_to_trash(conn: &mut SqliteConnection, table: &str, id: &str) -> Result<()> {
+    let query_str = format!("UPDATE {} SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL", table);
+
+    let result = sqlx::query(&query_str)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("{} {}", table, id)));
+    }
+
+    Ok(())
+}
+
+/// Takes a row out of the trash. Fails when an active row has taken its name in
+/// the meantime.
+async fn restore_from_trash(conn: &mut SqliteConnection, table: &str, id: &str) -> Result<()> {
+    let clash_str = format!(
+        "SELECT active.name FROM {0} trashed JOIN {0} active ON active.name = trashed.name
+         WHERE trashed.id = ? AND active.deleted_at IS NULL",
+        table
+    );
+    let clash = sqlx::query_scalar::<_, String>(&clash_str)
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if let Some(name) = clash {
+        return Err(crate::error::SkillEdError::DuplicateName {
+            entity: table.trim_end_matches('s').to_string(),
+            name,
+        });
+    }
+
+    let query_str = format!(
+        "UPDATE {} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL AND deleted_at >= ?",
+        table
+    );
+
+    let result = sqlx::query(&query_str)
+        .bind(id)
+        .bind(trash_cutoff())
+        .execute(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!(
+            "{} {} is not in the trash or can no longer be restored",
+            table, id
+        )));
+    }
+
+    Ok(())
+}
+
+async fn set_archived(conn: &mut SqliteConnection, table: &str, id: &str, archived: bool) -> Result<()> {
+    let query_str = format!("UPDATE {} SET is_archived = ? WHERE id = ? AND deleted_at IS NULL", table);
+
+    let result = sqlx::query(&query_str)
+        .bind(archived)
+        .bind(id)
+        .execute(conn)
         .await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("{} {}", table, id)));
+    }
+
+    Ok(())
+}
+
+/// Writes `ids` positions as the sort order. Every id must be a row of `table`
+/// outside the trash; otherwise nothing is changed. The logged event concerns the
+/// whole list, so its entity id is empty.
+async fn reorder(pool: &SqlitePool, table: &str, ids: &[String]) -> Result<()> {
+    let query_str = format!("UPDATE {} SET sort_order = ? WHERE id = ? AND deleted_at IS NULL", table);
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    for (position, id) in ids.iter().enumerate() {
+        let result = sqlx::query(&query_str)
+            .bind(position as i32)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        // Dropping the transaction rolls back the positions already written
+        if result.rows_affected() == 0 {
+            return Err(crate::error::SkillEdError::NotFound(format!("{} {}", table, id)));
+        }
+    }
+
+    record_activity(
+        &mut tx,
+        &format!("{}_reordered", table),
+        table.trim_end_matches('s'),
+        "",
+        format!("Reordered {}", table),
+        &ids,
+    )
+    .await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
     Ok(())
 }
 
@@ -187,21 +748,62 @@ pub async fn log_habit_completion(
     pool: &SqlitePool,
     habit_id: &str,
 ) -> Result<HabitCompletion> {
-    let completion = HabitCompletion::new(habit_id.to_string());
+    let habit = get_habit(pool, habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))?;
+
+    // Logging a negative habit records a relapse rather than a completion
+    let mut completion = HabitCompletion::new(habit_id.to_string());
+    if habit.polarity == "negative" {
+        completion.completion_type = "relapse".to_string();
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     sqlx::query(
-        "INSERT INTO habit_completions (id, habit_id, completed_at)
-         VALUES (?, ?, ?)"
+        "INSERT INTO habit_completions (id, habit_id, completed_at, completion_type)
+         VALUES (?, ?, ?, ?)"
     )
     .bind(&completion.id)
     .bind(&completion.habit_id)
     .bind(&completion.completed_at)
-    .execute(pool)
+    .bind(&completion.completion_type)
+    .execute(&mut *tx)
     .await
     .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     // Update habit last_completed and streak
-    update_habit_streak(pool, habit_id).await?;
+    let streak = update_habit_streak(&mut tx, &habit, &completion.completed_at).await?;
+
+    // Relapses never count as practice or earn XP
+    if completion.completion_type == "completion" {
+        let config = get_xp_config(&mut *tx).await?;
+        let multiplier = config.streak_multiplier(streak);
+
+        let mut event = XpEvent::new(
+            "habit_completion",
+            completion.id.clone(),
+            None,
+            config.xp_per_habit_completion,
+            multiplier,
+            format!("Completed {}", habit.name),
+        );
+        event.created_at = completion.completed_at.clone();
+        append_xp(&mut tx, &event).await?;
+
+        apply_habit_skill_links(&mut tx, &habit, &completion, multiplier).await?;
+        record_activity(&mut tx, "habit_completed", "habit", &habit.id, format!("Completed {}", habit.name), &completion).await?;
+    } else {
+        record_activity(&mut tx, "habit_relapsed", "habit", &habit.id, format!("Relapsed on {}", habit.name), &completion).await?;
+    }
+
+    let unlocked = check_achievements(&mut tx).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+    notify_unlocked(&unlocked);
 
     Ok(completion)
 }
@@ -211,7 +813,7 @@ pub async fn get_habit_completions(
     habit_id: &str,
 ) -> Result<Vec<HabitCompletion>> {
     let completions = sqlx::query_as::<_, HabitCompletion>(
-        "SELECT id, habit_id, completed_at FROM habit_completions
+        "SELECT id, habit_id, completed_at, completion_type FROM habit_completions
          WHERE habit_id = ? ORDER BY completed_at DESC"
     )
     .bind(habit_id)
@@ -222,83 +824,676 @@ pub async fn get_habit_completions(
     Ok(completions)
 }
 
-async fn update_habit_streak(pool: &SqlitePool, habit_id: &str) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
+/// Records the completion on the habit and returns its streak afterwards
+async fn update_habit_streak(conn: &mut SqliteConnection, habit: &Habit, completed_at: &str) -> Result<i32> {
+    if habit.polarity == "negative" {
+        // A relapse closes the running clean period and resets the streak
+        let clean_since = parse_timestamp(habit.last_completed.as_deref().unwrap_or(&habit.created_at))?;
+        let clean_days = (parse_timestamp(completed_at)? - clean_since).num_days().max(0);
 
-    sqlx::query("UPDATE habits SET last_completed = ? WHERE id = ?")
-        .bind(&now)
-        .bind(habit_id)
-        .execute(pool)
+        sqlx::query(
+            "UPDATE habits SET last_completed = ?, streak_count = 0, best_streak = MAX(best_streak, ?) WHERE id = ?"
+        )
+        .bind(completed_at)
+        .bind(clean_days)
+        .bind(&habit.id)
+        .execute(conn)
         .await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
-    Ok(())
-}
+        return Ok(0);
+    }
 
-// ============ SKILL PROGRESS OPERATIONS ============
+    let last = habit.last_completed.as_deref().map(local_date).transpose()?;
+    let streak = next_streak(&habit.frequency, last, local_date(completed_at)?, habit.streak_count);
 
-pub async fn record_skill_progress(
+    sqlx::query(
+        "UPDATE habits SET last_completed = ?, streak_count = ?, best_streak = MAX(best_streak, ?) WHERE id = ?"
+    )
+    .bind(completed_at)
+    .bind(streak)
+    .bind(streak)
+    .bind(&habit.id)
+    .execute(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(streak)
+}
+
+/// Extends the streak when the previous completion fell in the period just before
+/// `today`, keeps it within the same period, and restarts it after a gap
+pub(super) fn next_streak(frequency: &str, last: Option<NaiveDate>, today: NaiveDate, streak: i32) -> i32 {
+    let Some(last) = last else {
+        return 1;
+    };
+
+    match period_index(frequency, today) - period_index(frequency, last) {
+        0 => streak.max(1),
+        1 => streak + 1,
+        _ => 1,
+    }
+}
+
+/// The stored streak, or 0 once a whole period has passed without a completion.
+/// A negative habit's streak is its clean streak, which only a relapse resets.
+pub fn current_streak(habit: &Habit) -> Result<i32> {
+    if habit.polarity == "negative" {
+        return Ok(habit.streak_count);
+    }
+
+    let Some(last) = habit.last_completed.as_deref() else {
+        return Ok(0);
+    };
+
+    let today = Local::now().date_naive();
+    if period_index(&habit.frequency, today) - period_index(&habit.frequency, local_date(last)?) > 1 {
+        return Ok(0);
+    }
+
+    Ok(habit.streak_count)
+}
+
+/// Sequential number of the day, ISO week or month containing `date`
+pub(super) fn period_index(frequency: &str, date: NaiveDate) -> i64 {
+    let day = date.num_days_from_ce() as i64;
+    match frequency {
+        "weekly" => (day - date.weekday().num_days_from_monday() as i64) / 7,
+        "monthly" => date.year() as i64 * 12 + date.month0() as i64,
+        _ => day,
+    }
+}
+
+/// Whether a positive habit was completed in the day, week or month containing
+/// `date`. Negative habits are never "done".
+pub(super) fn done_in_period(habit: &Habit, date: NaiveDate) -> Result<bool> {
+    match habit.last_completed.as_deref() {
+        Some(last) if habit.polarity == "positive" => {
+            Ok(period_index(&habit.frequency, local_date(last)?) == period_index(&habit.frequency, date))
+        }
+        _ => Ok(false),
+    }
+}
+
+pub(super) fn local_date(value: &str) -> Result<NaiveDate> {
+    Ok(parse_timestamp(value)?.with_timezone(&Local).date_naive())
+}
+
+/// Books a completion's contribution to every linked skill as a practice session
+/// ending at the completion time, plus the matching progress entry and XP
+async fn apply_habit_skill_links(
+    conn: &mut SqliteConnection,
+    habit: &Habit,
+    completion: &HabitCompletion,
+    multiplier: f64,
+) -> Result<()> {
+    let links = sqlx::query_as::<_, (String, i32, i32, i32)>(
+        "SELECT l.skill_id, l.minutes_per_completion, l.xp_per_completion, s.proficiency_level
+         FROM habit_skill_links l JOIN skills s ON s.id = l.skill_id
+         WHERE l.habit_id = ? AND s.deleted_at IS NULL"
+    )
+    .bind(&habit.id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let completed_at = parse_timestamp(&completion.completed_at)?;
+
+    for (skill_id, minutes, xp, proficiency_level) in links {
+        let mut event = XpEvent::new(
+            "habit_completion",
+            completion.id.clone(),
+            Some(skill_id.clone()),
+            xp as i64,
+            multiplier,
+            format!("Completed {}", habit.name),
+        );
+        event.created_at = completion.completed_at.clone();
+        append_xp(&mut *conn, &event).await?;
+
+        if minutes == 0 {
+            continue;
+        }
+
+        let mut session = PracticeSession::start(skill_id.clone(), None);
+        session.started_at = (completed_at - Duration::minutes(minutes as i64)).to_rfc3339();
+        session.ended_at = Some(completion.completed_at.clone());
+        session.duration_seconds = minutes as i64 * 60;
+        session.notes = Some(format!("Habit: {}", habit.name));
+        session.source_type = "habit_completion".to_string();
+        session.source_id = Some(completion.id.clone());
+        insert_practice_session(&mut *conn, &session).await?;
+
+        let mut progress = SkillProgress::new(skill_id.clone(), proficiency_level, minutes as f64 / 60.0);
+        progress.recorded_at = completion.completed_at.clone();
+        progress.source_type = "habit_completion".to_string();
+        progress.source_id = Some(completion.id.clone());
+        insert_skill_progress(&mut *conn, &progress).await?;
+
+        recompute_skill_hours(&mut *conn, &skill_id).await?;
+    }
+
+    Ok(())
+}
+
+// ============ HABIT-SKILL LINK OPERATIONS ============
+
+/// Links a habit to a skill, or updates the contribution of an existing link
+pub async fn link_habit_to_skill(
+    pool: &SqlitePool,
+    habit_id: &str,
+    skill_id: &str,
+    minutes_per_completion: i32,
+    xp_per_completion: i32,
+) -> Result<HabitSkillLink> {
+    if minutes_per_completion < 0 || xp_per_completion < 0 {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Minutes and XP per completion cannot be negative".to_string(),
+        ));
+    }
+
+    let habit = get_habit(pool, habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))?;
+    if habit.polarity == "negative" {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Negative habits cannot contribute to skills".to_string(),
+        ));
+    }
+    if get_skill(pool, skill_id).await?.is_none() {
+        return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)));
+    }
+
+    let link = HabitSkillLink {
+        habit_id: habit_id.to_string(),
+        skill_id: skill_id.to_string(),
+        minutes_per_completion,
+        xp_per_completion,
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    sqlx::query(
+        "INSERT INTO habit_skill_links (habit_id, skill_id, minutes_per_completion, xp_per_completion, created_at)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(habit_id, skill_id) DO UPDATE SET
+             minutes_per_completion = excluded.minutes_per_completion,
+             xp_per_completion = excluded.xp_per_completion"
+    )
+    .bind(&link.habit_id)
+    .bind(&link.skill_id)
+    .bind(link.minutes_per_completion)
+    .bind(link.xp_per_completion)
+    .bind(&link.created_at)
+    .execute(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(link)
+}
+
+pub async fn unlink_habit_from_skill(pool: &SqlitePool, habit_id: &str, skill_id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM habit_skill_links WHERE habit_id = ? AND skill_id = ?")
+        .bind(habit_id)
+        .bind(skill_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn get_habit_skill_links(pool: &SqlitePool, habit_id: &str) -> Result<Vec<HabitSkillLink>> {
+    let links = sqlx::query_as::<_, HabitSkillLink>(
+        "SELECT habit_id, skill_id, minutes_per_completion, xp_per_completion, created_at
+         FROM habit_skill_links WHERE habit_id = ? ORDER BY created_at ASC"
+    )
+    .bind(habit_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(links)
+}
+
+// ============ NEGATIVE HABIT OPERATIONS ============
+
+/// Computes time since the last relapse, the longest clean period and the relapse count
+pub async fn get_clean_streak_stats(pool: &SqlitePool, habit_id: &str) -> Result<CleanStreakStats> {
+    let habit = get_negative_habit(pool, habit_id).await?;
+
+    let relapses = sqlx::query_scalar::<_, String>(
+        "SELECT completed_at FROM habit_completions
+         WHERE habit_id = ? AND completion_type = 'relapse' ORDER BY completed_at ASC"
+    )
+    .bind(habit_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let relapse_times = relapses
+        .iter()
+        .map(|r| parse_timestamp(r))
+        .collect::<Result<Vec<_>>>()?;
+
+    let now = Utc::now();
+    let periods = clean_periods(parse_timestamp(&habit.created_at)?, &relapse_times, now);
+    let current = periods.last().copied().unwrap_or_else(Duration::zero);
+    let longest = periods.iter().copied().max().unwrap_or_else(Duration::zero);
+
+    Ok(CleanStreakStats {
+        habit_id: habit.id,
+        last_relapse: relapses.last().cloned(),
+        seconds_since_last_relapse: current.num_seconds(),
+        current_clean_days: current.num_days(),
+        longest_clean_days: longest.num_days(),
+        total_relapses: relapses.len() as i64,
+    })
+}
+
+/// Counts relapses per week (starting Monday) or per month
+pub async fn get_relapse_trend(
+    pool: &SqlitePool,
+    habit_id: &str,
+    period: &str,
+) -> Result<Vec<RelapseTrendPoint>> {
+    get_negative_habit(pool, habit_id).await?;
+
+    let bucket = match period {
+        "weekly" => "date(completed_at, 'localtime', 'weekday 0', '-6 days')",
+        "monthly" => "strftime('%Y-%m-01', completed_at, 'localtime')",
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Invalid period. Must be 'weekly' or 'monthly'".to_string(),
+            ))
+        }
+    };
+
+    let query_str = format!(
+        "SELECT {} AS period_start, COUNT(*) AS relapse_count FROM habit_completions
+         WHERE habit_id = ? AND completion_type = 'relapse'
+         GROUP BY period_start ORDER BY period_start ASC",
+        bucket
+    );
+
+    let trend = sqlx::query_as::<_, RelapseTrendPoint>(&query_str)
+        .bind(habit_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(trend)
+}
+
+async fn get_negative_habit(pool: &SqlitePool, habit_id: &str) -> Result<Habit> {
+    let habit = get_habit(pool, habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))?;
+
+    if habit.polarity != "negative" {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Clean streaks are only tracked for negative habits".to_string(),
+        ));
+    }
+
+    Ok(habit)
+}
+
+/// A negative habit's streak is the whole days since its last relapse (or since it
+/// was created); the stored count is only reset when a relapse is logged
+pub(super) fn with_clean_streak(mut habit: Habit) -> Result<Habit> {
+    if habit.polarity == "negative" {
+        let clean_since = parse_timestamp(habit.last_completed.as_deref().unwrap_or(&habit.created_at))?;
+        let clean_days = (Utc::now() - clean_since).num_days().max(0) as i32;
+        habit.streak_count = clean_days;
+        habit.best_streak = habit.best_streak.max(clean_days);
+    }
+
+    Ok(habit)
+}
+
+/// Splits the time between `start` and `now` into the clean periods separated by relapses.
+/// The last entry is the currently running period.
+fn clean_periods(start: DateTime<Utc>, relapses: &[DateTime<Utc>], now: DateTime<Utc>) -> Vec<Duration> {
+    let mut periods = Vec::with_capacity(relapses.len() + 1);
+    let mut clean_since = start;
+
+    for relapse in relapses {
+        periods.push((*relapse - clean_since).max(Duration::zero()));
+        clean_since = *relapse;
+    }
+    periods.push((now - clean_since).max(Duration::zero()));
+
+    periods
+}
+
+pub(super) fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(format!("Invalid timestamp '{}': {}", value, e)))
+}
+
+// ============ SKILL PROGRESS OPERATIONS ============
+
+pub async fn record_skill_progress(
     pool: &SqlitePool,
     skill_id: &str,
     proficiency_level: i32,
     hours_practiced: f64,
 ) -> Result<SkillProgress> {
+    if !(0..=100).contains(&proficiency_level) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Proficiency level must be between 0 and 100".to_string(),
+        ));
+    }
+
+    if hours_practiced < 0.0 {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Hours practiced cannot be negative".to_string(),
+        ));
+    }
+
     let progress = SkillProgress::new(skill_id.to_string(), proficiency_level, hours_practiced);
 
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let skill = get_skill(&mut *tx, skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)))?;
+
+    insert_skill_progress(&mut tx, &progress).await?;
+
+    // Hours logged by hand become a completed session ending now, so the
+    // total can still be derived from sessions alone
+    if hours_practiced > 0.0 {
+        let duration_seconds = (hours_practiced * 3600.0).round() as i64;
+        let ended_at = parse_timestamp(&progress.recorded_at)?;
+        let mut session = PracticeSession::start(skill_id.to_string(), None);
+        session.started_at = (ended_at - Duration::seconds(duration_seconds)).to_rfc3339();
+        session.ended_at = Some(progress.recorded_at.clone());
+        session.duration_seconds = duration_seconds;
+        session.source_type = "progress".to_string();
+        session.source_id = Some(progress.id.clone());
+        insert_practice_session(&mut tx, &session).await?;
+    }
+
+    // Update skill totals
+    update_skill_totals(&mut tx, skill_id).await?;
+    record_activity(&mut tx, "progress_logged", "skill", skill_id, format!("Logged progress on {}", skill.name), &progress).await?;
+
+    let unlocked = check_achievements(&mut tx).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+    notify_unlocked(&unlocked);
+
+    Ok(progress)
+}
+
+pub async fn get_skill_progress_history(
+    pool: &SqlitePool,
+    skill_id: &str,
+) -> Result<Vec<SkillProgress>> {
+    let query_str = format!(
+        "SELECT {} FROM skill_progress WHERE skill_id = ? ORDER BY recorded_at DESC",
+        PROGRESS_COLUMNS
+    );
+
+    let progress = sqlx::query_as::<_, SkillProgress>(&query_str)
+    .bind(skill_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(progress)
+}
+
+async fn insert_skill_progress(conn: &mut SqliteConnection, progress: &SkillProgress) -> Result<()> {
     sqlx::query(
-        "INSERT INTO skill_progress (id, skill_id, proficiency_level, hours_practiced, recorded_at)
-         VALUES (?, ?, ?, ?, ?)"
+        "INSERT INTO skill_progress (id, skill_id, proficiency_level, hours_practiced, recorded_at, source_type, source_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&progress.id)
     .bind(&progress.skill_id)
     .bind(progress.proficiency_level)
     .bind(progress.hours_practiced)
     .bind(&progress.recorded_at)
-    .execute(pool)
+    .bind(&progress.source_type)
+    .bind(&progress.source_id)
+    .execute(conn)
     .await
     .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
-    // Update skill totals
-    update_skill_totals(pool, skill_id, proficiency_level, hours_practiced).await?;
+    Ok(())
+}
 
-    Ok(progress)
+/// Rederives the skill's level from its most recent progress entry (so a
+/// back-dated entry cannot overwrite a newer level) and its hours from sessions
+async fn update_skill_totals(conn: &mut SqliteConnection, skill_id: &str) -> Result<()> {
+    let now = Utc::now().to_rfc3339();
+
+    sqlx::query(
+        "UPDATE skills SET proficiency_level = COALESCE((
+             SELECT proficiency_level FROM skill_progress
+             WHERE skill_id = ? ORDER BY recorded_at DESC LIMIT 1
+         ), proficiency_level), updated_at = ? WHERE id = ?"
+    )
+    .bind(skill_id)
+    .bind(&now)
+    .bind(skill_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    recompute_skill_hours(conn, skill_id).await
 }
 
-pub async fn get_skill_progress_history(
+/// Rederives `skills.total_hours` from the skill's completed practice sessions
+async fn recompute_skill_hours(conn: &mut SqliteConnection, skill_id: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE skills SET total_hours = (
+             SELECT COALESCE(SUM(duration_seconds), 0) / 3600.0 FROM practice_sessions
+             WHERE skill_id = ? AND ended_at IS NOT NULL
+         ), updated_at = ? WHERE id = ?"
+    )
+    .bind(skill_id)
+    .bind(Utc::now().to_rfc3339())
+    .bind(skill_id)
+    .execute(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+// ============ PRACTICE SESSION OPERATIONS ============
+
+/// Starts the practice timer for a skill. Only one session can run at a time.
+pub async fn start_session(
     pool: &SqlitePool,
     skill_id: &str,
-) -> Result<Vec<SkillProgress>> {
-    let progress = sqlx::query_as::<_, SkillProgress>(
-        "SELECT id, skill_id, proficiency_level, hours_practiced, recorded_at
-         FROM skill_progress WHERE skill_id = ? ORDER BY recorded_at DESC"
+    tags: Option<String>,
+) -> Result<PracticeSession> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let skill_exists = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM skills WHERE id = ? AND deleted_at IS NULL"
     )
     .bind(skill_id)
-    .fetch_all(pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
-    Ok(progress)
+    if skill_exists == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)));
+    }
+
+    let running_skill = sqlx::query_scalar::<_, String>(
+        "SELECT skill_id FROM practice_sessions WHERE ended_at IS NULL"
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if let Some(running_skill) = running_skill {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "A practice session is already running for skill {}",
+            running_skill
+        )));
+    }
+
+    let session = PracticeSession::start(skill_id.to_string(), tags);
+    insert_practice_session(&mut tx, &session).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(session)
 }
 
-async fn update_skill_totals(
+/// Returns the running session, if any. The timer lives in the database, so it
+/// survives app restarts.
+pub async fn get_running_session(pool: &SqlitePool) -> Result<Option<PracticeSession>> {
+    let query_str = format!("SELECT {} FROM practice_sessions WHERE ended_at IS NULL", SESSION_COLUMNS);
+
+    let session = sqlx::query_as::<_, PracticeSession>(&query_str)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(session)
+}
+
+/// Stops the running session, records it in the skill's progress history and
+/// rederives the skill's total hours
+pub async fn stop_session(
     pool: &SqlitePool,
-    skill_id: &str,
-    proficiency_level: i32,
-    hours_practiced: f64,
-) -> Result<()> {
-    let now = Utc::now().to_rfc3339();
+    focus_rating: Option<i32>,
+    notes: Option<String>,
+) -> Result<PracticeSession> {
+    if let Some(rating) = focus_rating {
+        if !(1..=5).contains(&rating) {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Focus rating must be between 1 and 5".to_string(),
+            ));
+        }
+    }
+
+    let mut session = get_running_session(pool)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound("No practice session is running".to_string()))?;
+    let skill = get_skill(pool, &session.skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", session.skill_id)))?;
+
+    let ended_at = Utc::now();
+    session.duration_seconds = (ended_at - parse_timestamp(&session.started_at)?).num_seconds().max(0);
+    session.ended_at = Some(ended_at.to_rfc3339());
+    session.focus_rating = focus_rating;
+    session.notes = notes;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     sqlx::query(
-        "UPDATE skills SET proficiency_level = ?, total_hours = total_hours + ?, updated_at = ? WHERE id = ?"
+        "UPDATE practice_sessions SET ended_at = ?, duration_seconds = ?, focus_rating = ?, notes = ?
+         WHERE id = ?"
     )
-    .bind(proficiency_level)
-    .bind(hours_practiced)
-    .bind(&now)
-    .bind(skill_id)
-    .execute(pool)
+    .bind(&session.ended_at)
+    .bind(session.duration_seconds)
+    .bind(session.focus_rating)
+    .bind(&session.notes)
+    .bind(&session.id)
+    .execute(&mut *tx)
     .await
     .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
+    let mut progress = SkillProgress::new(
+        skill.id.clone(),
+        skill.proficiency_level,
+        session.duration_seconds as f64 / 3600.0,
+    );
+    progress.recorded_at = ended_at.to_rfc3339();
+    progress.source_type = "practice_session".to_string();
+    progress.source_id = Some(session.id.clone());
+    insert_skill_progress(&mut tx, &progress).await?;
+    recompute_skill_hours(&mut tx, &skill.id).await?;
+
+    let config = get_xp_config(&mut *tx).await?;
+    let base_xp = (session.duration_seconds as f64 / 3600.0 * config.xp_per_practice_hour as f64).round() as i64;
+    let mut event = XpEvent::new(
+        "practice_session",
+        session.id.clone(),
+        Some(skill.id.clone()),
+        base_xp,
+        1.0,
+        format!("Practiced {}", skill.name),
+    );
+    event.created_at = ended_at.to_rfc3339();
+    append_xp(&mut tx, &event).await?;
+    record_activity(
+        &mut tx,
+        "session_completed",
+        "practice_session",
+        &session.id,
+        format!("Practiced {} for {} min", skill.name, session.duration_seconds / 60),
+        &session,
+    )
+    .await?;
+
+    let unlocked = check_achievements(&mut tx).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+    notify_unlocked(&unlocked);
+
+    Ok(session)
+}
+
+pub async fn get_practice_sessions(pool: &SqlitePool, skill_id: &str) -> Result<Vec<PracticeSession>> {
+    let query_str = format!(
+        "SELECT {} FROM practice_sessions WHERE skill_id = ? ORDER BY started_at DESC",
+        SESSION_COLUMNS
+    );
+
+    let sessions = sqlx::query_as::<_, PracticeSession>(&query_str)
+        .bind(skill_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(sessions)
+}
+
+async fn insert_practice_session(conn: &mut SqliteConnection, session: &PracticeSession) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds, focus_rating, notes, tags, source_type, source_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&session.id)
+    .bind(&session.skill_id)
+    .bind(&session.started_at)
+    .bind(&session.ended_at)
+    .bind(session.duration_seconds)
+    .bind(session.focus_rating)
+    .bind(&session.notes)
+    .bind(&session.tags)
+    .bind(&session.source_type)
+    .bind(&session.source_id)
+    .execute(conn)
+    .await
+    .map_err(|e| match &e {
+        // A timer started elsewhere since the caller checked for one
+        sqlx::Error::Database(db_err) if db_err.message().contains("idx_practice_sessions_running") => {
+            crate::error::SkillEdError::ValidationError("A practice session is already running".to_string())
+        }
+        _ => crate::error::SkillEdError::DatabaseError(e.to_string()),
+    })?;
+
     Ok(())
 }
 
@@ -311,7 +1506,11 @@ pub async fn create_milestone(
     skill_id: Option<String>,
     description: Option<String>,
 ) -> Result<Milestone> {
-    let milestone = Milestone::new(title, target_date, skill_id);
+    let mut milestone = Milestone::new(title, target_date, skill_id);
+    milestone.description = description;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
     sqlx::query(
         "INSERT INTO milestones (id, skill_id, title, description, target_date, is_completed, created_at)
@@ -320,11 +1519,41 @@ pub async fn create_milestone(
     .bind(&milestone.id)
     .bind(&milestone.skill_id)
     .bind(&milestone.title)
-    .bind(&description)
+    .bind(&milestone.description)
     .bind(&milestone.target_date)
     .bind(milestone.is_completed)
     .bind(&milestone.created_at)
-    .execute(pool)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    record_activity(
+        &mut tx,
+        "milestone_created",
+        "milestone",
+        &milestone.id,
+        format!("Set milestone {}", milestone.title),
+        &milestone,
+    )
+    .await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(milestone)
+}
+
+async fn get_milestone<'e, E>(executor: E, milestone_id: &str) -> Result<Option<Milestone>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let milestone = sqlx::query_as::<_, Milestone>(
+        "SELECT id, skill_id, title, description, target_date, completion_date, is_completed, created_at
+         FROM milestones WHERE id = ?"
+    )
+    .bind(milestone_id)
+    .fetch_optional(executor)
     .await
     .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
@@ -346,22 +1575,482 @@ pub async fn get_all_milestones(pool: &SqlitePool) -> Result<Vec<Milestone>> {
 pub async fn complete_milestone(pool: &SqlitePool, milestone_id: &str) -> Result<()> {
     let now = Utc::now().to_rfc3339();
 
-    sqlx::query("UPDATE milestones SET is_completed = 1, completion_date = ? WHERE id = ?")
-        .bind(&now)
-        .bind(milestone_id)
-        .execute(pool)
-        .await
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let completed = sqlx::query(
+        "UPDATE milestones SET is_completed = 1, completion_date = ? WHERE id = ? AND is_completed = 0"
+    )
+    .bind(&now)
+    .bind(milestone_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+    .rows_affected();
+
+    // XP is only awarded the first time a milestone is completed
+    if completed > 0 {
+        let milestone = get_milestone(&mut *tx, milestone_id)
+            .await?
+            .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Milestone {}", milestone_id)))?;
+
+        let config = get_xp_config(&mut *tx).await?;
+        let mut event = XpEvent::new(
+            "milestone",
+            milestone_id.to_string(),
+            milestone.skill_id.clone(),
+            config.xp_per_milestone,
+            1.0,
+            format!("Reached {}", milestone.title),
+        );
+        event.created_at = now;
+        append_xp(&mut tx, &event).await?;
+
+        record_activity(
+            &mut tx,
+            "milestone_completed",
+            "milestone",
+            milestone_id,
+            format!("Reached milestone {}", milestone.title),
+            &milestone,
+        )
+        .await?;
+    }
+
+    let unlocked = check_achievements(&mut tx).await?;
+
+    tx.commit().await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+    notify_unlocked(&unlocked);
 
     Ok(())
 }
 
 pub async fn delete_milestone(pool: &SqlitePool, milestone_id: &str) -> Result<()> {
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // Deleted milestones are gone for good; the event keeps their last state
+    let Some(milestone) = get_milestone(&mut *tx, milestone_id).await? else {
+        return Ok(());
+    };
+
     sqlx::query("DELETE FROM milestones WHERE id = ?")
         .bind(milestone_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await
         .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
 
+    record_activity(
+        &mut tx,
+        "milestone_deleted",
+        "milestone",
+        milestone_id,
+        format!("Deleted milestone {}", milestone.title),
+        &milestone,
+    )
+    .await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_timestamp(value).unwrap()
+    }
+
+    #[test]
+    fn test_next_streak_by_frequency() {
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+
+        assert_eq!(next_streak("daily", None, day("2024-03-04"), 0), 1);
+        assert_eq!(next_streak("daily", Some(day("2024-03-04")), day("2024-03-04"), 3), 3);
+        assert_eq!(next_streak("daily", Some(day("2024-03-03")), day("2024-03-04"), 3), 4);
+        assert_eq!(next_streak("daily", Some(day("2024-03-01")), day("2024-03-04"), 3), 1);
+
+        // Sunday to the following Monday is the next ISO week
+        assert_eq!(next_streak("weekly", Some(day("2024-03-03")), day("2024-03-04"), 2), 3);
+        assert_eq!(next_streak("weekly", Some(day("2024-02-25")), day("2024-03-04"), 2), 1);
+        assert_eq!(next_streak("monthly", Some(day("2023-12-31")), day("2024-01-15"), 5), 6);
+    }
+
+    #[test]
+    fn test_clean_periods_without_relapse() {
+        let periods = clean_periods(at("2024-01-01T00:00:00Z"), &[], at("2024-01-11T00:00:00Z"));
+        assert_eq!(periods, vec![Duration::days(10)]);
+    }
+
+    #[test]
+    fn test_clean_periods_split_by_relapses() {
+        let relapses = [at("2024-01-04T00:00:00Z"), at("2024-01-20T00:00:00Z")];
+        let periods = clean_periods(at("2024-01-01T00:00:00Z"), &relapses, at("2024-01-22T00:00:00Z"));
+
+        assert_eq!(periods, vec![Duration::days(3), Duration::days(16), Duration::days(2)]);
+        assert_eq!(periods.iter().max(), Some(&Duration::days(16)));
+    }
+
+    #[tokio::test]
+    async fn test_negative_habit_streak_counts_clean_days() {
+        let pool = crate::db::test_pool().await;
+        let habit = create_habit(&pool, "Smoking".to_string(), "daily".to_string(), "negative".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let relapsed_at = (Utc::now() - Duration::days(5) - Duration::hours(1)).to_rfc3339();
+        sqlx::query("UPDATE habits SET last_completed = ?, best_streak = 3 WHERE id = ?")
+            .bind(&relapsed_at)
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let habit = get_habit(&pool, &habit.id).await.unwrap().unwrap();
+        assert_eq!((habit.streak_count, habit.best_streak), (5, 5));
+        // Days without a relapse are the streak, however long ago the last entry was
+        assert_eq!(current_streak(&habit).unwrap(), 5);
+
+        log_habit_completion(&pool, &habit.id).await.unwrap();
+        let habit = get_habit(&pool, &habit.id).await.unwrap().unwrap();
+        assert_eq!((habit.streak_count, habit.best_streak), (0, 5));
+    }
+
+    fn names(skills: &[Skill]) -> Vec<&str> {
+        skills.iter().map(|skill| skill.name.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_trash() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let chess = create_skill(&pool, "Chess".to_string(), "Games".to_string(), None).await.unwrap();
+
+        set_skill_archived(&pool, &piano.id, true).await.unwrap();
+        assert_eq!(names(&get_all_skills(&pool).await.unwrap()), vec!["Chess"]);
+        assert_eq!(names(&get_archived_skills(&pool).await.unwrap()), vec!["Piano"]);
+        set_skill_archived(&pool, &piano.id, false).await.unwrap();
+        assert_eq!(get_all_skills(&pool).await.unwrap().len(), 2);
+
+        delete_skill(&pool, &chess.id).await.unwrap();
+        assert_eq!(names(&get_all_skills(&pool).await.unwrap()), vec!["Piano"]);
+        assert_eq!(names(&get_trashed_skills(&pool).await.unwrap()), vec!["Chess"]);
+        assert!(set_skill_archived(&pool, &chess.id, true).await.is_err());
+        restore_skill(&pool, &chess.id).await.unwrap();
+        assert!(get_trashed_skills(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_trash_expires_after_retention() {
+        let pool = crate::db::test_pool().await;
+        let recent = create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        let expired = create_habit(&pool, "Run".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        delete_habit(&pool, &recent.id).await.unwrap();
+        delete_habit(&pool, &expired.id).await.unwrap();
+
+        let deleted_at = (Utc::now() - Duration::days(TRASH_RETENTION_DAYS + 1)).to_rfc3339();
+        sqlx::query("UPDATE habits SET deleted_at = ? WHERE id = ?")
+            .bind(&deleted_at)
+            .bind(&expired.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(get_trashed_habits(&pool).await.unwrap().len(), 1);
+        assert!(restore_habit(&pool, &expired.id).await.is_err());
+        assert_eq!(purge_expired_trash(&pool).await.unwrap(), 1);
+        assert!(get_habit(&pool, &expired.id).await.unwrap().is_none());
+
+        restore_habit(&pool, &recent.id).await.unwrap();
+        assert_eq!(get_all_habits(&pool).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_trashed_name_can_be_reused() {
+        let pool = crate::db::test_pool().await;
+        let old = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        delete_skill(&pool, &old.id).await.unwrap();
+
+        create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let err = restore_skill(&pool, &old.id).await.unwrap_err();
+        assert_eq!(err.code(), "DUPLICATE_NAME");
+    }
+
+    #[tokio::test]
+    async fn test_reorder() {
+        let pool = crate::db::test_pool().await;
+        let mut ids = Vec::new();
+        for name in ["Piano", "Chess", "Go"] {
+            ids.push(create_skill(&pool, name.to_string(), "Hobby".to_string(), None).await.unwrap().id);
+        }
+        // New skills go on top until the user reorders them
+        assert_eq!(names(&get_all_skills(&pool).await.unwrap()), vec!["Go", "Chess", "Piano"]);
+
+        reorder_skills(&pool, &ids).await.unwrap();
+        assert_eq!(names(&get_all_skills(&pool).await.unwrap()), vec!["Piano", "Chess", "Go"]);
+        let filter = crate::db::ActivityFilter { event_types: vec!["skills_reordered".to_string()], ..Default::default() };
+        assert_eq!(crate::db::get_activity_feed(&pool, &filter, 10, 0).await.unwrap().len(), 1);
+
+        let mut unknown = ids.clone();
+        unknown.reverse();
+        unknown.push("missing".to_string());
+        assert!(matches!(
+            reorder_skills(&pool, &unknown).await,
+            Err(crate::error::SkillEdError::NotFound(_))
+        ));
+        assert_eq!(names(&get_all_skills(&pool).await.unwrap()), vec!["Piano", "Chess", "Go"]);
+    }
+
+    #[tokio::test]
+    async fn test_session_start_and_stop() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let chess = create_skill(&pool, "Chess".to_string(), "Games".to_string(), None).await.unwrap();
+
+        let session = start_session(&pool, &piano.id, None).await.unwrap();
+        assert_eq!(session.source_type, "timer");
+        let err = start_session(&pool, &chess.id, None).await.unwrap_err();
+        assert!(matches!(err, crate::error::SkillEdError::ValidationError(_)));
+        assert!(start_session(&pool, "missing", None).await.is_err());
+
+        // A second timer slipping past the check is stopped by the unique index
+        let mut conn = pool.acquire().await.unwrap();
+        let err = insert_practice_session(&mut conn, &PracticeSession::start(chess.id.clone(), None))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::error::SkillEdError::ValidationError(_)));
+        drop(conn);
+
+        // Backdate the start so the stopped session has a measurable length
+        sqlx::query("UPDATE practice_sessions SET started_at = ? WHERE id = ?")
+            .bind((Utc::now() - Duration::minutes(90)).to_rfc3339())
+            .bind(&session.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let stopped = stop_session(&pool, Some(4), None).await.unwrap();
+        assert_eq!(stopped.id, session.id);
+        assert!((stopped.duration_seconds - 5400).abs() <= 1);
+        assert!(get_running_session(&pool).await.unwrap().is_none());
+        assert!(stop_session(&pool, None, None).await.is_err());
+
+        let skill = get_skill(&pool, &piano.id).await.unwrap().unwrap();
+        assert!((skill.total_hours - 1.5).abs() < 0.01);
+        let history = get_skill_progress_history(&pool, &piano.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].source_type, "practice_session");
+        assert_eq!(history[0].source_id.as_deref(), Some(session.id.as_str()));
+
+        // The timer is free again
+        start_session(&pool, &chess.id, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_logged_hours_book_a_progress_session() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+
+        let progress = record_skill_progress(&pool, &piano.id, 2, 0.5).await.unwrap();
+        assert_eq!(progress.source_type, "manual");
+
+        let sessions = get_practice_sessions(&pool, &piano.id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].source_type, "progress");
+        assert_eq!(sessions[0].source_id.as_deref(), Some(progress.id.as_str()));
+        let skill = get_skill(&pool, &piano.id).await.unwrap().unwrap();
+        assert!((skill.total_hours - 0.5).abs() < 0.01);
+    }
+
+    #[tokio::test]
+    async fn test_running_session_survives_restart() {
+        let path = std::env::temp_dir().join(format!("skill-ed-{}.db", uuid::Uuid::new_v4()));
+        let open = || async {
+            let options = sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&path)
+                .create_if_missing(true);
+            let pool = SqlitePool::connect_with(options).await.unwrap();
+            sqlx::query(crate::db::schema::CREATE_TABLES_SQL).execute(&pool).await.unwrap();
+            pool
+        };
+
+        let pool = open().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let session = start_session(&pool, &piano.id, Some("scales".to_string())).await.unwrap();
+        pool.close().await;
+
+        let pool = open().await;
+        let running = get_running_session(&pool).await.unwrap().unwrap();
+        assert_eq!((running.id.as_str(), running.tags.as_deref()), (session.id.as_str(), Some("scales")));
+        stop_session(&pool, None, None).await.unwrap();
+        pool.close().await;
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_update_skill_binds_typed_values() {
+        let pool = crate::db::test_pool().await;
+        let skill = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+
+        let update = SkillUpdate::new().name("Grand piano").description("Classical").proficiency_level(40);
+        let updated = update_skill(&pool, &skill.id, update).await.unwrap();
+        assert_eq!(
+            (updated.name.as_str(), updated.description.as_deref(), updated.category.as_str()),
+            ("Grand piano", Some("Classical"), "Music")
+        );
+
+        let (level_type, hours_type): (String, String) = sqlx::query_as(
+            "SELECT typeof(proficiency_level), typeof(total_hours) FROM skills WHERE id = ?"
+        )
+        .bind(&skill.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((level_type.as_str(), hours_type.as_str()), ("integer", "real"));
+    }
+
+    #[tokio::test]
+    async fn test_update_skill_rejects_level_out_of_range() {
+        let pool = crate::db::test_pool().await;
+        let skill = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+
+        for level in [-1, 101] {
+            let err = update_skill(&pool, &skill.id, SkillUpdate::new().proficiency_level(level)).await.unwrap_err();
+            assert!(matches!(err, crate::error::SkillEdError::ValidationError(_)));
+        }
+        update_skill(&pool, &skill.id, SkillUpdate::new().proficiency_level(100)).await.unwrap();
+        assert!(update_skill(&pool, "missing", SkillUpdate::new().proficiency_level(1)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_skill_records_progress_on_level_or_hours_change() {
+        let pool = crate::db::test_pool().await;
+        let skill = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+
+        // Renaming alone leaves the history untouched
+        update_skill(&pool, &skill.id, SkillUpdate::new().name("Keys")).await.unwrap();
+        assert!(get_skill_progress_history(&pool, &skill.id).await.unwrap().is_empty());
+
+        update_skill(&pool, &skill.id, SkillUpdate::new().proficiency_level(30)).await.unwrap();
+        let updated = update_skill(&pool, &skill.id, SkillUpdate::new().total_hours(2.0)).await.unwrap();
+        assert_eq!(updated.total_hours, 2.0);
+
+        let history = get_skill_progress_history(&pool, &skill.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|entry| entry.proficiency_level == 30 && entry.source_type == "manual"));
+        assert_eq!(history.iter().map(|entry| entry.hours_practiced).sum::<f64>(), 2.0);
+
+        // The hours are booked as a session standing in for the manual entry
+        let sessions = get_practice_sessions(&pool, &skill.id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].source_type, "progress");
+
+        let err = update_skill(&pool, &skill.id, SkillUpdate::new().total_hours(1.0)).await.unwrap_err();
+        assert!(matches!(err, crate::error::SkillEdError::ValidationError(_)));
+        assert_eq!(get_skill(&pool, &skill.id).await.unwrap().unwrap().total_hours, 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_update_clears_description() {
+        let pool = crate::db::test_pool().await;
+        let skill = create_skill(&pool, "Piano".to_string(), "Music".to_string(), Some("Scales".to_string()))
+            .await
+            .unwrap();
+
+        let updated = update_skill(&pool, &skill.id, SkillUpdate::new().name("Grand piano")).await.unwrap();
+        assert_eq!(updated.description.as_deref(), Some("Scales"));
+
+        let updated = update_skill(&pool, &skill.id, SkillUpdate::new().clear_description()).await.unwrap();
+        assert_eq!(updated.description, None);
+    }
+
+    #[tokio::test]
+    async fn test_update_habit() {
+        let pool = crate::db::test_pool().await;
+        let habit = create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let updated = update_habit(&pool, &habit.id, HabitUpdate::new().name("Read fiction").frequency("weekly"))
+            .await
+            .unwrap();
+        assert_eq!((updated.name.as_str(), updated.frequency.as_str()), ("Read fiction", "weekly"));
+        assert!(update_habit(&pool, &habit.id, HabitUpdate::new().frequency("hourly")).await.is_err());
+        assert!(update_habit(&pool, "missing", HabitUpdate::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_linked_completion_updates_skill_once() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let chess = create_skill(&pool, "Chess".to_string(), "Games".to_string(), None).await.unwrap();
+        let habit = create_habit(&pool, "Practice".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        link_habit_to_skill(&pool, &habit.id, &piano.id, 30, 10).await.unwrap();
+        link_habit_to_skill(&pool, &habit.id, &chess.id, 0, 5).await.unwrap();
+
+        let completion = log_habit_completion(&pool, &habit.id).await.unwrap();
+
+        let skill = get_skill(&pool, &piano.id).await.unwrap().unwrap();
+        assert!((skill.total_hours - 0.5).abs() < 1e-9);
+        let sessions = get_practice_sessions(&pool, &piano.id).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].source_type, "habit_completion");
+        assert_eq!(sessions[0].source_id.as_deref(), Some(completion.id.as_str()));
+        let history = get_skill_progress_history(&pool, &piano.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].source_type, "habit_completion");
+
+        assert_eq!(crate::db::get_skill_xp(&pool, &piano.id).await.unwrap(), 10);
+
+        // A link without minutes books no time but still earns XP
+        assert!(get_practice_sessions(&pool, &chess.id).await.unwrap().is_empty());
+        assert_eq!(get_skill(&pool, &chess.id).await.unwrap().unwrap().total_hours, 0.0);
+        assert_eq!(crate::db::get_skill_xp(&pool, &chess.id).await.unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_negative_habits_cannot_be_linked() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = create_habit(&pool, "Smoking".to_string(), "daily".to_string(), "negative".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let err = link_habit_to_skill(&pool, &habit.id, &piano.id, 30, 0).await.unwrap_err();
+        assert!(matches!(err, crate::error::SkillEdError::ValidationError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_names_are_reported() {
+        let pool = crate::db::test_pool().await;
+        create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let guitar = create_skill(&pool, "Guitar".to_string(), "Music".to_string(), None).await.unwrap();
+
+        let err = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap_err();
+        assert_eq!(err.code(), "DUPLICATE_NAME");
+
+        let err = update_skill(&pool, &guitar.id, SkillUpdate::new().name("Piano")).await.unwrap_err();
+        assert!(matches!(err, crate::error::SkillEdError::DuplicateName { ref name, .. } if name == "Piano"));
+
+        create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        let err = create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_response().details.unwrap()["entity"], "habit");
+    }
+}
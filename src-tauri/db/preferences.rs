@@ -0,0 +1,48 @@
+use crate::error::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{Executor, Sqlite};
+use chrono::Utc;
+
+// ============ PREFERENCE OPERATIONS ============
+//
+// Non-secret app configuration stored as JSON. Anything sensitive belongs in the
+// encrypted `settings` table instead.
+
+/// Reads a preference, or `None` when it has never been set
+pub async fn get_preference<'e, T, E>(executor: E, key: &str) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+    E: Executor<'e, Database = Sqlite>,
+{
+    let value = sqlx::query_scalar::<_, String>("SELECT value FROM preferences WHERE key = ?")
+        .bind(key)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    match value {
+        Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+        None => Ok(None),
+    }
+}
+
+pub async fn set_preference<'e, T, E>(executor: E, key: &str, value: &T) -> Result<()>
+where
+    T: Serialize,
+    E: Executor<'e, Database = Sqlite>,
+{
+    let value = serde_json::to_string(value)?;
+
+    sqlx::query(
+        "INSERT INTO preferences (key, value, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"
+    )
+    .bind(key)
+    .bind(&value)
+    .bind(Utc::now().to_rfc3339())
+    .execute(executor)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,374 @@
+use crate::db::{DueReminder, Reminder, ReminderUpdate};
+use crate::error::Result;
+use super::operations::{done_in_period, get_habit, parse_timestamp};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Utc};
+use sqlx::{Executor, Sqlite, SqlitePool};
+
+/// A reminder whose time passed while the app was closed still fires if it is
+/// at most this late; older ones are dropped until their next slot
+pub const REMINDER_GRACE_MINUTES: i64 = 60;
+
+const MAX_SNOOZE_MINUTES: i64 = 24 * 60;
+
+const REMINDER_COLUMNS: &str = "id, entity_type, entity_id, time_of_day, weekdays, only_if_not_done, is_enabled, snoozed_until, last_fired_at, created_at";
+
+type ReminderRow = (String, String, String, String, i64, bool, bool, Option<String>, Option<String>, String);
+
+fn from_row(row: ReminderRow) -> Reminder {
+    let (id, entity_type, entity_id, time_of_day, weekdays, only_if_not_done, is_enabled, snoozed_until, last_fired_at, created_at) = row;
+    Reminder {
+        id,
+        entity_type,
+        entity_id,
+        time_of_day,
+        weekdays: (0..7).filter(|day| weekdays & (1 << day) != 0).collect(),
+        only_if_not_done,
+        is_enabled,
+        snoozed_until,
+        last_fired_at,
+        created_at,
+    }
+}
+
+fn weekday_mask(weekdays: &[u32]) -> i64 {
+    weekdays.iter().fold(0, |mask, day| mask | (1 << day))
+}
+
+// ============ REMINDER OPERATIONS ============
+
+pub async fn create_reminder(pool: &SqlitePool, mut reminder: Reminder) -> Result<Reminder> {
+    validate_reminder(&reminder)?;
+    reminder.time_of_day = normalize_time(&reminder.time_of_day)?;
+
+    let exists = match reminder.entity_type.as_str() {
+        "habit" => get_habit(pool, &reminder.entity_id).await?.is_some(),
+        "milestone" => sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM milestones WHERE id = ?")
+            .bind(&reminder.entity_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))? > 0,
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Reminders can only be set on a 'habit' or 'milestone'".to_string(),
+            ))
+        }
+    };
+    if !exists {
+        return Err(crate::error::SkillEdError::NotFound(format!("{} {}", reminder.entity_type, reminder.entity_id)));
+    }
+
+    sqlx::query(
+        "INSERT INTO reminders (id, entity_type, entity_id, time_of_day, weekdays, only_if_not_done, is_enabled, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&reminder.id)
+    .bind(&reminder.entity_type)
+    .bind(&reminder.entity_id)
+    .bind(&reminder.time_of_day)
+    .bind(weekday_mask(&reminder.weekdays))
+    .bind(reminder.only_if_not_done)
+    .bind(reminder.is_enabled)
+    .bind(&reminder.created_at)
+    .execute(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(reminder)
+}
+
+pub async fn get_reminder<'e, E>(executor: E, reminder_id: &str) -> Result<Option<Reminder>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let query_str = format!("SELECT {} FROM reminders WHERE id = ?", REMINDER_COLUMNS);
+    let row = sqlx::query_as::<_, ReminderRow>(&query_str)
+        .bind(reminder_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(row.map(from_row))
+}
+
+/// Reminders for one habit or milestone, or all of them
+pub async fn get_reminders(pool: &SqlitePool, entity_type: Option<&str>, entity_id: Option<&str>) -> Result<Vec<Reminder>> {
+    let query_str = format!(
+        "SELECT {} FROM reminders WHERE (?1 IS NULL OR entity_type = ?1) AND (?2 IS NULL OR entity_id = ?2)
+         ORDER BY time_of_day ASC, created_at ASC",
+        REMINDER_COLUMNS
+    );
+    let rows = sqlx::query_as::<_, ReminderRow>(&query_str)
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(rows.into_iter().map(from_row).collect())
+}
+
+pub async fn update_reminder(pool: &SqlitePool, reminder_id: &str, update: ReminderUpdate) -> Result<Reminder> {
+    let mut reminder = get_reminder(pool, reminder_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Reminder {}", reminder_id)))?;
+
+    if let Some(time_of_day) = update.time_of_day {
+        reminder.time_of_day = time_of_day;
+    }
+    if let Some(weekdays) = update.weekdays {
+        reminder.weekdays = weekdays;
+    }
+    if let Some(only_if_not_done) = update.only_if_not_done {
+        reminder.only_if_not_done = only_if_not_done;
+    }
+    if let Some(is_enabled) = update.is_enabled {
+        reminder.is_enabled = is_enabled;
+    }
+    validate_reminder(&reminder)?;
+    reminder.time_of_day = normalize_time(&reminder.time_of_day)?;
+
+    sqlx::query(
+        "UPDATE reminders SET time_of_day = ?, weekdays = ?, only_if_not_done = ?, is_enabled = ? WHERE id = ?"
+    )
+    .bind(&reminder.time_of_day)
+    .bind(weekday_mask(&reminder.weekdays))
+    .bind(reminder.only_if_not_done)
+    .bind(reminder.is_enabled)
+    .bind(reminder_id)
+    .execute(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(reminder)
+}
+
+pub async fn delete_reminder(pool: &SqlitePool, reminder_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM reminders WHERE id = ?")
+        .bind(reminder_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("Reminder {}", reminder_id)));
+    }
+    Ok(())
+}
+
+/// Fires the reminder again `minutes` after `now`
+pub async fn snooze_reminder(pool: &SqlitePool, reminder_id: &str, minutes: i64, now: DateTime<Utc>) -> Result<Reminder> {
+    if !(1..=MAX_SNOOZE_MINUTES).contains(&minutes) {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Snooze must be between 1 and {} minutes",
+            MAX_SNOOZE_MINUTES
+        )));
+    }
+
+    let until = (now + Duration::minutes(minutes)).to_rfc3339();
+    let result = sqlx::query("UPDATE reminders SET snoozed_until = ? WHERE id = ?")
+        .bind(&until)
+        .bind(reminder_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("Reminder {}", reminder_id)));
+    }
+
+    get_reminder(pool, reminder_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Reminder {}", reminder_id)))
+}
+
+/// Records that a reminder was handled at `now`, ending any snooze
+pub async fn mark_reminder_fired(pool: &SqlitePool, reminder_id: &str, now: DateTime<Utc>) -> Result<()> {
+    sqlx::query("UPDATE reminders SET last_fired_at = ?, snoozed_until = NULL WHERE id = ?")
+        .bind(now.to_rfc3339())
+        .bind(reminder_id)
+        .execute(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Reminders due at `now` with their notification text. Reminders for trashed or
+/// archived habits and completed milestones are left out.
+pub async fn due_reminders(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<DueReminder>> {
+    let query_str = format!("SELECT {} FROM reminders WHERE is_enabled = 1", REMINDER_COLUMNS);
+    let reminders = sqlx::query_as::<_, ReminderRow>(&query_str)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let today = now.with_timezone(&Local).date_naive();
+    let mut due = Vec::new();
+
+    for reminder in reminders.into_iter().map(from_row) {
+        if !reminder_due(&reminder, now)? {
+            continue;
+        }
+
+        match reminder.entity_type.as_str() {
+            "habit" => {
+                let Some(habit) = get_habit(pool, &reminder.entity_id).await? else {
+                    continue;
+                };
+                if habit.deleted_at.is_some() || habit.is_archived {
+                    continue;
+                }
+
+                let done = done_in_period(&habit, today)?;
+                let body = if habit.polarity == "negative" {
+                    format!("Stay on track with {}", habit.name)
+                } else {
+                    format!("Time for {}", habit.name)
+                };
+                due.push(DueReminder {
+                    suppressed: reminder.only_if_not_done && done,
+                    reminder,
+                    title: "Habit reminder".to_string(),
+                    body,
+                });
+            }
+            "milestone" => {
+                let milestone = sqlx::query_as::<_, (String, String, bool)>(
+                    "SELECT title, target_date, COALESCE(is_completed, 0) FROM milestones WHERE id = ?"
+                )
+                .bind(&reminder.entity_id)
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+                let Some((title, target_date, is_completed)) = milestone else {
+                    continue;
+                };
+                if is_completed {
+                    continue;
+                }
+                due.push(DueReminder {
+                    reminder,
+                    title: "Milestone reminder".to_string(),
+                    body: format!("{} is due {}", title, target_date),
+                    suppressed: false,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(due)
+}
+
+/// Whether a reminder's rule says it should fire at `now`: a snoozed reminder
+/// fires once the snooze ends; otherwise it fires once per slot (its time on an
+/// enabled weekday), up to `REMINDER_GRACE_MINUTES` late
+pub(super) fn reminder_due(reminder: &Reminder, now: DateTime<Utc>) -> Result<bool> {
+    if !reminder.is_enabled {
+        return Ok(false);
+    }
+
+    if let Some(snoozed_until) = reminder.snoozed_until.as_deref() {
+        return Ok(now >= parse_timestamp(snoozed_until)?);
+    }
+
+    let local_now = now.with_timezone(&Local);
+    if !reminder.weekdays.contains(&local_now.weekday().num_days_from_monday()) {
+        return Ok(false);
+    }
+
+    let time = parse_time(&reminder.time_of_day)?;
+    // A slot inside a DST gap moves to the first valid instant after it
+    let local_slot = local_now.date_naive().and_time(time);
+    let Some(slot) = Local
+        .from_local_datetime(&local_slot)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(local_slot + Duration::hours(1))).earliest())
+    else {
+        return Ok(false);
+    };
+    let slot = slot.with_timezone(&Utc);
+
+    if now < slot || now - slot > Duration::minutes(REMINDER_GRACE_MINUTES) {
+        return Ok(false);
+    }
+
+    match reminder.last_fired_at.as_deref() {
+        Some(last_fired_at) => Ok(parse_timestamp(last_fired_at)? < slot),
+        None => Ok(true),
+    }
+}
+
+fn validate_reminder(reminder: &Reminder) -> Result<()> {
+    if reminder.weekdays.is_empty() || reminder.weekdays.iter().any(|day| *day > 6) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Reminder weekdays must be between 0 (Monday) and 6 (Sunday), with at least one day".to_string(),
+        ));
+    }
+    parse_time(&reminder.time_of_day)?;
+    Ok(())
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+        crate::error::SkillEdError::ValidationError(format!("Invalid reminder time '{}', expected HH:MM", value))
+    })
+}
+
+/// Zero-padded HH:MM, so times sort as text
+fn normalize_time(value: &str) -> Result<String> {
+    Ok(parse_time(value)?.format("%H:%M").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UTC instant for a local wall-clock time, independent of the test machine's zone
+    fn local(value: &str) -> DateTime<Utc> {
+        let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&naive).earliest().unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_reminder_fires_once_per_slot() {
+        // 2024-03-04 is a Monday
+        let mut reminder = Reminder::new("habit".to_string(), "h1".to_string(), "08:00".to_string());
+        reminder.weekdays = vec![0, 2];
+
+        assert!(!reminder_due(&reminder, local("2024-03-04 07:59")).unwrap());
+        assert!(reminder_due(&reminder, local("2024-03-04 08:00")).unwrap());
+        assert!(reminder_due(&reminder, local("2024-03-04 08:45")).unwrap());
+        assert!(!reminder_due(&reminder, local("2024-03-04 09:30")).unwrap());
+        assert!(!reminder_due(&reminder, local("2024-03-05 08:00")).unwrap());
+
+        reminder.last_fired_at = Some(local("2024-03-04 08:00").to_rfc3339());
+        assert!(!reminder_due(&reminder, local("2024-03-04 08:10")).unwrap());
+        assert!(reminder_due(&reminder, local("2024-03-06 08:00")).unwrap());
+    }
+
+    #[test]
+    fn test_snoozed_reminder_waits_for_snooze() {
+        let mut reminder = Reminder::new("habit".to_string(), "h1".to_string(), "08:00".to_string());
+        reminder.last_fired_at = Some(local("2024-03-04 08:00").to_rfc3339());
+        reminder.snoozed_until = Some(local("2024-03-04 08:15").to_rfc3339());
+
+        assert!(!reminder_due(&reminder, local("2024-03-04 08:10")).unwrap());
+        assert!(reminder_due(&reminder, local("2024-03-04 08:15")).unwrap());
+
+        reminder.is_enabled = false;
+        assert!(!reminder_due(&reminder, local("2024-03-04 08:15")).unwrap());
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut reminder = Reminder::new("habit".to_string(), "h1".to_string(), "25:00".to_string());
+        assert!(validate_reminder(&reminder).is_err());
+        reminder.time_of_day = "7:05".to_string();
+        assert_eq!(normalize_time(&reminder.time_of_day).unwrap(), "07:05");
+        reminder.weekdays = vec![7];
+        assert!(validate_reminder(&reminder).is_err());
+        assert_eq!(weekday_mask(&[0, 6]), 65);
+    }
+}
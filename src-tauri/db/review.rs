@@ -0,0 +1,482 @@
+use crate::db::{Export, HabitReview, Milestone, ReviewDocument, ReviewReport};
+use crate::error::Result;
+use super::activity::{parse_bound, parse_date};
+use super::exports::write_export;
+use super::operations::{local_date, period_index};
+use super::stats::get_skill_hours_by_period;
+use chrono::{Datelike, Duration, Local, NaiveDate, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// ============ REVIEW OPERATIONS ============
+
+/// Builds the review for the week (Monday to Sunday) or month containing `date`
+pub async fn build_review(pool: &SqlitePool, period: &str, date: NaiveDate) -> Result<ReviewReport> {
+    let (from, to) = review_range(period, date)?;
+    let (from_str, to_str) = (from.to_string(), to.to_string());
+    let start = parse_bound(&from_str, false)?;
+    let end = parse_bound(&to_str, true)?;
+    let today = Local::now().date_naive();
+
+    let skill_hours = get_skill_hours_by_period(pool, period, &from_str, &to_str, None).await?;
+    let total_hours = skill_hours.iter().map(|point| point.hours).sum();
+
+    let habits = review_habits(pool, &end, from, to, today).await?;
+
+    let milestones_completed = sqlx::query_as::<_, Milestone>(
+        "SELECT id, skill_id, title, description, target_date, completion_date, is_completed, created_at
+         FROM milestones WHERE is_completed = 1 AND completion_date >= ? AND completion_date < ?
+         ORDER BY completion_date ASC"
+    )
+    .bind(&start)
+    .bind(&end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // Overdue as of the end of the period, or today while the period is still running
+    let overdue_before = (to + Duration::days(1)).min(today).to_string();
+    let milestones_overdue = sqlx::query_as::<_, Milestone>(
+        "SELECT id, skill_id, title, description, target_date, completion_date, is_completed, created_at
+         FROM milestones WHERE is_completed = 0 AND target_date < ?
+         ORDER BY target_date ASC"
+    )
+    .bind(&overdue_before)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let xp_earned = sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(SUM(xp), 0) FROM xp_ledger WHERE created_at >= ? AND created_at < ?"
+    )
+    .bind(&start)
+    .bind(&end)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(ReviewReport {
+        period: period.to_string(),
+        from: from_str,
+        to: to_str,
+        generated_at: Utc::now().to_rfc3339(),
+        total_hours,
+        skill_hours,
+        habits,
+        milestones_completed,
+        milestones_overdue,
+        xp_earned,
+    })
+}
+
+/// Builds and renders a review; with `data_dir` set, both renderings are also
+/// written to the exports directory and recorded in the export history
+pub async fn generate_review(
+    pool: &SqlitePool,
+    period: &str,
+    date: Option<&str>,
+    data_dir: Option<&Path>,
+) -> Result<ReviewDocument> {
+    let date = match date {
+        Some(date) => parse_date(date)?,
+        None => Local::now().date_naive(),
+    };
+
+    let report = build_review(pool, period, date).await?;
+    let markdown = render_markdown(&report);
+    let html = render_html(&report);
+
+    let mut exports: Vec<Export> = Vec::new();
+    if let Some(data_dir) = data_dir {
+        let stem = format!("review-{}-{}", report.period, report.from);
+        exports.push(write_export(pool, data_dir, "review_markdown", &format!("{}.md", stem), markdown.as_bytes()).await?);
+        exports.push(write_export(pool, data_dir, "review_html", &format!("{}.html", stem), html.as_bytes()).await?);
+    }
+
+    Ok(ReviewDocument { report, markdown, html, exports })
+}
+
+async fn review_habits(
+    pool: &SqlitePool,
+    end: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+) -> Result<Vec<HabitReview>> {
+    let habits = sqlx::query_as::<_, (String, String, String, String)>(
+        "SELECT id, name, frequency, created_at FROM habits
+         WHERE deleted_at IS NULL AND is_archived = 0 AND polarity = 'positive'
+         ORDER BY sort_order ASC, created_at DESC"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // Streaks at the start of the period need completions from before it too
+    let completions = sqlx::query_as::<_, (String, String)>(
+        "SELECT habit_id, completed_at FROM habit_completions
+         WHERE completion_type = 'completion' AND completed_at < ?"
+    )
+    .bind(end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let mut completed_days: HashMap<String, Vec<NaiveDate>> = HashMap::new();
+    for (habit_id, completed_at) in completions {
+        completed_days.entry(habit_id).or_default().push(local_date(&completed_at)?);
+    }
+
+    let mut reviews = Vec::with_capacity(habits.len());
+    for (habit_id, habit_name, frequency, created_at) in habits {
+        let done: HashSet<i64> = completed_days
+            .get(&habit_id)
+            .map(|days| days.iter().map(|day| period_index(&frequency, *day)).collect())
+            .unwrap_or_default();
+
+        let summary = summarize_periods(
+            &done,
+            period_index(&frequency, local_date(&created_at)?),
+            period_index(&frequency, from),
+            period_index(&frequency, to),
+            period_index(&frequency, today),
+        );
+
+        reviews.push(HabitReview {
+            habit_id,
+            habit_name,
+            frequency,
+            periods_hit: summary.hit,
+            periods_missed: summary.missed,
+            streak_at_start: summary.streak_at_start,
+            streak_at_end: summary.streak_at_end,
+            streak_lost: summary.streak_lost,
+        });
+    }
+
+    Ok(reviews)
+}
+
+/// First and last day of the review period containing `date`
+fn review_range(period: &str, date: NaiveDate) -> Result<(NaiveDate, NaiveDate)> {
+    match period {
+        "weekly" => {
+            let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            Ok((start, start + Duration::days(6)))
+        }
+        "monthly" => {
+            let start = date.with_day(1).expect("every month has a first day");
+            let next_month = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            }
+            .expect("first of the next month is a valid date");
+            Ok((start, next_month - Duration::days(1)))
+        }
+        _ => Err(crate::error::SkillEdError::ValidationError(
+            "Invalid period. Must be 'weekly' or 'monthly'".to_string(),
+        )),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PeriodSummary {
+    hit: i64,
+    missed: i64,
+    streak_at_start: i32,
+    streak_at_end: i32,
+    streak_lost: i32,
+}
+
+/// Walks a habit's periods from `start` to `end` given the set of periods with a
+/// completion. Periods before the habit was created (`first`) are skipped, and the
+/// `current` period is not a miss until it is over.
+fn summarize_periods(done: &HashSet<i64>, first: i64, start: i64, end: i64, current: i64) -> PeriodSummary {
+    let mut streak_at_start = 0;
+    let mut period = start - 1;
+    while period >= first && done.contains(&period) {
+        streak_at_start += 1;
+        period -= 1;
+    }
+
+    let mut summary = PeriodSummary {
+        hit: 0,
+        missed: 0,
+        streak_at_start,
+        streak_at_end: 0,
+        streak_lost: 0,
+    };
+    let mut run = streak_at_start;
+
+    for period in start.max(first)..=end.min(current) {
+        if done.contains(&period) {
+            summary.hit += 1;
+            run += 1;
+        } else if period < current {
+            summary.missed += 1;
+            summary.streak_lost = summary.streak_lost.max(run);
+            run = 0;
+        }
+    }
+
+    summary.streak_at_end = run;
+    summary
+}
+
+// ============ RENDERING ============
+
+pub fn render_markdown(report: &ReviewReport) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# {} Review: {} to {}\n\n", period_title(&report.period), report.from, report.to));
+
+    out.push_str("## Practice\n\n");
+    out.push_str(&format!("**Total:** {:.1} h\n\n", report.total_hours));
+    if !report.skill_hours.is_empty() {
+        out.push_str("| Skill | Hours |\n|---|---:|\n");
+        for point in &report.skill_hours {
+            out.push_str(&format!("| {} | {:.1} |\n", escape_markdown(&point.skill_name), point.hours));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Habits\n\n");
+    if report.habits.is_empty() {
+        out.push_str("No active habits.\n\n");
+    } else {
+        out.push_str("| Habit | Hit | Missed | Streak | Streak lost |\n|---|---:|---:|---|---:|\n");
+        for habit in &report.habits {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} → {} | {} |\n",
+                escape_markdown(&habit.habit_name),
+                habit.periods_hit,
+                habit.periods_missed,
+                habit.streak_at_start,
+                habit.streak_at_end,
+                habit.streak_lost
+            ));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Milestones\n\n");
+    out.push_str("### Completed\n\n");
+    push_milestone_list(&mut out, &report.milestones_completed, |m| {
+        format!("completed {}", m.completion_date.as_deref().map(date_part).unwrap_or(""))
+    });
+    out.push_str("### Overdue\n\n");
+    push_milestone_list(&mut out, &report.milestones_overdue, |m| format!("due {}", m.target_date));
+
+    out.push_str("## XP\n\n");
+    out.push_str(&format!("**Earned:** {} XP\n", report.xp_earned));
+    out
+}
+
+pub fn render_html(report: &ReviewReport) -> String {
+    let mut out = String::new();
+    let title = format!("{} Review: {} to {}", period_title(&report.period), report.from, report.to);
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n</head>\n<body>\n", escape_html(&title)));
+    out.push_str(&format!("<h1>{}</h1>\n", escape_html(&title)));
+
+    out.push_str("<h2>Practice</h2>\n");
+    out.push_str(&format!("<p><strong>Total:</strong> {:.1} h</p>\n", report.total_hours));
+    if !report.skill_hours.is_empty() {
+        out.push_str("<table>\n<tr><th>Skill</th><th>Hours</th></tr>\n");
+        for point in &report.skill_hours {
+            out.push_str(&format!("<tr><td>{}</td><td>{:.1}</td></tr>\n", escape_html(&point.skill_name), point.hours));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Habits</h2>\n");
+    if report.habits.is_empty() {
+        out.push_str("<p>No active habits.</p>\n");
+    } else {
+        out.push_str("<table>\n<tr><th>Habit</th><th>Hit</th><th>Missed</th><th>Streak</th><th>Streak lost</th></tr>\n");
+        for habit in &report.habits {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{} &rarr; {}</td><td>{}</td></tr>\n",
+                escape_html(&habit.habit_name),
+                habit.periods_hit,
+                habit.periods_missed,
+                habit.streak_at_start,
+                habit.streak_at_end,
+                habit.streak_lost
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Milestones</h2>\n<h3>Completed</h3>\n");
+    push_milestone_html(&mut out, &report.milestones_completed, |m| {
+        format!("completed {}", m.completion_date.as_deref().map(date_part).unwrap_or(""))
+    });
+    out.push_str("<h3>Overdue</h3>\n");
+    push_milestone_html(&mut out, &report.milestones_overdue, |m| format!("due {}", m.target_date));
+
+    out.push_str("<h2>XP</h2>\n");
+    out.push_str(&format!("<p><strong>Earned:</strong> {} XP</p>\n", report.xp_earned));
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn push_milestone_list(out: &mut String, milestones: &[Milestone], detail: impl Fn(&Milestone) -> String) {
+    if milestones.is_empty() {
+        out.push_str("None.\n\n");
+        return;
+    }
+    for milestone in milestones {
+        out.push_str(&format!("- {} ({})\n", escape_markdown(&milestone.title), detail(milestone)));
+    }
+    out.push('\n');
+}
+
+fn push_milestone_html(out: &mut String, milestones: &[Milestone], detail: impl Fn(&Milestone) -> String) {
+    if milestones.is_empty() {
+        out.push_str("<p>None.</p>\n");
+        return;
+    }
+    out.push_str("<ul>\n");
+    for milestone in milestones {
+        out.push_str(&format!(
+            "<li>{} ({})</li>\n",
+            escape_html(&milestone.title),
+            escape_html(&detail(milestone))
+        ));
+    }
+    out.push_str("</ul>\n");
+}
+
+fn period_title(period: &str) -> &'static str {
+    if period == "monthly" {
+        "Monthly"
+    } else {
+        "Weekly"
+    }
+}
+
+/// The YYYY-MM-DD part of a date or timestamp
+fn date_part(value: &str) -> &str {
+    value.get(..10).unwrap_or(value)
+}
+
+fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    fn day(value: &str) -> NaiveDate {
+        parse_date(value).unwrap()
+    }
+
+    #[test]
+    fn test_review_range() {
+        assert_eq!(review_range("weekly", day("2024-03-06")).unwrap(), (day("2024-03-04"), day("2024-03-10")));
+        assert_eq!(review_range("monthly", day("2024-02-14")).unwrap(), (day("2024-02-01"), day("2024-02-29")));
+        assert_eq!(review_range("monthly", day("2024-12-31")).unwrap(), (day("2024-12-01"), day("2024-12-31")));
+        assert!(review_range("daily", day("2024-03-06")).is_err());
+    }
+
+    #[test]
+    fn test_summarize_periods_tracks_streaks() {
+        // Done on 8-12 and 15-16; the review covers 10-16 with 16 still running
+        let done: HashSet<i64> = [8, 9, 10, 11, 12, 15, 16].into_iter().collect();
+        let summary = summarize_periods(&done, 0, 10, 16, 16);
+
+        assert_eq!(
+            summary,
+            PeriodSummary { hit: 5, missed: 2, streak_at_start: 2, streak_at_end: 2, streak_lost: 5 }
+        );
+
+        // The running period does not count as missed before it ends
+        let summary = summarize_periods(&done, 0, 10, 17, 17);
+        assert_eq!((summary.missed, summary.streak_at_end), (2, 2));
+    }
+
+    #[test]
+    fn test_rendering_escapes_names() {
+        let report = ReviewReport {
+            period: "weekly".to_string(),
+            from: "2024-03-04".to_string(),
+            to: "2024-03-10".to_string(),
+            generated_at: "2024-03-10T18:00:00+00:00".to_string(),
+            total_hours: 1.5,
+            skill_hours: vec![crate::db::SkillHoursPoint {
+                skill_id: "s1".to_string(),
+                skill_name: "C++ | <templates>".to_string(),
+                period_start: "2024-03-04".to_string(),
+                hours: 1.5,
+            }],
+            habits: Vec::new(),
+            milestones_completed: Vec::new(),
+            milestones_overdue: Vec::new(),
+            xp_earned: 90,
+        };
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.starts_with("# Weekly Review: 2024-03-04 to 2024-03-10"));
+        assert!(markdown.contains("| C++ \\| <templates> | 1.5 |"));
+        assert!(markdown.contains("**Earned:** 90 XP"));
+
+        let html = render_html(&report);
+        assert!(html.contains("<td>C++ | &lt;templates&gt;</td>"));
+    }
+
+    #[tokio::test]
+    async fn test_review_covers_seeded_week() {
+        let pool = test_pool().await;
+        let habit = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE habits SET created_at = '2024-02-01T12:00:00+00:00' WHERE id = ?")
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, completed_at) in [("c1", "2024-03-03T12:00:00+00:00"), ("c2", "2024-03-04T12:00:00+00:00")] {
+            sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(&habit.id)
+                .bind(completed_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO milestones (id, title, target_date, is_completed, created_at)
+             VALUES ('m1', 'Recital', '2024-03-08', 0, '2024-01-01T00:00:00+00:00')"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let report = build_review(&pool, "weekly", day("2024-03-06")).await.unwrap();
+        let habit_review = &report.habits[0];
+        assert_eq!((habit_review.periods_hit, habit_review.periods_missed), (1, 6));
+        assert_eq!((habit_review.streak_at_start, habit_review.streak_lost), (1, 2));
+        assert_eq!(report.milestones_overdue.len(), 1);
+        assert_eq!(report.xp_earned, 0);
+
+        let dir = std::env::temp_dir().join(format!("review-test-{}", uuid::Uuid::new_v4()));
+        let document = generate_review(&pool, "weekly", Some("2024-03-06"), Some(&dir)).await.unwrap();
+        assert_eq!(document.exports.len(), 2);
+        assert!(Path::new(&document.exports[0].file_path).exists());
+        assert_eq!(db::get_exports(&pool).await.unwrap().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
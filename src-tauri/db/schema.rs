@@ -11,6 +11,16 @@ CREATE TABLE IF NOT EXISTS auth_user (
     last_login TEXT
 );
 
+-- Categories for skills and habits
+CREATE TABLE IF NOT EXISTS categories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL COLLATE NOCASE,
+    color TEXT,
+    icon TEXT,
+    created_at TEXT NOT NULL,
+    UNIQUE(name)
+);
+
 -- Skills Table
 CREATE TABLE IF NOT EXISTS skills (
     id TEXT PRIMARY KEY,
@@ -23,9 +33,35 @@ CREATE TABLE IF NOT EXISTS skills (
     updated_at TEXT NOT NULL,
     encrypted_notes TEXT,
     nonce_notes TEXT,
-    UNIQUE(name)
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    is_archived BOOLEAN NOT NULL DEFAULT 0,
+    deleted_at TEXT,
+    parent_id TEXT,
+    category_id TEXT,
+    level_mode TEXT NOT NULL DEFAULT 'manual' CHECK(level_mode IN ('manual', 'computed')),
+    FOREIGN KEY (parent_id) REFERENCES skills(id) ON DELETE SET NULL,
+    FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE SET NULL
 );
 
+-- Names are unique among skills outside the trash, so a trashed name can be reused
+CREATE UNIQUE INDEX IF NOT EXISTS idx_skills_name ON skills(name) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_skills_parent_id ON skills(parent_id);
+CREATE INDEX IF NOT EXISTS idx_skills_category_id ON skills(category_id);
+
+-- Skill Prerequisites (learning path edges: skill_id requires prerequisite_id)
+CREATE TABLE IF NOT EXISTS skill_prerequisites (
+    skill_id TEXT NOT NULL,
+    prerequisite_id TEXT NOT NULL,
+    min_proficiency INTEGER NOT NULL DEFAULT 50 CHECK(min_proficiency BETWEEN 0 AND 100),
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (skill_id, prerequisite_id),
+    CHECK(skill_id != prerequisite_id),
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE,
+    FOREIGN KEY (prerequisite_id) REFERENCES skills(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_prerequisites_prerequisite_id ON skill_prerequisites(prerequisite_id);
+
 -- Skill Progress History
 CREATE TABLE IF NOT EXISTS skill_progress (
     id TEXT PRIMARY KEY,
@@ -33,34 +69,86 @@ CREATE TABLE IF NOT EXISTS skill_progress (
     proficiency_level INTEGER NOT NULL,
     hours_practiced REAL DEFAULT 0.0,
     recorded_at TEXT NOT NULL,
-    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE,
-    INDEX idx_skill_id (skill_id),
-    INDEX idx_recorded_at (recorded_at)
+    -- What booked the entry: the user directly, a finished practice session or a
+    -- completion of a linked habit (source_id points at that session or completion)
+    source_type TEXT NOT NULL DEFAULT 'manual' CHECK(source_type IN ('manual', 'practice_session', 'habit_completion')),
+    source_id TEXT,
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_progress_skill_id ON skill_progress(skill_id);
+CREATE INDEX IF NOT EXISTS idx_skill_progress_recorded_at ON skill_progress(recorded_at);
+
+-- Practice Sessions (the source of truth for skills.total_hours)
+CREATE TABLE IF NOT EXISTS practice_sessions (
+    id TEXT PRIMARY KEY,
+    skill_id TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT,
+    duration_seconds INTEGER NOT NULL DEFAULT 0,
+    focus_rating INTEGER CHECK(focus_rating BETWEEN 1 AND 5),
+    notes TEXT,
+    tags TEXT,
+    -- 'timer' sessions were actually timed. 'progress' sessions stand in for hours
+    -- logged by hand (source_id is the skill_progress row) and 'habit_completion'
+    -- sessions for a linked habit's completion; those only count towards totals
+    source_type TEXT NOT NULL DEFAULT 'timer' CHECK(source_type IN ('timer', 'progress', 'habit_completion')),
+    source_id TEXT,
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE
 );
 
+CREATE INDEX IF NOT EXISTS idx_practice_sessions_skill_id ON practice_sessions(skill_id);
+CREATE INDEX IF NOT EXISTS idx_practice_sessions_started_at ON practice_sessions(started_at);
+-- At most one session can be running at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_practice_sessions_running ON practice_sessions((ended_at IS NULL)) WHERE ended_at IS NULL;
+
 -- Habits Table
 CREATE TABLE IF NOT EXISTS habits (
     id TEXT PRIMARY KEY,
     name TEXT NOT NULL,
     description TEXT,
     frequency TEXT NOT NULL CHECK(frequency IN ('daily', 'weekly', 'monthly')),
+    polarity TEXT NOT NULL DEFAULT 'positive' CHECK(polarity IN ('positive', 'negative')),
     streak_count INTEGER DEFAULT 0,
     best_streak INTEGER DEFAULT 0,
     created_at TEXT NOT NULL,
     last_completed TEXT,
-    UNIQUE(name)
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    is_archived BOOLEAN NOT NULL DEFAULT 0,
+    deleted_at TEXT,
+    category_id TEXT,
+    FOREIGN KEY (category_id) REFERENCES categories(id) ON DELETE SET NULL
 );
 
+CREATE UNIQUE INDEX IF NOT EXISTS idx_habits_name ON habits(name) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_habits_category_id ON habits(category_id);
+
 -- Habit Completions
 CREATE TABLE IF NOT EXISTS habit_completions (
     id TEXT PRIMARY KEY,
     habit_id TEXT NOT NULL,
     completed_at TEXT NOT NULL,
+    completion_type TEXT NOT NULL DEFAULT 'completion' CHECK(completion_type IN ('completion', 'relapse')),
+    FOREIGN KEY (habit_id) REFERENCES habits(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_habit_completions_habit_id ON habit_completions(habit_id);
+CREATE INDEX IF NOT EXISTS idx_habit_completions_completed_at ON habit_completions(completed_at);
+
+-- Habit to Skill Links (each completion contributes practice time and XP to the skill)
+CREATE TABLE IF NOT EXISTS habit_skill_links (
+    habit_id TEXT NOT NULL,
+    skill_id TEXT NOT NULL,
+    minutes_per_completion INTEGER NOT NULL DEFAULT 0 CHECK(minutes_per_completion >= 0),
+    xp_per_completion INTEGER NOT NULL DEFAULT 0 CHECK(xp_per_completion >= 0),
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (habit_id, skill_id),
     FOREIGN KEY (habit_id) REFERENCES habits(id) ON DELETE CASCADE,
-    INDEX idx_habit_id (habit_id),
-    INDEX idx_completed_at (completed_at)
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE
 );
 
+CREATE INDEX IF NOT EXISTS idx_habit_skill_links_skill_id ON habit_skill_links(skill_id);
+
 -- Milestones and Goals
 CREATE TABLE IF NOT EXISTS milestones (
     id TEXT PRIMARY KEY,
@@ -71,11 +159,75 @@ CREATE TABLE IF NOT EXISTS milestones (
     completion_date TEXT,
     is_completed BOOLEAN DEFAULT 0,
     created_at TEXT NOT NULL,
-    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE SET NULL,
-    INDEX idx_skill_id (skill_id),
-    INDEX idx_target_date (target_date)
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_milestones_skill_id ON milestones(skill_id);
+CREATE INDEX IF NOT EXISTS idx_milestones_target_date ON milestones(target_date);
+
+-- Reminders for habits and milestones (weekdays is a bitmask, Monday = 1 through Sunday = 64)
+CREATE TABLE IF NOT EXISTS reminders (
+    id TEXT PRIMARY KEY,
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('habit', 'milestone')),
+    entity_id TEXT NOT NULL,
+    time_of_day TEXT NOT NULL,
+    weekdays INTEGER NOT NULL DEFAULT 127 CHECK(weekdays BETWEEN 1 AND 127),
+    only_if_not_done BOOLEAN NOT NULL DEFAULT 1,
+    is_enabled BOOLEAN NOT NULL DEFAULT 1,
+    snoozed_until TEXT,
+    last_fired_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_entity ON reminders(entity_type, entity_id);
+
+-- Reminders cannot reference two tables with a foreign key, so they are removed by trigger
+CREATE TRIGGER IF NOT EXISTS reminders_habit_cleanup AFTER DELETE ON habits
+BEGIN
+    DELETE FROM reminders WHERE entity_type = 'habit' AND entity_id = OLD.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS reminders_milestone_cleanup AFTER DELETE ON milestones
+BEGIN
+    DELETE FROM reminders WHERE entity_type = 'milestone' AND entity_id = OLD.id;
+END;
+
+-- Tags shared by skills, habits and milestones
+CREATE TABLE IF NOT EXISTS tags (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL COLLATE NOCASE,
+    created_at TEXT NOT NULL,
+    UNIQUE(name)
 );
 
+CREATE TABLE IF NOT EXISTS skill_tags (
+    tag_id TEXT NOT NULL,
+    skill_id TEXT NOT NULL,
+    PRIMARY KEY (tag_id, skill_id),
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    FOREIGN KEY (skill_id) REFERENCES skills(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS habit_tags (
+    tag_id TEXT NOT NULL,
+    habit_id TEXT NOT NULL,
+    PRIMARY KEY (tag_id, habit_id),
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    FOREIGN KEY (habit_id) REFERENCES habits(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS milestone_tags (
+    tag_id TEXT NOT NULL,
+    milestone_id TEXT NOT NULL,
+    PRIMARY KEY (tag_id, milestone_id),
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE,
+    FOREIGN KEY (milestone_id) REFERENCES milestones(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_tags_skill_id ON skill_tags(skill_id);
+CREATE INDEX IF NOT EXISTS idx_habit_tags_habit_id ON habit_tags(habit_id);
+CREATE INDEX IF NOT EXISTS idx_milestone_tags_milestone_id ON milestone_tags(milestone_id);
+
 -- Application Settings (encrypted)
 CREATE TABLE IF NOT EXISTS settings (
     key TEXT PRIMARY KEY,
@@ -83,6 +235,70 @@ CREATE TABLE IF NOT EXISTS settings (
     nonce TEXT NOT NULL
 );
 
+-- XP Ledger (append-only; totals and levels are always summed from these rows)
+CREATE TABLE IF NOT EXISTS xp_ledger (
+    id TEXT PRIMARY KEY,
+    source_type TEXT NOT NULL CHECK(source_type IN ('habit_completion', 'practice_session', 'milestone')),
+    source_id TEXT NOT NULL,
+    skill_id TEXT,
+    base_xp INTEGER NOT NULL,
+    multiplier REAL NOT NULL DEFAULT 1.0,
+    xp INTEGER NOT NULL,
+    reason TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_created_at ON xp_ledger(created_at);
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_skill_id ON xp_ledger(skill_id);
+CREATE INDEX IF NOT EXISTS idx_xp_ledger_source ON xp_ledger(source_type, source_id);
+
+CREATE TRIGGER IF NOT EXISTS xp_ledger_no_update BEFORE UPDATE ON xp_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'xp_ledger is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS xp_ledger_no_delete BEFORE DELETE ON xp_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'xp_ledger is append-only');
+END;
+
+-- Activity Log (append-only; written in the same transaction as the change it describes)
+CREATE TABLE IF NOT EXISTS activity_events (
+    id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    payload TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_events_created_at ON activity_events(created_at);
+CREATE INDEX IF NOT EXISTS idx_activity_events_entity ON activity_events(entity_type, entity_id);
+
+CREATE TRIGGER IF NOT EXISTS activity_events_no_update BEFORE UPDATE ON activity_events
+BEGIN
+    SELECT RAISE(ABORT, 'activity_events is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS activity_events_no_delete BEFORE DELETE ON activity_events
+BEGIN
+    SELECT RAISE(ABORT, 'activity_events is append-only');
+END;
+
+-- Achievement Unlocks (definitions live in code; ids are stable)
+CREATE TABLE IF NOT EXISTS achievement_unlocks (
+    achievement_id TEXT PRIMARY KEY,
+    unlocked_at TEXT NOT NULL
+);
+
+-- Preferences (plain JSON values for non-secret configuration)
+CREATE TABLE IF NOT EXISTS preferences (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
 -- Data Export History
 CREATE TABLE IF NOT EXISTS exports (
     id TEXT PRIMARY KEY,
@@ -0,0 +1,563 @@
+use crate::db::{
+    HabitCompletionRate, HourActivity, ProficiencyVelocity, SkillHoursPoint, TrendPoint, WeekdayActivity,
+    WeekdayStats,
+};
+use crate::error::Result;
+use super::activity::{parse_bound, parse_date};
+use super::operations::{local_date, parse_timestamp, period_index};
+use chrono::NaiveDate;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+const WEEKDAY_NAMES: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Habit completions and finished timed practice sessions of items that are not in
+/// the trash, one row each. Sessions booked for logged hours or linked habit
+/// completions are left out. `?1` and `?2` bind the UTC range.
+const ACTIVITY_ROWS: &str = "
+    SELECT c.completed_at AS at, 1 AS completions, 0 AS sessions, 0 AS seconds
+    FROM habit_completions c JOIN habits h ON h.id = c.habit_id
+    WHERE c.completion_type = 'completion' AND h.deleted_at IS NULL
+      AND c.completed_at >= ?1 AND c.completed_at < ?2
+    UNION ALL
+    SELECT ps.started_at, 0, 1, ps.duration_seconds
+    FROM practice_sessions ps JOIN skills s ON s.id = ps.skill_id
+    WHERE ps.ended_at IS NOT NULL AND ps.source_type = 'timer' AND s.deleted_at IS NULL
+      AND ps.started_at >= ?1 AND ps.started_at < ?2";
+
+// ============ STATISTICS OPERATIONS ============
+//
+// Every range is a pair of inclusive local dates (YYYY-MM-DD), and every bucket is
+// a local calendar day, week (starting Monday) or month.
+
+/// Hours practiced per skill in each week or month, from finished sessions of
+/// every source, so the hours add up to the skill's total
+pub async fn get_skill_hours_by_period(
+    pool: &SqlitePool,
+    period: &str,
+    from: &str,
+    to: &str,
+    skill_id: Option<&str>,
+) -> Result<Vec<SkillHoursPoint>> {
+    let bucket = match period {
+        "weekly" => "date(ps.started_at, 'localtime', 'weekday 0', '-6 days')",
+        "monthly" => "strftime('%Y-%m-01', ps.started_at, 'localtime')",
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Invalid period. Must be 'weekly' or 'monthly'".to_string(),
+            ))
+        }
+    };
+    let (start, end) = range_bounds(from, to)?;
+
+    let query_str = format!(
+        "SELECT s.id AS skill_id, s.name AS skill_name, {} AS period_start,
+                SUM(ps.duration_seconds) / 3600.0 AS hours
+         FROM practice_sessions ps JOIN skills s ON s.id = ps.skill_id
+         WHERE ps.ended_at IS NOT NULL AND s.deleted_at IS NULL
+           AND ps.started_at >= ?1 AND ps.started_at < ?2 AND (?3 IS NULL OR s.id = ?3)
+         GROUP BY s.id, period_start
+         ORDER BY period_start ASC, s.name ASC",
+        bucket
+    );
+
+    let points = sqlx::query_as::<_, SkillHoursPoint>(&query_str)
+        .bind(&start)
+        .bind(&end)
+        .bind(skill_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(points)
+}
+
+/// Share of days, weeks or months (by each habit's frequency) in the range in which
+/// an active positive habit was completed at least once
+pub async fn get_habit_completion_rates(pool: &SqlitePool, from: &str, to: &str) -> Result<Vec<HabitCompletionRate>> {
+    let (start, end) = range_bounds(from, to)?;
+    let from_date = parse_date(from)?;
+    let to_date = parse_date(to)?;
+
+    let rows = sqlx::query_as::<_, (String, String, String, String, i64)>(
+        "SELECT h.id, h.name, h.frequency, h.created_at,
+                (SELECT COUNT(DISTINCT CASE h.frequency
+                            WHEN 'weekly' THEN date(c.completed_at, 'localtime', 'weekday 0', '-6 days')
+                            WHEN 'monthly' THEN strftime('%Y-%m', c.completed_at, 'localtime')
+                            ELSE date(c.completed_at, 'localtime')
+                        END)
+                 FROM habit_completions c
+                 WHERE c.habit_id = h.id AND c.completion_type = 'completion'
+                   AND c.completed_at >= ?1 AND c.completed_at < ?2) AS periods_completed
+         FROM habits h
+         WHERE h.deleted_at IS NULL AND h.is_archived = 0 AND h.polarity = 'positive'
+         ORDER BY h.sort_order ASC, h.created_at DESC"
+    )
+    .bind(&start)
+    .bind(&end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let mut rates = Vec::with_capacity(rows.len());
+    for (habit_id, habit_name, frequency, created_at, periods_completed) in rows {
+        // Periods before the habit existed are not counted as missed
+        let first_day = from_date.max(local_date(&created_at)?);
+        let periods_expected = periods_between(&frequency, first_day, to_date);
+
+        rates.push(HabitCompletionRate {
+            habit_id,
+            habit_name,
+            frequency,
+            periods_expected,
+            periods_completed,
+            completion_rate: completion_rate(periods_completed, periods_expected),
+        });
+    }
+
+    Ok(rates)
+}
+
+/// Activity per weekday, with the most and least active days. Days are ranked by
+/// habit completions plus practice sessions.
+pub async fn get_weekday_stats(pool: &SqlitePool, from: &str, to: &str) -> Result<WeekdayStats> {
+    let buckets = activity_buckets(pool, "%w", from, to).await?;
+
+    // SQLite numbers Sunday 0; the week here starts on Monday
+    let days: Vec<WeekdayActivity> = (0..7)
+        .map(|weekday| {
+            let (completions, sessions, seconds) = buckets.get(&((weekday + 1) % 7)).copied().unwrap_or((0, 0, 0));
+            WeekdayActivity {
+                weekday,
+                name: WEEKDAY_NAMES[weekday as usize].to_string(),
+                habit_completions: completions,
+                practice_sessions: sessions,
+                practice_minutes: seconds / 60,
+            }
+        })
+        .collect();
+
+    let (best_day, worst_day) = best_and_worst(&days);
+    Ok(WeekdayStats { days, best_day, worst_day })
+}
+
+/// Activity per local hour of the day, 0 through 23
+pub async fn get_time_of_day_stats(pool: &SqlitePool, from: &str, to: &str) -> Result<Vec<HourActivity>> {
+    let buckets = activity_buckets(pool, "%H", from, to).await?;
+
+    Ok((0..24)
+        .map(|hour| {
+            let (completions, sessions, seconds) = buckets.get(&hour).copied().unwrap_or((0, 0, 0));
+            HourActivity {
+                hour,
+                habit_completions: completions,
+                practice_sessions: sessions,
+                practice_minutes: seconds / 60,
+            }
+        })
+        .collect())
+}
+
+/// Daily series of `metric` ("practice_minutes" or "habit_completions") for every
+/// day in the range, with a trailing moving average over `window` days.
+/// Practice minutes come from timed sessions only. `skill_id` narrows practice
+/// minutes only; habit completions reject it.
+pub async fn get_activity_trend(
+    pool: &SqlitePool,
+    metric: &str,
+    from: &str,
+    to: &str,
+    window: usize,
+    skill_id: Option<&str>,
+) -> Result<Vec<TrendPoint>> {
+    let query_str = match metric {
+        "practice_minutes" => {
+            "SELECT date(ps.started_at, 'localtime') AS day, SUM(ps.duration_seconds) / 60.0
+             FROM practice_sessions ps JOIN skills s ON s.id = ps.skill_id
+             WHERE ps.ended_at IS NOT NULL AND ps.source_type = 'timer' AND s.deleted_at IS NULL
+               AND ps.started_at >= ?1 AND ps.started_at < ?2 AND (?3 IS NULL OR s.id = ?3)
+             GROUP BY day"
+        }
+        "habit_completions" if skill_id.is_some() => {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Habit completions cannot be filtered by skill".to_string(),
+            ))
+        }
+        "habit_completions" => {
+            "SELECT date(c.completed_at, 'localtime') AS day, CAST(COUNT(*) AS REAL)
+             FROM habit_completions c JOIN habits h ON h.id = c.habit_id
+             WHERE c.completion_type = 'completion' AND h.deleted_at IS NULL
+               AND c.completed_at >= ?1 AND c.completed_at < ?2 AND ?3 IS NULL
+             GROUP BY day"
+        }
+        _ => {
+            return Err(crate::error::SkillEdError::ValidationError(
+                "Invalid metric. Must be 'practice_minutes' or 'habit_completions'".to_string(),
+            ))
+        }
+    };
+    if !(1..=365).contains(&window) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Moving average window must be between 1 and 365 days".to_string(),
+        ));
+    }
+    let (start, end) = range_bounds(from, to)?;
+
+    let values: HashMap<String, f64> = sqlx::query_as::<_, (String, f64)>(query_str)
+        .bind(&start)
+        .bind(&end)
+        .bind(skill_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .collect();
+
+    let from_date = parse_date(from)?;
+    let to_date = parse_date(to)?;
+    let days: Vec<NaiveDate> = from_date.iter_days().take_while(|day| *day <= to_date).collect();
+    let series: Vec<f64> = days
+        .iter()
+        .map(|day| values.get(&day.to_string()).copied().unwrap_or(0.0))
+        .collect();
+    let averages = moving_average(&series, window);
+
+    Ok(days
+        .iter()
+        .zip(series.iter().zip(averages))
+        .map(|(day, (value, moving_average))| TrendPoint {
+            date: day.to_string(),
+            value: *value,
+            moving_average,
+        })
+        .collect())
+}
+
+/// How fast a skill's proficiency changed over the range, per week and per hour practiced
+pub async fn get_proficiency_velocity(
+    pool: &SqlitePool,
+    skill_id: &str,
+    from: &str,
+    to: &str,
+) -> Result<ProficiencyVelocity> {
+    let (start, end) = range_bounds(from, to)?;
+
+    let entries = sqlx::query_as::<_, (i32, String)>(
+        "SELECT proficiency_level, recorded_at FROM skill_progress
+         WHERE skill_id = ?1 AND recorded_at >= ?2 AND recorded_at < ?3
+         ORDER BY recorded_at ASC"
+    )
+    .bind(skill_id)
+    .bind(&start)
+    .bind(&end)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let hours_practiced = sqlx::query_scalar::<_, f64>(
+        "SELECT COALESCE(SUM(duration_seconds), 0) / 3600.0 FROM practice_sessions
+         WHERE skill_id = ?1 AND ended_at IS NOT NULL AND started_at >= ?2 AND started_at < ?3"
+    )
+    .bind(skill_id)
+    .bind(&start)
+    .bind(&end)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let (first, last) = match (entries.first(), entries.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => {
+            return Ok(ProficiencyVelocity {
+                skill_id: skill_id.to_string(),
+                entries: 0,
+                from_level: None,
+                to_level: None,
+                days: 0.0,
+                hours_practiced,
+                points_per_week: 0.0,
+                points_per_hour: None,
+            })
+        }
+    };
+
+    let days = (parse_timestamp(&last.1)? - parse_timestamp(&first.1)?).num_seconds() as f64 / 86_400.0;
+    let change = (last.0 - first.0) as f64;
+
+    Ok(ProficiencyVelocity {
+        skill_id: skill_id.to_string(),
+        entries: entries.len() as i64,
+        from_level: Some(first.0),
+        to_level: Some(last.0),
+        days,
+        hours_practiced,
+        points_per_week: if days > 0.0 { change / days * 7.0 } else { 0.0 },
+        points_per_hour: (hours_practiced > 0.0).then(|| change / hours_practiced),
+    })
+}
+
+// ============ HELPERS ============
+
+/// Completions, sessions and practice seconds grouped by a local `strftime` field
+async fn activity_buckets(
+    pool: &SqlitePool,
+    format: &str,
+    from: &str,
+    to: &str,
+) -> Result<HashMap<i32, (i64, i64, i64)>> {
+    let (start, end) = range_bounds(from, to)?;
+    let query_str = format!(
+        "SELECT CAST(strftime('{}', at, 'localtime') AS INTEGER) AS bucket,
+                SUM(completions), SUM(sessions), SUM(seconds)
+         FROM ({}) GROUP BY bucket",
+        format, ACTIVITY_ROWS
+    );
+
+    let rows = sqlx::query_as::<_, (i32, i64, i64, i64)>(&query_str)
+        .bind(&start)
+        .bind(&end)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(bucket, completions, sessions, seconds)| (bucket, (completions, sessions, seconds)))
+        .collect())
+}
+
+/// Validates an inclusive local date range and returns its UTC bounds
+fn range_bounds(from: &str, to: &str) -> Result<(String, String)> {
+    if parse_date(from)? > parse_date(to)? {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Start date must not be after the end date".to_string(),
+        ));
+    }
+
+    Ok((parse_bound(from, false)?, parse_bound(to, true)?))
+}
+
+/// Number of days, weeks or months touched by the inclusive range
+fn periods_between(frequency: &str, start: NaiveDate, end: NaiveDate) -> i64 {
+    if start > end {
+        return 0;
+    }
+    period_index(frequency, end) - period_index(frequency, start) + 1
+}
+
+fn completion_rate(completed: i64, expected: i64) -> f64 {
+    if expected <= 0 {
+        return 0.0;
+    }
+    (completed as f64 / expected as f64).min(1.0)
+}
+
+/// Trailing average over up to `window` values; the first points average what is available
+fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut sum = 0.0;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= window {
+                sum -= values[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect()
+}
+
+fn best_and_worst(days: &[WeekdayActivity]) -> (Option<String>, Option<String>) {
+    let score = |day: &WeekdayActivity| day.habit_completions + day.practice_sessions;
+    if days.iter().all(|day| score(day) == 0) {
+        return (None, None);
+    }
+
+    // Ties go to the earlier day of the week
+    let best = days.iter().rev().max_by_key(|day| score(day)).map(|day| day.name.clone());
+    let worst = days.iter().min_by_key(|day| score(day)).map(|day| day.name.clone());
+    (best, worst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    fn day(value: &str) -> NaiveDate {
+        parse_date(value).unwrap()
+    }
+
+    /// A skill with sessions in two weeks and a daily habit completed on three days,
+    /// all at midday UTC so local dates match in practically every timezone
+    async fn seeded_pool() -> (SqlitePool, String, String) {
+        let pool = test_pool().await;
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+
+        sqlx::query("UPDATE habits SET created_at = '2024-03-01T12:00:00+00:00' WHERE id = ?")
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Monday 4 March, Wednesday 6 March and Monday 11 March
+        let sessions = [
+            ("s1", "2024-03-04T12:00:00+00:00", 3600),
+            ("s2", "2024-03-06T12:00:00+00:00", 1800),
+            ("s3", "2024-03-11T12:00:00+00:00", 5400),
+        ];
+        for (id, started_at, seconds) in sessions {
+            sqlx::query(
+                "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds)
+                 VALUES (?, ?, ?, ?, ?)"
+            )
+            .bind(id)
+            .bind(&skill.id)
+            .bind(started_at)
+            .bind(started_at)
+            .bind(seconds)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let completions = [
+            ("c1", "2024-03-04T12:00:00+00:00"),
+            ("c2", "2024-03-05T12:00:00+00:00"),
+            ("c3", "2024-03-11T12:00:00+00:00"),
+        ];
+        for (id, completed_at) in completions {
+            sqlx::query("INSERT INTO habit_completions (id, habit_id, completed_at) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(&habit.id)
+                .bind(completed_at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let progress = [("p1", 10, "2024-03-04T12:00:00+00:00"), ("p2", 24, "2024-03-11T12:00:00+00:00")];
+        for (id, level, recorded_at) in progress {
+            sqlx::query(
+                "INSERT INTO skill_progress (id, skill_id, proficiency_level, hours_practiced, recorded_at)
+                 VALUES (?, ?, ?, 0, ?)"
+            )
+            .bind(id)
+            .bind(&skill.id)
+            .bind(level)
+            .bind(recorded_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        (pool, skill.id, habit.id)
+    }
+
+    #[test]
+    fn test_moving_average_uses_available_points_at_the_start() {
+        let averages = moving_average(&[2.0, 4.0, 6.0, 8.0], 2);
+        assert_eq!(averages, vec![2.0, 3.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn test_periods_between_by_frequency() {
+        assert_eq!(periods_between("daily", day("2024-03-01"), day("2024-03-10")), 10);
+        assert_eq!(periods_between("weekly", day("2024-03-03"), day("2024-03-04")), 2);
+        assert_eq!(periods_between("monthly", day("2024-01-31"), day("2024-03-01")), 3);
+        assert_eq!(periods_between("daily", day("2024-03-10"), day("2024-03-01")), 0);
+    }
+
+    #[tokio::test]
+    async fn test_weekly_hours_per_skill() {
+        let (pool, skill_id, _) = seeded_pool().await;
+        let points = get_skill_hours_by_period(&pool, "weekly", "2024-03-01", "2024-03-31", Some(&skill_id))
+            .await
+            .unwrap();
+
+        let weeks: Vec<(&str, f64)> = points.iter().map(|p| (p.period_start.as_str(), p.hours)).collect();
+        assert_eq!(weeks, vec![("2024-03-04", 1.5), ("2024-03-11", 1.5)]);
+
+        let monthly = get_skill_hours_by_period(&pool, "monthly", "2024-03-01", "2024-03-31", None).await.unwrap();
+        assert_eq!(monthly.len(), 1);
+        assert_eq!(monthly[0].hours, 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_completion_rate_ignores_days_before_creation() {
+        let (pool, _, habit_id) = seeded_pool().await;
+        let rates = get_habit_completion_rates(&pool, "2024-02-20", "2024-03-12").await.unwrap();
+
+        let rate = rates.iter().find(|r| r.habit_id == habit_id).unwrap();
+        assert_eq!(rate.periods_expected, 12);
+        assert_eq!(rate.periods_completed, 3);
+        assert!((rate.completion_rate - 0.25).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_weekday_and_time_of_day_distribution() {
+        let (pool, _, _) = seeded_pool().await;
+        let weekdays = get_weekday_stats(&pool, "2024-03-01", "2024-03-31").await.unwrap();
+
+        assert_eq!(weekdays.days[0].name, "Monday");
+        assert_eq!(weekdays.days[0].habit_completions, 2);
+        assert_eq!(weekdays.days[0].practice_minutes, 150);
+        assert_eq!(weekdays.best_day.as_deref(), Some("Monday"));
+        assert_eq!(weekdays.worst_day.as_deref(), Some("Thursday"));
+
+        let hours = get_time_of_day_stats(&pool, "2024-03-01", "2024-03-31").await.unwrap();
+        assert_eq!(hours.len(), 24);
+        assert_eq!(hours.iter().map(|h| h.habit_completions).sum::<i64>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_trend_and_velocity() {
+        let (pool, skill_id, _) = seeded_pool().await;
+        let trend = get_activity_trend(&pool, "practice_minutes", "2024-03-04", "2024-03-06", 2, None)
+            .await
+            .unwrap();
+
+        let values: Vec<(f64, f64)> = trend.iter().map(|p| (p.value, p.moving_average)).collect();
+        assert_eq!(values, vec![(60.0, 60.0), (0.0, 30.0), (30.0, 15.0)]);
+        assert!(get_activity_trend(&pool, "habit_completions", "2024-03-04", "2024-03-06", 2, Some(&skill_id))
+            .await
+            .is_err());
+
+        let velocity = get_proficiency_velocity(&pool, &skill_id, "2024-03-01", "2024-03-31").await.unwrap();
+        assert_eq!((velocity.from_level, velocity.to_level), (Some(10), Some(24)));
+        assert!((velocity.points_per_week - 14.0).abs() < 1e-9);
+        assert!((velocity.points_per_hour.unwrap() - 14.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_booked_sessions_only_count_towards_hours() {
+        let (pool, skill_id, _) = seeded_pool().await;
+        // An hour booked by a linked habit completion on Tuesday 5 March
+        sqlx::query(
+            "INSERT INTO practice_sessions (id, skill_id, started_at, ended_at, duration_seconds, source_type, source_id)
+             VALUES ('s4', ?, '2024-03-05T12:00:00+00:00', '2024-03-05T13:00:00+00:00', 3600, 'habit_completion', 'c2')"
+        )
+        .bind(&skill_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let weekdays = get_weekday_stats(&pool, "2024-03-01", "2024-03-31").await.unwrap();
+        assert_eq!((weekdays.days[1].practice_sessions, weekdays.days[1].practice_minutes), (0, 0));
+        let hours = get_time_of_day_stats(&pool, "2024-03-01", "2024-03-31").await.unwrap();
+        assert_eq!(hours.iter().map(|h| h.practice_sessions).sum::<i64>(), 3);
+        let trend = get_activity_trend(&pool, "practice_minutes", "2024-03-05", "2024-03-05", 1, None)
+            .await
+            .unwrap();
+        assert_eq!(trend[0].value, 0.0);
+
+        let monthly = get_skill_hours_by_period(&pool, "monthly", "2024-03-01", "2024-03-31", None).await.unwrap();
+        assert_eq!(monthly[0].hours, 4.0);
+    }
+}
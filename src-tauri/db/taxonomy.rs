@@ -0,0 +1,612 @@
+use crate::db::{Category, Tag};
+use crate::error::Result;
+use super::activity::{notify_changes, record_activity};
+use sqlx::{SqliteConnection, SqlitePool};
+use chrono::Utc;
+use uuid::Uuid;
+
+// ============ CATEGORY OPERATIONS ============
+
+pub async fn create_category(
+    pool: &SqlitePool,
+    name: &str,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<Category> {
+    let name = normalize_name(name, "Category")?;
+    if let Some(color) = &color {
+        validate_color(color)?;
+    }
+
+    if find_category_by_name(pool, &name).await?.is_some() {
+        return Err(crate::error::SkillEdError::DuplicateName {
+            entity: "category".to_string(),
+            name,
+        });
+    }
+
+    let category = Category {
+        id: Uuid::new_v4().to_string(),
+        name,
+        color,
+        icon,
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    sqlx::query("INSERT INTO categories (id, name, color, icon, created_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&category.id)
+        .bind(&category.name)
+        .bind(&category.color)
+        .bind(&category.icon)
+        .bind(&category.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "categories", "category", &category.name))?;
+
+    record_activity(&mut tx, "category_created", "category", &category.id, format!("Created category {}", category.name), &category).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(category)
+}
+
+pub async fn get_categories(pool: &SqlitePool) -> Result<Vec<Category>> {
+    sqlx::query_as::<_, Category>("SELECT id, name, color, icon, created_at FROM categories ORDER BY name ASC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+/// Looks a category up by name, ignoring case and surrounding whitespace,
+/// and creates it when it does not exist yet
+pub async fn ensure_category(conn: &mut SqliteConnection, name: &str) -> Result<Category> {
+    let name = normalize_name(name, "Category")?;
+
+    let existing = sqlx::query_as::<_, Category>(
+        "SELECT id, name, color, icon, created_at FROM categories WHERE name = ? COLLATE NOCASE"
+    )
+    .bind(&name)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if let Some(category) = existing {
+        return Ok(category);
+    }
+
+    let category = Category {
+        id: Uuid::new_v4().to_string(),
+        name,
+        color: None,
+        icon: None,
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    sqlx::query("INSERT INTO categories (id, name, color, icon, created_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&category.id)
+        .bind(&category.name)
+        .bind(&category.color)
+        .bind(&category.icon)
+        .bind(&category.created_at)
+        .execute(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "categories", "category", &category.name))?;
+
+    Ok(category)
+}
+
+/// Renames a category and updates its colour and icon. Renaming onto an existing
+/// category is rejected; use `merge_categories` for that.
+pub async fn update_category(
+    pool: &SqlitePool,
+    category_id: &str,
+    name: Option<&str>,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<Category> {
+    let mut category = get_category(pool, category_id).await?;
+
+    if let Some(name) = name {
+        let name = normalize_name(name, "Category")?;
+        if let Some(other) = find_category_by_name(pool, &name).await? {
+            // Renaming onto another category is a merge, which `merge_categories` does
+            if other.id != category.id {
+                return Err(crate::error::SkillEdError::DuplicateName {
+                    entity: "category".to_string(),
+                    name,
+                });
+            }
+        }
+        category.name = name;
+    }
+    if let Some(color) = color {
+        validate_color(&color)?;
+        category.color = Some(color);
+    }
+    if let Some(icon) = icon {
+        category.icon = Some(icon);
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    sqlx::query("UPDATE categories SET name = ?, color = ?, icon = ? WHERE id = ?")
+        .bind(&category.name)
+        .bind(&category.color)
+        .bind(&category.icon)
+        .bind(&category.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "categories", "category", &category.name))?;
+
+    // skills.category mirrors the category name for display
+    sqlx::query("UPDATE skills SET category = ? WHERE category_id = ?")
+        .bind(&category.name)
+        .bind(&category.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    record_activity(&mut tx, "category_updated", "category", &category.id, format!("Updated category {}", category.name), &category).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(category)
+}
+
+/// Moves every skill and habit from `source_id` into `target_id` and removes the source
+pub async fn merge_categories(pool: &SqlitePool, source_id: &str, target_id: &str) -> Result<Category> {
+    if source_id == target_id {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Cannot merge a category into itself".to_string(),
+        ));
+    }
+
+    let source = get_category(pool, source_id).await?;
+    let target = get_category(pool, target_id).await?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    sqlx::query("UPDATE skills SET category_id = ?, category = ? WHERE category_id = ?")
+        .bind(&target.id)
+        .bind(&target.name)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    sqlx::query("UPDATE habits SET category_id = ? WHERE category_id = ?")
+        .bind(&target.id)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    sqlx::query("DELETE FROM categories WHERE id = ?")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    record_activity(
+        &mut tx,
+        "categories_merged",
+        "category",
+        &target.id,
+        format!("Merged category {} into {}", source.name, target.name),
+        &target,
+    )
+    .await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(target)
+}
+
+async fn get_category(pool: &SqlitePool, category_id: &str) -> Result<Category> {
+    sqlx::query_as::<_, Category>("SELECT id, name, color, icon, created_at FROM categories WHERE id = ?")
+        .bind(category_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Category {}", category_id)))
+}
+
+async fn find_category_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Category>> {
+    sqlx::query_as::<_, Category>(
+        "SELECT id, name, color, icon, created_at FROM categories WHERE name = ? COLLATE NOCASE"
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+// ============ TAG OPERATIONS ============
+
+pub async fn get_tags(pool: &SqlitePool) -> Result<Vec<Tag>> {
+    sqlx::query_as::<_, Tag>("SELECT id, name, created_at FROM tags ORDER BY name ASC")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+/// Tags a skill, habit or milestone, creating the tag when it does not exist yet.
+/// `entity_type` is one of "skill", "habit" or "milestone".
+pub async fn add_tag(pool: &SqlitePool, entity_type: &str, entity_id: &str, tag_name: &str) -> Result<Tag> {
+    let (table, column) = tag_table(entity_type)?;
+    let name = normalize_name(tag_name, "Tag")?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // tag_table has vetted entity_type, so this names the skills, habits or milestones table
+    let query_str = format!("SELECT COUNT(*) FROM {}s WHERE id = ?", entity_type);
+    let exists = sqlx::query_scalar::<_, i64>(&query_str)
+        .bind(entity_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    if exists == 0 {
+        return Err(crate::error::SkillEdError::NotFound(format!("{} {}", entity_type, entity_id)));
+    }
+
+    let existing = sqlx::query_as::<_, Tag>("SELECT id, name, created_at FROM tags WHERE name = ? COLLATE NOCASE")
+        .bind(&name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let tag = match existing {
+        Some(tag) => tag,
+        None => {
+            let tag = Tag {
+                id: Uuid::new_v4().to_string(),
+                name,
+                created_at: Utc::now().to_rfc3339(),
+            };
+
+            sqlx::query("INSERT INTO tags (id, name, created_at) VALUES (?, ?, ?)")
+                .bind(&tag.id)
+                .bind(&tag.name)
+                .bind(&tag.created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| crate::error::SkillEdError::from_name_write(e, "tags", "tag", &tag.name))?;
+
+            tag
+        }
+    };
+
+    let query_str = format!("INSERT OR IGNORE INTO {} (tag_id, {}) VALUES (?, ?)", table, column);
+    let added = sqlx::query(&query_str)
+        .bind(&tag.id)
+        .bind(entity_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+    if added > 0 {
+        record_activity(&mut tx, "tag_added", entity_type, entity_id, format!("Tagged {} {}", entity_type, tag.name), &tag).await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(tag)
+}
+
+pub async fn remove_tag(pool: &SqlitePool, entity_type: &str, entity_id: &str, tag_id: &str) -> Result<()> {
+    let (table, column) = tag_table(entity_type)?;
+    let query_str = format!("DELETE FROM {} WHERE tag_id = ? AND {} = ?", table, column);
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let removed = sqlx::query(&query_str)
+        .bind(tag_id)
+        .bind(entity_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .rows_affected();
+
+    if removed > 0 {
+        let tag = get_tag(&mut tx, tag_id).await?;
+        record_activity(&mut tx, "tag_removed", entity_type, entity_id, format!("Untagged {} {}", entity_type, tag.name), &tag).await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+pub async fn get_entity_tags(pool: &SqlitePool, entity_type: &str, entity_id: &str) -> Result<Vec<Tag>> {
+    let (table, column) = tag_table(entity_type)?;
+    let query_str = format!(
+        "SELECT t.id, t.name, t.created_at FROM tags t
+         JOIN {} et ON et.tag_id = t.id
+         WHERE et.{} = ? ORDER BY t.name ASC",
+        table, column
+    );
+
+    sqlx::query_as::<_, Tag>(&query_str)
+        .bind(entity_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))
+}
+
+pub async fn rename_tag(pool: &SqlitePool, tag_id: &str, name: &str) -> Result<Tag> {
+    let name = normalize_name(name, "Tag")?;
+
+    let clash = sqlx::query_scalar::<_, String>("SELECT id FROM tags WHERE name = ? COLLATE NOCASE AND id != ?")
+        .bind(&name)
+        .bind(tag_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    // Renaming onto another tag is a merge, which `merge_tags` does
+    if clash.is_some() {
+        return Err(crate::error::SkillEdError::DuplicateName {
+            entity: "tag".to_string(),
+            name,
+        });
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let old_name = get_tag(&mut tx, tag_id).await?.name;
+
+    sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+        .bind(&name)
+        .bind(tag_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::from_name_write(e, "tags", "tag", &name))?;
+
+    let tag = get_tag(&mut tx, tag_id).await?;
+    record_activity(&mut tx, "tag_renamed", "tag", tag_id, format!("Renamed tag {} to {}", old_name, tag.name), &tag).await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(tag)
+}
+
+async fn get_tag(conn: &mut SqliteConnection, tag_id: &str) -> Result<Tag> {
+    sqlx::query_as::<_, Tag>("SELECT id, name, created_at FROM tags WHERE id = ?")
+        .bind(tag_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Tag {}", tag_id)))
+}
+
+/// Re-tags everything tagged `source_id` with `target_id` and removes the source tag
+pub async fn merge_tags(pool: &SqlitePool, source_id: &str, target_id: &str) -> Result<()> {
+    if source_id == target_id {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Cannot merge a tag into itself".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let source = get_tag(&mut tx, source_id).await?;
+    let target = get_tag(&mut tx, target_id).await?;
+
+    for entity_type in ["skill", "habit", "milestone"] {
+        let (table, column) = tag_table(entity_type)?;
+        let query_str = format!(
+            "INSERT OR IGNORE INTO {table} (tag_id, {column})
+             SELECT ?, {column} FROM {table} WHERE tag_id = ?",
+            table = table,
+            column = column
+        );
+
+        sqlx::query(&query_str)
+            .bind(target_id)
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    }
+
+    // The remaining links to the source tag go with it through the cascades
+    sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    record_activity(
+        &mut tx,
+        "tags_merged",
+        "tag",
+        target_id,
+        format!("Merged tag {} into {}", source.name, target.name),
+        &target,
+    )
+    .await?;
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(())
+}
+
+/// Maps an entity type to its tag join table and id column
+pub(super) fn tag_table(entity_type: &str) -> Result<(&'static str, &'static str)> {
+    match entity_type {
+        "skill" => Ok(("skill_tags", "skill_id")),
+        "habit" => Ok(("habit_tags", "habit_id")),
+        "milestone" => Ok(("milestone_tags", "milestone_id")),
+        _ => Err(crate::error::SkillEdError::ValidationError(
+            "Invalid entity type. Must be 'skill', 'habit', or 'milestone'".to_string(),
+        )),
+    }
+}
+
+/// Trims a category or tag name and collapses inner whitespace
+fn normalize_name(name: &str, kind: &str) -> Result<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "{} name cannot be empty",
+            kind
+        )));
+    }
+    Ok(name)
+}
+
+/// Accepts `#rgb` and `#rrggbb` hex colours
+fn validate_color(color: &str) -> Result<()> {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    if (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(crate::error::SkillEdError::ValidationError(
+            "Colour must be a hex value such as #4f46e5".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::operations::{create_habit, create_skill, get_skill, get_skills_filtered};
+    use crate::db::ListFilter;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("  Music   Theory ", "Category").unwrap(), "Music Theory");
+        assert!(normalize_name("   ", "Tag").is_err());
+    }
+
+    #[test]
+    fn test_validate_color() {
+        assert!(validate_color("#4f46e5").is_ok());
+        assert!(validate_color("#FFF").is_ok());
+        assert!(validate_color("4f46e5").is_err());
+        assert!(validate_color("#12345g").is_err());
+    }
+
+    async fn skill_names(pool: &SqlitePool, filter: ListFilter) -> Vec<String> {
+        get_skills_filtered(pool, &filter)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|skill| skill.name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_category_rename_and_merge() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        // Free-text categories resolve ignoring case
+        create_skill(&pool, "Guitar".to_string(), "music".to_string(), None).await.unwrap();
+        create_skill(&pool, "Chess".to_string(), "Games".to_string(), None).await.unwrap();
+        assert_eq!(get_categories(&pool).await.unwrap().len(), 2);
+
+        let music = piano.category_id.clone().unwrap();
+        let renamed = update_category(&pool, &music, Some("Instruments"), None, None).await.unwrap();
+        assert_eq!(renamed.name, "Instruments");
+        assert_eq!(get_skill(&pool, &piano.id).await.unwrap().unwrap().category, "Instruments");
+        let err = update_category(&pool, &music, Some("GAMES"), None, None).await.unwrap_err();
+        assert_eq!(err.code(), "DUPLICATE_NAME");
+
+        let games = find_category_by_name(&pool, "Games").await.unwrap().unwrap();
+        let merged = merge_categories(&pool, &music, &games.id).await.unwrap();
+        assert_eq!(merged.id, games.id);
+        assert_eq!(get_categories(&pool).await.unwrap().len(), 1);
+        let skill = get_skill(&pool, &piano.id).await.unwrap().unwrap();
+        assert_eq!((skill.category_id.as_deref(), skill.category.as_str()), (Some(games.id.as_str()), "Games"));
+    }
+
+    #[tokio::test]
+    async fn test_tag_rename_and_merge() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let habit = create_habit(&pool, "Practice".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+
+        let evening = add_tag(&pool, "skill", &piano.id, "evening").await.unwrap();
+        let night = add_tag(&pool, "habit", &habit.id, "Night").await.unwrap();
+        add_tag(&pool, "skill", &piano.id, "night").await.unwrap();
+        assert_eq!(get_tags(&pool).await.unwrap().len(), 2);
+        assert!(matches!(
+            add_tag(&pool, "skill", "missing", "evening").await,
+            Err(crate::error::SkillEdError::NotFound(_))
+        ));
+
+        let err = rename_tag(&pool, &evening.id, "NIGHT").await.unwrap_err();
+        assert_eq!(err.code(), "DUPLICATE_NAME");
+        assert_eq!(rename_tag(&pool, &evening.id, "Evening").await.unwrap().name, "Evening");
+
+        merge_tags(&pool, &night.id, &evening.id).await.unwrap();
+        let tags = get_tags(&pool).await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(get_entity_tags(&pool, "skill", &piano.id).await.unwrap().len(), 1);
+        assert_eq!(get_entity_tags(&pool, "habit", &habit.id).await.unwrap()[0].id, evening.id);
+
+        let filter = crate::db::ActivityFilter { entity_type: Some("tag".to_string()), ..Default::default() };
+        let events: Vec<String> = crate::db::get_activity_feed(&pool, &filter, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.event_type)
+            .collect();
+        assert_eq!(events, vec!["tags_merged", "tag_renamed"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_filter_category_and_tag_sets() {
+        let pool = crate::db::test_pool().await;
+        let piano = create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+        let guitar = create_skill(&pool, "Guitar".to_string(), "Music".to_string(), None).await.unwrap();
+        let chess = create_skill(&pool, "Chess".to_string(), "Games".to_string(), None).await.unwrap();
+        create_skill(&pool, "Sketching".to_string(), "Art".to_string(), None).await.unwrap();
+        let daily = add_tag(&pool, "skill", &piano.id, "daily").await.unwrap();
+        add_tag(&pool, "skill", &chess.id, "daily").await.unwrap();
+        let weekend = add_tag(&pool, "skill", &guitar.id, "weekend").await.unwrap();
+
+        let music = piano.category_id.clone().unwrap();
+        let games = chess.category_id.clone().unwrap();
+        assert_eq!(skill_names(&pool, ListFilter::default()).await.len(), 4);
+        assert_eq!(
+            skill_names(&pool, ListFilter { category_ids: vec![music.clone(), games.clone()], tag_ids: vec![] }).await,
+            vec!["Chess", "Guitar", "Piano"]
+        );
+        assert_eq!(
+            skill_names(&pool, ListFilter { category_ids: vec![], tag_ids: vec![daily.id.clone(), weekend.id.clone()] }).await,
+            vec!["Chess", "Guitar", "Piano"]
+        );
+        // Both sets must match
+        assert_eq!(
+            skill_names(&pool, ListFilter { category_ids: vec![music], tag_ids: vec![daily.id.clone()] }).await,
+            vec!["Piano"]
+        );
+        assert!(skill_names(&pool, ListFilter { category_ids: vec![games], tag_ids: vec![weekend.id] }).await.is_empty());
+    }
+}
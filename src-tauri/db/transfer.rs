@@ -0,0 +1,509 @@
+use crate::db::{Export, ImportSummary, TableImportSummary, VaultExport};
+use crate::error::Result;
+use super::activity::notify_changes;
+use super::exports::write_export;
+use super::schema;
+use chrono::Utc;
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool, TypeInfo, ValueRef};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Version of the JSON export format; bump it whenever a table or column changes.
+/// Version 2 added reminders.
+pub const EXPORT_SCHEMA_VERSION: u32 = 2;
+
+/// Tables included in an export, parents before children so rows can be
+/// inserted in this order. The login record and the export history belong to
+/// this install and are left out; settings are exported still encrypted, so they
+/// only read back under the same master password.
+pub const EXPORT_TABLES: &[&str] = &[
+    "categories",
+    "skills",
+    "skill_prerequisites",
+    "skill_progress",
+    "practice_sessions",
+    "habits",
+    "habit_completions",
+    "habit_skill_links",
+    "milestones",
+    "reminders",
+    "tags",
+    "skill_tags",
+    "habit_tags",
+    "milestone_tags",
+    "settings",
+    "xp_ledger",
+    "activity_events",
+    "achievement_unlocks",
+    "preferences",
+];
+
+/// Append-only tables whose delete guards are lifted while an import replaces them
+const GUARDED_TRIGGERS: &[&str] = &["xp_ledger_no_delete", "activity_events_no_delete"];
+
+/// Tables whose names are unique; when merging, an exported row that only
+/// clashes by name is matched to the existing row instead of being dropped
+const NAMED_TABLES: &[&str] = &["categories", "skills", "habits", "tags"];
+
+/// Exported ids matched to existing rows while merging, per referenced table
+type IdRemaps = HashMap<&'static str, HashMap<String, String>>;
+
+// ============ EXPORT OPERATIONS ============
+
+/// Reads every exported table into a versioned document
+pub async fn build_export(pool: &SqlitePool) -> Result<VaultExport> {
+    let mut tables = BTreeMap::new();
+
+    for table in EXPORT_TABLES {
+        let rows = sqlx::query(&format!("SELECT * FROM {} ORDER BY rowid", table))
+            .fetch_all(pool)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        let rows = rows.iter().map(row_to_json).collect::<Result<Vec<_>>>()?;
+        tables.insert(table.to_string(), rows);
+    }
+
+    Ok(VaultExport {
+        schema_version: EXPORT_SCHEMA_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        tables,
+    })
+}
+
+/// Writes the full export as JSON to the exports directory and records it
+pub async fn export_data(pool: &SqlitePool, data_dir: &Path) -> Result<Export> {
+    let document = build_export(pool).await?;
+    let contents = serde_json::to_vec_pretty(&document)?;
+    let file_name = format!("skill-ed-export-{}.json", Utc::now().format("%Y%m%d-%H%M%S"));
+
+    write_export(pool, data_dir, "json", &file_name, &contents).await
+}
+
+fn row_to_json(row: &SqliteRow) -> Result<Map<String, Value>> {
+    let mut object = Map::new();
+
+    for column in row.columns() {
+        let index = column.ordinal();
+        let raw = row
+            .try_get_raw(index)
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+        // SQLite is dynamically typed, so go by the stored value rather than the declared column type
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get::<i64, _>(index)
+                    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?),
+                "REAL" => Value::from(row.try_get::<f64, _>(index)
+                    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?),
+                "TEXT" => Value::from(row.try_get::<String, _>(index)
+                    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?),
+                other => {
+                    return Err(crate::error::SkillEdError::DatabaseError(format!(
+                        "Cannot export {} value in column {}",
+                        other,
+                        column.name()
+                    )))
+                }
+            }
+        };
+
+        object.insert(column.name().to_string(), value);
+    }
+
+    Ok(object)
+}
+
+// ============ IMPORT OPERATIONS ============
+
+/// Loads an export into the database in one transaction. `mode` is "merge" to
+/// add only rows whose id is not present yet, or "replace" to clear every
+/// exported table first. When merging, a skill, habit, category or tag whose
+/// name is already taken is matched to the existing row and the exported rows
+/// that reference it are attached there.
+pub async fn import_data(pool: &SqlitePool, document: &VaultExport, mode: &str) -> Result<ImportSummary> {
+    if mode != "merge" && mode != "replace" {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Invalid import mode. Must be 'merge' or 'replace'".to_string(),
+        ));
+    }
+
+    if document.schema_version == 0 || document.schema_version > EXPORT_SCHEMA_VERSION {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Unsupported export schema version {} (this version reads up to {})",
+            document.schema_version, EXPORT_SCHEMA_VERSION
+        )));
+    }
+
+    if let Some(table) = document.tables.keys().find(|table| !EXPORT_TABLES.contains(&table.as_str())) {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Export contains unknown table '{}'",
+            table
+        )));
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    // Rows reference each other within a table (skills.parent_id), so check keys at commit
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    if mode == "replace" {
+        clear_tables(&mut tx).await?;
+    }
+
+    let mut remaps = IdRemaps::new();
+    let mut tables = Vec::new();
+    for table in EXPORT_TABLES {
+        let Some(rows) = document.tables.get(*table) else {
+            continue;
+        };
+
+        let columns = table_columns(&mut tx, table).await?;
+        let mut summary = TableImportSummary {
+            table: table.to_string(),
+            inserted: 0,
+            skipped: 0,
+            merged: 0,
+        };
+
+        for row in rows {
+            let row = remap_references(table, row, &remaps);
+            if insert_row(&mut tx, table, &columns, &row, mode == "merge").await? {
+                summary.inserted += 1;
+            } else if let Some((id, existing_id)) = find_name_clash(&mut tx, table, &row).await? {
+                remaps.entry(table).or_default().insert(id, existing_id);
+                summary.merged += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+
+        tables.push(summary);
+    }
+
+    // Merged sessions may belong to skills that already had hours
+    if mode == "merge" {
+        sqlx::query(
+            "UPDATE skills SET total_hours = (
+                 SELECT COALESCE(SUM(duration_seconds), 0) / 3600.0 FROM practice_sessions
+                 WHERE skill_id = skills.id AND ended_at IS NOT NULL
+             )"
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    }
+
+    tx.commit().await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    notify_changes();
+
+    Ok(ImportSummary {
+        mode: mode.to_string(),
+        schema_version: document.schema_version,
+        tables,
+    })
+}
+
+/// Reads and imports an export file
+pub async fn import_file(pool: &SqlitePool, path: &Path, mode: &str) -> Result<ImportSummary> {
+    let contents = std::fs::read(path)?;
+    let document: VaultExport = serde_json::from_slice(&contents)?;
+
+    import_data(pool, &document, mode).await
+}
+
+async fn clear_tables(conn: &mut SqliteConnection) -> Result<()> {
+    for trigger in GUARDED_TRIGGERS {
+        sqlx::query(&format!("DROP TRIGGER IF EXISTS {}", trigger))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    }
+
+    for table in EXPORT_TABLES.iter().rev() {
+        sqlx::query(&format!("DELETE FROM {}", table))
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    }
+
+    // Restores the dropped triggers; everything else already exists
+    sqlx::query(schema::CREATE_TABLES_SQL)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<HashSet<String>> {
+    let columns = sqlx::query_scalar::<_, String>("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(columns.into_iter().collect())
+}
+
+/// The table an exported id column points at, if any. Reminders and activity
+/// events name their entity by type instead of a foreign key.
+fn reference_target(table: &str, column: &str, row: &Map<String, Value>) -> Option<&'static str> {
+    match (table, column) {
+        ("skills", "parent_id") | (_, "skill_id") | (_, "prerequisite_id") => Some("skills"),
+        (_, "habit_id") => Some("habits"),
+        (_, "category_id") => Some("categories"),
+        (_, "tag_id") => Some("tags"),
+        ("reminders" | "activity_events", "entity_id") => {
+            match row.get("entity_type").and_then(Value::as_str) {
+                Some("skill") => Some("skills"),
+                Some("habit") => Some("habits"),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Points the row's references at the existing rows that exported ids were matched to
+fn remap_references(table: &str, row: &Map<String, Value>, remaps: &IdRemaps) -> Map<String, Value> {
+    let mut row = row.clone();
+    let updates: Vec<(String, String)> = row
+        .iter()
+        .filter_map(|(column, value)| {
+            let target = reference_target(table, column, &row)?;
+            let existing_id = remaps.get(target)?.get(value.as_str()?)?;
+            Some((column.clone(), existing_id.clone()))
+        })
+        .collect();
+
+    for (column, existing_id) in updates {
+        row.insert(column, Value::from(existing_id));
+    }
+
+    row
+}
+
+/// For a row that merging did not insert, the existing row that holds its name
+/// when its id is new: `(exported id, existing id)`
+async fn find_name_clash(
+    conn: &mut SqliteConnection,
+    table: &'static str,
+    row: &Map<String, Value>,
+) -> Result<Option<(String, String)>> {
+    if !NAMED_TABLES.contains(&table) {
+        return Ok(None);
+    }
+    let (Some(id), Some(name)) = (
+        row.get("id").and_then(Value::as_str),
+        row.get("name").and_then(Value::as_str),
+    ) else {
+        return Ok(None);
+    };
+
+    let exists = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {} WHERE id = ?", table))
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    if exists > 0 {
+        return Ok(None);
+    }
+
+    // Trashed skills and habits do not hold their name
+    let active = if table == "skills" || table == "habits" { " AND deleted_at IS NULL" } else { "" };
+    let existing_id = sqlx::query_scalar::<_, String>(&format!("SELECT id FROM {} WHERE name = ?{}", table, active))
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(existing_id.map(|existing_id| (id.to_string(), existing_id)))
+}
+
+/// Inserts one exported row; returns false when merging skipped an existing row
+async fn insert_row(
+    conn: &mut SqliteConnection,
+    table: &str,
+    columns: &HashSet<String>,
+    row: &Map<String, Value>,
+    merge: bool,
+) -> Result<bool> {
+    // Column names are spliced into the SQL, so only ones the table actually has are allowed
+    if let Some(column) = row.keys().find(|column| !columns.contains(*column)) {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Export has unknown column '{}' in table '{}'",
+            column, table
+        )));
+    }
+
+    if row.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(format!(
+            "Export has an empty row in table '{}'",
+            table
+        )));
+    }
+
+    let verb = if merge { "INSERT OR IGNORE INTO" } else { "INSERT INTO" };
+    let mut builder = QueryBuilder::<Sqlite>::new(format!("{} {} (", verb, table));
+    let mut separated = builder.separated(", ");
+    for column in row.keys() {
+        separated.push(column);
+    }
+    builder.push(") VALUES (");
+
+    let mut separated = builder.separated(", ");
+    for (column, value) in row {
+        match value {
+            Value::Null => separated.push_bind(None::<String>),
+            Value::Bool(flag) => separated.push_bind(*flag as i64),
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => separated.push_bind(integer),
+                None => separated.push_bind(number.as_f64().unwrap_or_default()),
+            },
+            Value::String(text) => separated.push_bind(text.clone()),
+            Value::Array(_) | Value::Object(_) => {
+                return Err(crate::error::SkillEdError::ValidationError(format!(
+                    "Export has a nested value in {}.{}",
+                    table, column
+                )))
+            }
+        };
+    }
+    builder.push(")");
+
+    let result = builder
+        .build()
+        .execute(conn)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(format!("Importing into {}: {}", table, e)))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, test_pool};
+
+    async fn seed(pool: &SqlitePool) -> (String, String) {
+        let skill = db::create_skill(pool, "Piano".to_string(), "Music".to_string(), Some("Scales".to_string()))
+            .await
+            .unwrap();
+        db::record_skill_progress(pool, &skill.id, 20, 1.5).await.unwrap();
+        let habit = db::create_habit(pool, "Practice".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::log_habit_completion(pool, &habit.id).await.unwrap();
+        db::create_milestone(pool, "Recital".to_string(), "2030-06-01".to_string(), Some(skill.id.clone()), None)
+            .await
+            .unwrap();
+        (skill.id, habit.id)
+    }
+
+    async fn table_dump(pool: &SqlitePool) -> BTreeMap<String, Vec<Map<String, Value>>> {
+        build_export(pool).await.unwrap().tables
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_preserves_every_row() {
+        let source = test_pool().await;
+        seed(&source).await;
+        let document = build_export(&source).await.unwrap();
+        assert!(!document.tables["xp_ledger"].is_empty());
+        assert!(!document.tables["activity_events"].is_empty());
+
+        // Through JSON text, as the file would be
+        let json = serde_json::to_string(&document).unwrap();
+        let parsed: VaultExport = serde_json::from_str(&json).unwrap();
+
+        let target = test_pool().await;
+        let summary = import_data(&target, &parsed, "replace").await.unwrap();
+        assert!(summary.tables.iter().all(|table| table.skipped == 0));
+
+        assert_eq!(table_dump(&target).await, document.tables);
+    }
+
+    #[tokio::test]
+    async fn test_merge_skips_existing_ids() {
+        let pool = test_pool().await;
+        seed(&pool).await;
+        let document = build_export(&pool).await.unwrap();
+
+        db::create_skill(&pool, "Guitar".to_string(), "Music".to_string(), None).await.unwrap();
+        let summary = import_data(&pool, &document, "merge").await.unwrap();
+        assert!(summary.tables.iter().all(|table| table.inserted == 0));
+
+        // Merging keeps rows the export does not have
+        assert_eq!(table_dump(&pool).await["skills"].len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_matches_rows_by_name() {
+        let source = test_pool().await;
+        seed(&source).await;
+        let document = build_export(&source).await.unwrap();
+
+        // Same names under different ids
+        let pool = test_pool().await;
+        let (skill_id, habit_id) = seed(&pool).await;
+        let summary = import_data(&pool, &document, "merge").await.unwrap();
+
+        let merged = |table: &str| summary.tables.iter().find(|t| t.table == table).unwrap().merged;
+        assert_eq!((merged("skills"), merged("habits")), (1, 1));
+
+        let dump = table_dump(&pool).await;
+        assert_eq!(dump["skills"].len(), 1);
+        assert!(dump["skill_progress"].iter().all(|row| row["skill_id"] == skill_id.as_str()));
+        assert!(dump["habit_completions"].iter().all(|row| row["habit_id"] == habit_id.as_str()));
+        assert_eq!(dump["habit_completions"].len(), 2);
+
+        let skill = db::get_skill(&pool, &skill_id).await.unwrap().unwrap();
+        assert!((skill.total_hours - 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_replace_clears_append_only_tables() {
+        let source = test_pool().await;
+        let document = build_export(&source).await.unwrap();
+
+        let pool = test_pool().await;
+        seed(&pool).await;
+        import_data(&pool, &document, "replace").await.unwrap();
+
+        let dump = table_dump(&pool).await;
+        assert!(dump.values().all(|rows| rows.is_empty()));
+
+        // The append-only guards are back in place afterwards
+        seed(&pool).await;
+        assert!(sqlx::query("DELETE FROM xp_ledger").execute(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_validates_document() {
+        let pool = test_pool().await;
+        let mut document = build_export(&pool).await.unwrap();
+
+        document.schema_version = EXPORT_SCHEMA_VERSION + 1;
+        assert!(import_data(&pool, &document, "merge").await.is_err());
+        document.schema_version = EXPORT_SCHEMA_VERSION;
+        assert!(import_data(&pool, &document, "overwrite").await.is_err());
+
+        let mut row = Map::new();
+        row.insert("id; DROP TABLE skills".to_string(), Value::from("x"));
+        document.tables.insert("skills".to_string(), vec![row]);
+        assert!(import_data(&pool, &document, "merge").await.is_err());
+
+        document.tables.insert("auth_user".to_string(), Vec::new());
+        assert!(import_data(&pool, &document, "merge").await.is_err());
+    }
+}
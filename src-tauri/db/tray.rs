@@ -0,0 +1,104 @@
+use crate::db::{ListFilter, TraySettings, TraySummary};
+use crate::error::Result;
+use super::operations::{done_in_period, get_habits_filtered, get_running_session, get_skill};
+use super::preferences::{get_preference, set_preference};
+use chrono::{DateTime, Local, Utc};
+use sqlx::SqlitePool;
+
+const TRAY_SETTINGS_KEY: &str = "tray_settings";
+
+pub async fn get_tray_settings(pool: &SqlitePool) -> Result<TraySettings> {
+    Ok(get_preference(pool, TRAY_SETTINGS_KEY).await?.unwrap_or_default())
+}
+
+pub async fn update_tray_settings(pool: &SqlitePool, settings: TraySettings) -> Result<TraySettings> {
+    set_preference(pool, TRAY_SETTINGS_KEY, &settings).await?;
+    Ok(settings)
+}
+
+/// Collects the tray menu contents as of `now`
+pub async fn get_tray_summary(pool: &SqlitePool, now: DateTime<Utc>) -> Result<TraySummary> {
+    let today = now.with_timezone(&Local).date_naive();
+
+    let mut due_habits = Vec::new();
+    for habit in get_habits_filtered(pool, &ListFilter::default()).await? {
+        if habit.polarity == "positive" && !done_in_period(&habit, today)? {
+            due_habits.push(habit);
+        }
+    }
+
+    let running_session = get_running_session(pool).await?;
+    let skill_id = match &running_session {
+        Some(session) => Some(session.skill_id.clone()),
+        None => sqlx::query_scalar::<_, String>(
+            "SELECT p.skill_id FROM practice_sessions p
+             JOIN skills s ON s.id = p.skill_id
+             WHERE p.source_type = 'timer' AND s.is_archived = 0 AND s.deleted_at IS NULL
+             ORDER BY p.started_at DESC LIMIT 1"
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?,
+    };
+    let timer_skill = match skill_id {
+        Some(skill_id) => get_skill(pool, &skill_id).await?,
+        None => None,
+    };
+
+    Ok(TraySummary {
+        due_habits,
+        running_session,
+        timer_skill,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[tokio::test]
+    async fn test_tray_summary() {
+        let pool = db::test_pool().await;
+        let read = db::create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::create_habit(&pool, "Walk".to_string(), "weekly".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        db::create_habit(&pool, "Smoking".to_string(), "daily".to_string(), "negative".to_string(), None, None)
+            .await
+            .unwrap();
+        let skill = db::create_skill(&pool, "Piano".to_string(), "Music".to_string(), None).await.unwrap();
+
+        let summary = get_tray_summary(&pool, Utc::now()).await.unwrap();
+        assert_eq!(summary.due_habits.len(), 2);
+        assert!(summary.running_session.is_none());
+        assert!(summary.timer_skill.is_none());
+
+        db::log_habit_completion(&pool, &read.id).await.unwrap();
+        db::start_session(&pool, &skill.id, None).await.unwrap();
+        let summary = get_tray_summary(&pool, Utc::now()).await.unwrap();
+        assert_eq!(summary.due_habits.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), vec!["Walk"]);
+        assert!(summary.running_session.is_some());
+        assert_eq!(summary.timer_skill.unwrap().id, skill.id);
+
+        // With the timer stopped, the last practiced skill is offered again;
+        // hours logged by hand are not practice with the timer
+        db::stop_session(&pool, None, None).await.unwrap();
+        let guitar = db::create_skill(&pool, "Guitar".to_string(), "Music".to_string(), None).await.unwrap();
+        db::record_skill_progress(&pool, &guitar.id, 10, 1.0).await.unwrap();
+        let summary = get_tray_summary(&pool, Utc::now()).await.unwrap();
+        assert!(summary.running_session.is_none());
+        assert_eq!(summary.timer_skill.unwrap().id, skill.id);
+    }
+
+    #[tokio::test]
+    async fn test_tray_settings_default_to_minimize() {
+        let pool = db::test_pool().await;
+        assert!(get_tray_settings(&pool).await.unwrap().minimize_to_tray);
+
+        update_tray_settings(&pool, TraySettings { minimize_to_tray: false }).await.unwrap();
+        assert!(!get_tray_settings(&pool).await.unwrap().minimize_to_tray);
+    }
+}
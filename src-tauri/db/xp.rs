@@ -0,0 +1,351 @@
+use crate::db::{LevelCurve, LevelProgress, ProfileLevel, Skill, SkillLevel, SkillLevelConfig, XpConfig, XpEvent};
+use super::operations::get_skill;
+use crate::error::Result;
+use super::preferences::{get_preference, set_preference};
+use sqlx::{Executor, Sqlite, SqliteConnection, SqlitePool};
+
+const XP_CONFIG_KEY: &str = "xp_config";
+const SKILL_LEVEL_CONFIG_KEY: &str = "skill_level_config";
+
+/// Safety net for curves that grow too slowly to ever stop
+const MAX_LEVEL: i32 = 1000;
+
+// ============ XP CONFIG OPERATIONS ============
+
+pub async fn get_xp_config<'e, E>(executor: E) -> Result<XpConfig>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(get_preference(executor, XP_CONFIG_KEY).await?.unwrap_or_default())
+}
+
+pub async fn update_xp_config(pool: &SqlitePool, config: XpConfig) -> Result<XpConfig> {
+    validate_curve(&config.level_curve)?;
+    if config.xp_per_practice_hour < 0 || config.xp_per_habit_completion < 0 || config.xp_per_milestone < 0 {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "XP rewards cannot be negative".to_string(),
+        ));
+    }
+    if config.streak_multipliers.iter().any(|m| m.min_streak < 1 || m.multiplier < 1.0) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Streak multipliers need a streak of at least 1 and a multiplier of at least 1.0".to_string(),
+        ));
+    }
+
+    set_preference(pool, XP_CONFIG_KEY, &config).await?;
+    Ok(config)
+}
+
+pub async fn get_skill_level_config<'e, E>(executor: E) -> Result<SkillLevelConfig>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(get_preference(executor, SKILL_LEVEL_CONFIG_KEY).await?.unwrap_or_default())
+}
+
+pub async fn update_skill_level_config(pool: &SqlitePool, config: SkillLevelConfig) -> Result<SkillLevelConfig> {
+    validate_curve(&config.curve)?;
+    if !["hours", "xp"].contains(&config.basis.as_str()) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill levels must be based on 'hours' or 'xp'".to_string(),
+        ));
+    }
+    if !(1..=MAX_LEVEL).contains(&config.max_level) {
+        return Err(crate::error::SkillEdError::ValidationError(
+            format!("Maximum skill level must be between 1 and {}", MAX_LEVEL),
+        ));
+    }
+
+    set_preference(pool, SKILL_LEVEL_CONFIG_KEY, &config).await?;
+    Ok(config)
+}
+
+/// Rejects curves whose thresholds would not increase from level to level
+pub fn validate_curve(curve: &LevelCurve) -> Result<()> {
+    let valid = match *curve {
+        LevelCurve::Linear { per_level } => per_level > 0.0,
+        LevelCurve::Power { base, exponent } => base > 0.0 && exponent > 0.0,
+        LevelCurve::Exponential { base, growth } => base > 0.0 && growth >= 1.0,
+    };
+
+    if !valid {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Level curve parameters must be positive and increasing".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+// ============ XP LEDGER OPERATIONS ============
+
+/// Appends an event to the ledger. Events with no XP are not recorded.
+pub(super) async fn append_xp(conn: &mut SqliteConnection, event: &XpEvent) -> Result<()> {
+    if event.xp == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO xp_ledger (id, source_type, source_id, skill_id, base_xp, multiplier, xp, reason, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&event.id)
+    .bind(&event.source_type)
+    .bind(&event.source_id)
+    .bind(&event.skill_id)
+    .bind(event.base_xp)
+    .bind(event.multiplier)
+    .bind(event.xp)
+    .bind(&event.reason)
+    .bind(&event.created_at)
+    .execute(conn)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(())
+}
+
+pub async fn get_profile_level(pool: &SqlitePool) -> Result<ProfileLevel> {
+    let total_xp = sqlx::query_scalar::<_, i64>("SELECT COALESCE(SUM(xp), 0) FROM xp_ledger")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+    let config = get_xp_config(pool).await?;
+
+    Ok(ProfileLevel {
+        total_xp,
+        progress: level_progress(&config.level_curve, total_xp as f64, None),
+    })
+}
+
+/// Total XP earned on a skill, including XP from habits linked to it
+pub async fn get_skill_xp<'e, E>(executor: E, skill_id: &str) -> Result<i64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let xp = sqlx::query_scalar::<_, i64>("SELECT COALESCE(SUM(xp), 0) FROM xp_ledger WHERE skill_id = ?")
+        .bind(skill_id)
+        .fetch_one(executor)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(xp)
+}
+
+/// Ledger entries, newest first, optionally narrowed to one skill
+pub async fn get_xp_history(
+    pool: &SqlitePool,
+    skill_id: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<XpEvent>> {
+    let events = sqlx::query_as::<_, XpEvent>(
+        "SELECT id, source_type, source_id, skill_id, base_xp, multiplier, xp, reason, created_at
+         FROM xp_ledger WHERE (?1 IS NULL OR skill_id = ?1)
+         ORDER BY created_at DESC, id ASC LIMIT ?2 OFFSET ?3"
+    )
+    .bind(skill_id)
+    .bind(limit.max(0))
+    .bind(offset.max(0))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    Ok(events)
+}
+
+// ============ SKILL LEVEL OPERATIONS ============
+
+pub async fn get_skill_level(pool: &SqlitePool, skill_id: &str) -> Result<SkillLevel> {
+    let skill = get_skill(pool, skill_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Skill {}", skill_id)))?;
+    let total_xp = get_skill_xp(pool, skill_id).await?;
+    let config = get_skill_level_config(pool).await?;
+    let xp_config = get_xp_config(pool).await?;
+
+    Ok(skill_level(&skill, total_xp, &config, xp_config.xp_per_practice_hour))
+}
+
+/// Levels for every skill that is not in the trash
+pub async fn get_skill_levels(pool: &SqlitePool) -> Result<Vec<SkillLevel>> {
+    let query_str = format!(
+        "SELECT {} FROM skills WHERE deleted_at IS NULL ORDER BY sort_order ASC, created_at DESC",
+        super::operations::SKILL_COLUMNS
+    );
+    let skills = sqlx::query_as::<_, Skill>(&query_str)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?;
+
+    let xp_by_skill: std::collections::HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT skill_id, SUM(xp) FROM xp_ledger WHERE skill_id IS NOT NULL GROUP BY skill_id"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| crate::error::SkillEdError::DatabaseError(e.to_string()))?
+    .into_iter()
+    .collect();
+
+    let config = get_skill_level_config(pool).await?;
+    let xp_config = get_xp_config(pool).await?;
+
+    Ok(skills
+        .iter()
+        .map(|skill| {
+            let total_xp = xp_by_skill.get(&skill.id).copied().unwrap_or(0);
+            skill_level(skill, total_xp, &config, xp_config.xp_per_practice_hour)
+        })
+        .collect())
+}
+
+/// Computes a skill's level on the configured curve. On an XP curve, hours to the
+/// next level are estimated from the XP earned per practice hour.
+fn skill_level(skill: &Skill, total_xp: i64, config: &SkillLevelConfig, xp_per_practice_hour: i64) -> SkillLevel {
+    let (progress, hours_to_next_level) = if config.basis == "xp" {
+        let progress = level_progress(&config.curve, total_xp as f64, Some(config.max_level));
+        let hours = progress
+            .to_next_level
+            .filter(|_| xp_per_practice_hour > 0)
+            .map(|xp| xp / xp_per_practice_hour as f64);
+        (progress, hours)
+    } else {
+        let progress = level_progress(&config.curve, skill.total_hours, Some(config.max_level));
+        let hours = progress.to_next_level;
+        (progress, hours)
+    };
+
+    let level = if skill.level_mode == "computed" {
+        progress.level
+    } else {
+        skill.proficiency_level
+    };
+
+    SkillLevel {
+        skill_id: skill.id.clone(),
+        level_mode: skill.level_mode.clone(),
+        level,
+        manual_level: skill.proficiency_level,
+        computed_level: progress.level,
+        total_hours: skill.total_hours,
+        total_xp,
+        hours_to_next_level,
+        progress_percent: progress.progress_percent,
+    }
+}
+
+// ============ LEVEL CURVES ============
+
+/// Places `points` on `curve`, stopping at `max_level` when one is given
+pub fn level_progress(curve: &LevelCurve, points: f64, max_level: Option<i32>) -> LevelProgress {
+    let max_level = max_level.unwrap_or(MAX_LEVEL).clamp(1, MAX_LEVEL);
+    let points = points.max(0.0);
+
+    let mut level = 1;
+    while level < max_level && curve.threshold(level + 1) <= points {
+        level += 1;
+    }
+
+    let level_start = curve.threshold(level);
+    let next_level_at = (level < max_level).then(|| curve.threshold(level + 1));
+    let progress_percent = match next_level_at {
+        Some(next) if next > level_start => ((points - level_start) / (next - level_start) * 100.0).clamp(0.0, 100.0),
+        _ => 100.0,
+    };
+
+    LevelProgress {
+        level,
+        points,
+        level_start,
+        next_level_at,
+        to_next_level: next_level_at.map(|next| next - points),
+        progress_percent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StreakMultiplier;
+
+    #[test]
+    fn test_level_progress_on_power_curve() {
+        let curve = LevelCurve::Power { base: 100.0, exponent: 2.0 };
+
+        let start = level_progress(&curve, 0.0, None);
+        assert_eq!(start.level, 1);
+        assert_eq!(start.next_level_at, Some(100.0));
+
+        // Level 3 starts at 400 XP, level 4 at 900
+        let mid = level_progress(&curve, 650.0, None);
+        assert_eq!(mid.level, 3);
+        assert_eq!(mid.to_next_level, Some(250.0));
+        assert!((mid.progress_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_level_progress_stops_at_max_level() {
+        let curve = LevelCurve::Linear { per_level: 10.0 };
+        let capped = level_progress(&curve, 5000.0, Some(100));
+
+        assert_eq!(capped.level, 100);
+        assert_eq!(capped.next_level_at, None);
+        assert_eq!(capped.progress_percent, 100.0);
+    }
+
+    #[test]
+    fn test_exponential_curve_thresholds() {
+        let curve = LevelCurve::Exponential { base: 100.0, growth: 2.0 };
+        assert_eq!(curve.threshold(1), 0.0);
+        assert_eq!(curve.threshold(2), 100.0);
+        assert_eq!(curve.threshold(4), 700.0);
+        assert!(validate_curve(&LevelCurve::Exponential { base: 100.0, growth: 0.5 }).is_err());
+    }
+
+    #[test]
+    fn test_mastery_curve_tops_out_at_ten_thousand_hours() {
+        let config = SkillLevelConfig::mastery();
+        let mut skill = Skill::new("Piano".to_string(), "Music".to_string());
+        skill.proficiency_level = 40;
+
+        skill.total_hours = 9_999.0;
+        let almost = skill_level(&skill, 0, &config, 60);
+        assert_eq!(almost.computed_level, 99);
+        assert!((almost.hours_to_next_level.unwrap() - 1.0).abs() < 1e-6);
+        // Manual mode keeps the hand-entered value
+        assert_eq!(almost.level, 40);
+
+        skill.total_hours = 10_000.0;
+        skill.level_mode = "computed".to_string();
+        let mastered = skill_level(&skill, 0, &config, 60);
+        assert_eq!(mastered.level, 100);
+        assert_eq!(mastered.hours_to_next_level, None);
+    }
+
+    #[test]
+    fn test_xp_curve_estimates_hours_from_practice_rate() {
+        let config = SkillLevelConfig::rpg();
+        let skill = Skill::new("Chess".to_string(), "Games".to_string());
+
+        // Level 2 costs 60 XP; with 30 XP earned and 60 XP per hour, half an hour remains
+        let level = skill_level(&skill, 30, &config, 60);
+        assert_eq!(level.computed_level, 1);
+        assert!((level.hours_to_next_level.unwrap() - 0.5).abs() < 1e-9);
+        assert!((level.progress_percent - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_streak_multiplier_picks_highest_reached_tier() {
+        let config = XpConfig {
+            streak_multipliers: vec![
+                StreakMultiplier { min_streak: 7, multiplier: 1.25 },
+                StreakMultiplier { min_streak: 3, multiplier: 1.1 },
+            ],
+            ..XpConfig::default()
+        };
+
+        assert_eq!(config.streak_multiplier(2), 1.0);
+        assert_eq!(config.streak_multiplier(5), 1.1);
+        assert_eq!(config.streak_multiplier(10), 1.25);
+    }
+}
@@ -1,3 +1,5 @@
+use serde::Serialize;
+use serde_json::{json, Value};
 use thiserror::Error;
 use tauri::InvokeError;
 
@@ -14,12 +16,21 @@ pub enum SkillEdError {
     #[error("Authentication error: {0}")]
     AuthError(String),
 
+    #[error("The vault is locked")]
+    VaultLocked,
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    #[error("Validation error: {}", format_field_errors(.0))]
+    ValidationFailed(Vec<FieldError>),
+
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("A {entity} named '{name}' already exists")]
+    DuplicateName { entity: String, name: String },
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -27,8 +38,118 @@ pub enum SkillEdError {
     SerializationError(#[from] serde_json::Error),
 }
 
+/// A problem with one input of a request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// What the frontend receives for a failed command. `code` is stable and meant
+/// for matching; `message` is for display and may change.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorResponse {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl SkillEdError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SkillEdError::DatabaseError(_) => "DATABASE_ERROR",
+            SkillEdError::CryptoError(_) => "CRYPTO_ERROR",
+            SkillEdError::AuthError(_) => "AUTH_FAILED",
+            SkillEdError::VaultLocked => "VAULT_LOCKED",
+            SkillEdError::ValidationError(_) | SkillEdError::ValidationFailed(_) => "VALIDATION_FAILED",
+            SkillEdError::NotFound(_) => "NOT_FOUND",
+            SkillEdError::DuplicateName { .. } => "DUPLICATE_NAME",
+            SkillEdError::IoError(_) => "IO_ERROR",
+            SkillEdError::SerializationError(_) => "SERIALIZATION_ERROR",
+        }
+    }
+
+    pub fn to_response(&self) -> ErrorResponse {
+        let (field, details) = match self {
+            // `field` names the input when there is exactly one; `details.errors` lists them all
+            SkillEdError::ValidationFailed(errors) => {
+                let field = match errors.as_slice() {
+                    [only] => Some(only.field.clone()),
+                    _ => None,
+                };
+                (field, Some(json!({ "errors": errors })))
+            }
+            SkillEdError::DuplicateName { entity, name } => {
+                (Some("name".to_string()), Some(json!({ "entity": entity, "name": name })))
+            }
+            _ => (None, None),
+        };
+
+        ErrorResponse {
+            code: self.code(),
+            message: self.to_string(),
+            field,
+            details,
+        }
+    }
+
+    /// Maps a failed insert or update of a named row, turning a clash on the
+    /// table's unique `name` column into `DuplicateName`
+    pub fn from_name_write(err: sqlx::Error, table: &str, entity: &str, name: &str) -> Self {
+        if let sqlx::Error::Database(db_err) = &err {
+            if db_err.is_unique_violation() && db_err.message().contains(&format!("{}.name", table)) {
+                return SkillEdError::DuplicateName {
+                    entity: entity.to_string(),
+                    name: name.to_string(),
+                };
+            }
+        }
+        SkillEdError::DatabaseError(err.to_string())
+    }
+}
+
 impl From<SkillEdError> for InvokeError {
     fn from(err: SkillEdError) -> Self {
-        InvokeError::from(err.to_string())
+        InvokeError::from(err.to_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_response() {
+        let response = SkillEdError::DuplicateName {
+            entity: "skill".to_string(),
+            name: "Piano".to_string(),
+        }
+        .to_response();
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "code": "DUPLICATE_NAME",
+                "message": "A skill named 'Piano' already exists",
+                "field": "name",
+                "details": { "entity": "skill", "name": "Piano" },
+            })
+        );
+
+        let response = SkillEdError::NotFound("Skill 1".to_string()).to_response();
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({ "code": "NOT_FOUND", "message": "Not found: Skill 1" })
+        );
     }
 }
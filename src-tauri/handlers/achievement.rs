@@ -0,0 +1,9 @@
+use crate::db::{self, Achievement};
+use crate::error::Result;
+
+/// Lists every achievement; earned ones carry their `unlocked_at` timestamp
+#[tauri::command]
+pub async fn get_achievements() -> Result<Vec<Achievement>> {
+    let pool = db::get_db_pool()?;
+    db::get_achievements(&pool).await
+}
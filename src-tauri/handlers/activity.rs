@@ -0,0 +1,29 @@
+use crate::db::{self, ActivityEvent, ActivityFilter, ActivityHeatmap, HeatmapFilter};
+use crate::error::Result;
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Pages through the activity log, newest first
+#[tauri::command]
+pub async fn get_activity_feed(
+    filter: Option<ActivityFilter>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<ActivityEvent>> {
+    let pool = db::get_db_pool()?;
+    db::get_activity_feed(
+        &pool,
+        &filter.unwrap_or_default(),
+        limit.unwrap_or(DEFAULT_PAGE_SIZE),
+        offset.unwrap_or(0),
+    )
+    .await
+}
+
+/// Per-day activity counts and intensity buckets for the calendar heatmap.
+/// `from` and `to` are inclusive local dates (YYYY-MM-DD).
+#[tauri::command]
+pub async fn get_activity_heatmap(from: String, to: String, filter: Option<HeatmapFilter>) -> Result<ActivityHeatmap> {
+    let pool = db::get_db_pool()?;
+    db::get_activity_heatmap(&pool, &from, &to, &filter.unwrap_or_default()).await
+}
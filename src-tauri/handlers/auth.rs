@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::error::Result;
 use crate::crypto;
+use crate::state::AppState;
 use rand::Rng;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,7 +15,7 @@ pub struct VerifyPasswordRequest {
 }
 
 #[tauri::command]
-pub fn set_master_password(request: SetPasswordRequest) -> Result<()> {
+pub fn set_master_password(request: SetPasswordRequest, state: tauri::State<'_, AppState>) -> Result<()> {
     // Validate password strength
     if request.password.len() < 12 {
         return Err(crate::error::SkillEdError::ValidationError(
@@ -39,12 +40,15 @@ pub fn set_master_password(request: SetPasswordRequest) -> Result<()> {
     let password_hash = crypto::hash_password(&request.password)?;
 
     // Derive master key from password
-    let _master_key = crypto::derive_master_key(&request.password, &salt)?;
+    let master_key = crypto::derive_master_key(&request.password, &salt)?;
 
     // TODO: Store in database
     // - Store password_hash and salt hex-encoded
     // - This is a zero-knowledge operation - salt and hash never leave encrypted storage
 
+    // The vault stays unlocked for this session
+    state.set_master_key(master_key);
+
     Ok(())
 }
 
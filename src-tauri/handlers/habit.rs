@@ -1,60 +1,261 @@
 use serde::{Deserialize, Serialize};
+use crate::db::{self, CleanStreakStats, Habit, HabitSkillLink, HabitUpdate, ListFilter, RelapseTrendPoint};
 use crate::error::Result;
+use super::validation::{Validate, Validator, MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH};
+
+const FREQUENCIES: [&str; 3] = ["daily", "weekly", "monthly"];
+const POLARITIES: [&str; 2] = ["positive", "negative"];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateHabitRequest {
     pub name: String,
     pub frequency: String, // "daily", "weekly", "monthly"
+    pub polarity: Option<String>, // "positive" (default), "negative"
+    pub category: Option<String>,
     pub description: Option<String>,
 }
 
+impl Validate for CreateHabitRequest {
+    fn validate(self) -> Result<Self> {
+        let mut v = Validator::new();
+        let request = CreateHabitRequest {
+            name: v.name("name", &self.name, MAX_NAME_LENGTH),
+            frequency: v.choice("frequency", &self.frequency, &FREQUENCIES),
+            polarity: Some(v.choice("polarity", self.polarity.as_deref().unwrap_or("positive"), &POLARITIES)),
+            category: self
+                .category
+                .filter(|category| !category.trim().is_empty())
+                .map(|category| v.name("category", &category, MAX_NAME_LENGTH)),
+            description: v.text("description", self.description, MAX_DESCRIPTION_LENGTH),
+        };
+        v.finish()?;
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateHabitRequest {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<Option<String>>, // absent keeps it; a blank value clears it
+    pub frequency: Option<String>,
+    pub category: Option<String>,
+}
+
+impl Validate for UpdateHabitRequest {
+    fn validate(self) -> Result<Self> {
+        let mut v = Validator::new();
+        let request = UpdateHabitRequest {
+            id: v.id("id", &self.id),
+            name: v.optional_name("name", self.name, MAX_NAME_LENGTH),
+            description: self
+                .description
+                .map(|description| v.text("description", description, MAX_DESCRIPTION_LENGTH)),
+            frequency: self.frequency.map(|frequency| v.choice("frequency", &frequency, &FREQUENCIES)),
+            category: v.optional_name("category", self.category, MAX_NAME_LENGTH),
+        };
+        v.finish()?;
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkHabitRequest {
+    pub habit_id: String,
+    pub skill_id: String,
+    pub minutes_per_completion: i32,
+    pub xp_per_completion: i32,
+}
+
+#[tauri::command]
+pub async fn create_habit(request: CreateHabitRequest) -> Result<String> {
+    let request = request.validate()?;
+    let polarity = request.polarity.unwrap_or_else(|| "positive".to_string());
+
+    let pool = db::get_db_pool()?;
+    let habit = db::create_habit(
+        &pool,
+        request.name,
+        request.frequency,
+        polarity,
+        request.category,
+        request.description,
+    )
+    .await?;
+    Ok(habit.id)
+}
+
 #[tauri::command]
-pub fn create_habit(request: CreateHabitRequest) -> Result<String> {
-    // Validate inputs
-    if request.name.is_empty() {
+pub async fn update_habit(request: UpdateHabitRequest) -> Result<Habit> {
+    let request = request.validate()?;
+
+    let update = HabitUpdate {
+        name: request.name,
+        description: request.description,
+        frequency: request.frequency,
+        category: request.category,
+    };
+
+    let pool = db::get_db_pool()?;
+    db::update_habit(&pool, &request.id, update).await
+}
+
+#[tauri::command]
+pub async fn log_habit_completion(habit_id: String) -> Result<()> {
+    if habit_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
-            "Habit name cannot be empty".to_string(),
+            "Habit ID cannot be empty".to_string(),
         ));
     }
 
-    let valid_frequencies = vec!["daily", "weekly", "monthly"];
-    if !valid_frequencies.contains(&request.frequency.as_str()) {
+    // Negative habits record a relapse and reset their clean streak; completions
+    // of positive habits feed the skills the habit is linked to
+    let pool = db::get_db_pool()?;
+    db::log_habit_completion(&pool, &habit_id).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn link_habit_to_skill(request: LinkHabitRequest) -> Result<HabitSkillLink> {
+    if request.habit_id.is_empty() || request.skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Habit ID and skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::link_habit_to_skill(
+        &pool,
+        &request.habit_id,
+        &request.skill_id,
+        request.minutes_per_completion,
+        request.xp_per_completion,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn unlink_habit_from_skill(habit_id: String, skill_id: String) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::unlink_habit_from_skill(&pool, &habit_id, &skill_id).await
+}
+
+#[tauri::command]
+pub async fn get_habit_skill_links(habit_id: String) -> Result<Vec<HabitSkillLink>> {
+    let pool = db::get_db_pool()?;
+    db::get_habit_skill_links(&pool, &habit_id).await
+}
+
+#[tauri::command]
+pub async fn get_habit_streak(habit_id: String) -> Result<i32> {
+    if habit_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
-            "Invalid frequency. Must be 'daily', 'weekly', or 'monthly'".to_string(),
+            "Habit ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database insertion
-    let habit_id = uuid::Uuid::new_v4().to_string();
-    Ok(habit_id)
+    let pool = db::get_db_pool()?;
+    let habit = db::get_habit(&pool, &habit_id)
+        .await?
+        .ok_or_else(|| crate::error::SkillEdError::NotFound(format!("Habit {}", habit_id)))?;
+    db::current_streak(&habit)
 }
 
 #[tauri::command]
-pub fn log_habit_completion(habit_id: String) -> Result<()> {
+pub async fn get_clean_streak_stats(habit_id: String) -> Result<CleanStreakStats> {
     if habit_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
             "Habit ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database insertion and streak update
-    Ok(())
+    let pool = db::get_db_pool()?;
+    db::get_clean_streak_stats(&pool, &habit_id).await
+}
+
+#[tauri::command]
+pub async fn get_relapse_trend(habit_id: String, period: String) -> Result<Vec<RelapseTrendPoint>> {
+    if habit_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Habit ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::get_relapse_trend(&pool, &habit_id, &period).await
+}
+
+/// Lists active habits, optionally narrowed to categories and tags; archived and
+/// trashed habits have their own commands
+#[tauri::command]
+pub async fn get_habits(filter: Option<ListFilter>) -> Result<Vec<Habit>> {
+    let pool = db::get_db_pool()?;
+    db::get_habits_filtered(&pool, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn get_archived_habits() -> Result<Vec<Habit>> {
+    let pool = db::get_db_pool()?;
+    db::get_archived_habits(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_trashed_habits() -> Result<Vec<Habit>> {
+    let pool = db::get_db_pool()?;
+    db::get_trashed_habits(&pool).await
+}
+
+/// Moves a habit to the trash, from where it can be restored for a limited time
+#[tauri::command]
+pub async fn delete_habit(habit_id: String) -> Result<()> {
+    if habit_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Habit ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::delete_habit(&pool, &habit_id).await
+}
+
+#[tauri::command]
+pub async fn restore_habit(habit_id: String) -> Result<()> {
+    if habit_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Habit ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::restore_habit(&pool, &habit_id).await
+}
+
+#[tauri::command]
+pub async fn archive_habit(habit_id: String) -> Result<()> {
+    if habit_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Habit ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::set_habit_archived(&pool, &habit_id, true).await
 }
 
 #[tauri::command]
-pub fn get_habit_streak(habit_id: String) -> Result<i32> {
+pub async fn unarchive_habit(habit_id: String) -> Result<()> {
     if habit_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
             "Habit ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement streak calculation
-    Ok(0)
+    let pool = db::get_db_pool()?;
+    db::set_habit_archived(&pool, &habit_id, false).await
 }
 
 #[tauri::command]
-pub fn get_habits() -> Result<Vec<String>> {
-    // TODO: Implement database query
-    Ok(vec![])
+pub async fn reorder_habits(habit_ids: Vec<String>) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::reorder_habits(&pool, &habit_ids).await
 }
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use crate::db::{self, Milestone};
+use crate::error::Result;
+use super::validation::{Validate, Validator, MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateMilestoneRequest {
+    pub title: String,
+    pub target_date: String, // YYYY-MM-DD or RFC 3339
+    pub skill_id: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Validate for CreateMilestoneRequest {
+    fn validate(self) -> Result<Self> {
+        let mut v = Validator::new();
+        let request = CreateMilestoneRequest {
+            title: v.name("title", &self.title, MAX_NAME_LENGTH),
+            target_date: v.date("target_date", &self.target_date),
+            skill_id: self.skill_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()),
+            description: v.text("description", self.description, MAX_DESCRIPTION_LENGTH),
+        };
+        v.finish()?;
+        Ok(request)
+    }
+}
+
+#[tauri::command]
+pub async fn create_milestone(request: CreateMilestoneRequest) -> Result<Milestone> {
+    let request = request.validate()?;
+
+    let pool = db::get_db_pool()?;
+    db::create_milestone(&pool, request.title, request.target_date, request.skill_id, request.description).await
+}
+
+#[tauri::command]
+pub async fn get_milestones() -> Result<Vec<Milestone>> {
+    let pool = db::get_db_pool()?;
+    db::get_all_milestones(&pool).await
+}
+
+#[tauri::command]
+pub async fn complete_milestone(milestone_id: String) -> Result<()> {
+    if milestone_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Milestone ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::complete_milestone(&pool, &milestone_id).await
+}
+
+#[tauri::command]
+pub async fn delete_milestone(milestone_id: String) -> Result<()> {
+    if milestone_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Milestone ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::delete_milestone(&pool, &milestone_id).await
+}
@@ -1,3 +1,15 @@
 pub mod skill;
 pub mod habit;
 pub mod auth;
+pub mod session;
+pub mod taxonomy;
+pub mod xp;
+pub mod achievement;
+pub mod activity;
+pub mod stats;
+pub mod review;
+pub mod transfer;
+pub mod reminder;
+pub mod tray;
+pub mod milestone;
+pub mod validation;
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use crate::db::{self, Reminder, ReminderUpdate};
+use crate::error::Result;
+use chrono::Utc;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReminderRequest {
+    pub entity_type: String, // "habit", "milestone"
+    pub entity_id: String,
+    pub time_of_day: String, // HH:MM, local time
+    pub weekdays: Option<Vec<u32>>, // 0 = Monday; every day if omitted
+    pub only_if_not_done: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn create_reminder(request: CreateReminderRequest) -> Result<Reminder> {
+    if request.entity_id.trim().is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Reminder target ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    let mut reminder = Reminder::new(request.entity_type, request.entity_id, request.time_of_day);
+    if let Some(weekdays) = request.weekdays {
+        reminder.weekdays = weekdays;
+    }
+    if let Some(only_if_not_done) = request.only_if_not_done {
+        reminder.only_if_not_done = only_if_not_done;
+    }
+
+    db::create_reminder(&pool, reminder).await
+}
+
+/// Reminders for one habit or milestone, or every reminder when no target is given
+#[tauri::command]
+pub async fn get_reminders(entity_type: Option<String>, entity_id: Option<String>) -> Result<Vec<Reminder>> {
+    let pool = db::get_db_pool()?;
+    db::get_reminders(&pool, entity_type.as_deref(), entity_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn update_reminder(reminder_id: String, update: ReminderUpdate) -> Result<Reminder> {
+    if reminder_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Reminder ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::update_reminder(&pool, &reminder_id, update).await
+}
+
+#[tauri::command]
+pub async fn delete_reminder(reminder_id: String) -> Result<()> {
+    if reminder_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Reminder ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::delete_reminder(&pool, &reminder_id).await
+}
+
+/// Postpones a reminder by `minutes` (1 to 1440) from now
+#[tauri::command]
+pub async fn snooze_reminder(reminder_id: String, minutes: i64) -> Result<Reminder> {
+    if reminder_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Reminder ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::snooze_reminder(&pool, &reminder_id, minutes, Utc::now()).await
+}
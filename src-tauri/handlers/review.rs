@@ -0,0 +1,23 @@
+use crate::db::{self, Export, ReviewDocument};
+use crate::error::Result;
+
+/// Builds the weekly or monthly review containing `date` (YYYY-MM-DD, default
+/// today), rendered as Markdown and HTML. With `save`, both files are written to
+/// the exports directory.
+#[tauri::command]
+pub async fn generate_review(period: String, date: Option<String>, save: Option<bool>) -> Result<ReviewDocument> {
+    let pool = db::get_db_pool()?;
+    let data_dir = if save.unwrap_or(false) {
+        Some(db::get_app_data_dir()?)
+    } else {
+        None
+    };
+
+    db::generate_review(&pool, &period, date.as_deref(), data_dir.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn get_exports() -> Result<Vec<Export>> {
+    let pool = db::get_db_pool()?;
+    db::get_exports(&pool).await
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use crate::db::{self, PracticeSession};
+use crate::error::Result;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StartSessionRequest {
+    pub skill_id: String,
+    pub tags: Option<String>, // comma-separated
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StopSessionRequest {
+    pub focus_rating: Option<i32>, // 1-5
+    pub notes: Option<String>,
+}
+
+#[tauri::command]
+pub async fn start_session(request: StartSessionRequest) -> Result<PracticeSession> {
+    if request.skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::start_session(&pool, &request.skill_id, request.tags).await
+}
+
+#[tauri::command]
+pub async fn stop_session(request: StopSessionRequest) -> Result<PracticeSession> {
+    let pool = db::get_db_pool()?;
+    db::stop_session(&pool, request.focus_rating, request.notes).await
+}
+
+/// Returns the running timer so the UI can resume it after a restart
+#[tauri::command]
+pub async fn get_running_session() -> Result<Option<PracticeSession>> {
+    let pool = db::get_db_pool()?;
+    db::get_running_session(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_practice_sessions(skill_id: String) -> Result<Vec<PracticeSession>> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::get_practice_sessions(&pool, &skill_id).await
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use crate::db::{self, ListFilter, Skill, SkillLevel, SkillPrerequisite, SkillProgress, SkillTreeNode, SkillUpdate};
 use crate::error::Result;
+use super::validation::{Validate, Validator, MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSkillRequest {
@@ -8,74 +10,261 @@ pub struct CreateSkillRequest {
     pub description: Option<String>,
 }
 
+impl Validate for CreateSkillRequest {
+    fn validate(self) -> Result<Self> {
+        let mut v = Validator::new();
+        let request = CreateSkillRequest {
+            name: v.name("name", &self.name, MAX_NAME_LENGTH),
+            category: v.name("category", &self.category, MAX_NAME_LENGTH),
+            description: v.text("description", self.description, MAX_DESCRIPTION_LENGTH),
+        };
+        v.finish()?;
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddPrerequisiteRequest {
+    pub skill_id: String,
+    pub prerequisite_id: String,
+    pub min_proficiency: Option<i32>, // defaults to 50
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateSkillRequest {
     pub id: String,
     pub name: Option<String>,
     pub category: Option<String>,
-    pub description: Option<String>,
+    pub description: Option<Option<String>>, // absent keeps it; a blank value clears it
     pub proficiency_level: Option<i32>,
     pub total_hours: Option<f64>,
 }
 
+impl Validate for UpdateSkillRequest {
+    fn validate(self) -> Result<Self> {
+        let mut v = Validator::new();
+        if let Some(level) = self.proficiency_level {
+            v.range("proficiency_level", level, 0, 100);
+        }
+        if let Some(hours) = self.total_hours {
+            v.non_negative("total_hours", hours);
+        }
+        let request = UpdateSkillRequest {
+            id: v.id("id", &self.id),
+            name: v.optional_name("name", self.name, MAX_NAME_LENGTH),
+            category: v.optional_name("category", self.category, MAX_NAME_LENGTH),
+            description: self
+                .description
+                .map(|description| v.text("description", description, MAX_DESCRIPTION_LENGTH)),
+            proficiency_level: self.proficiency_level,
+            total_hours: self.total_hours,
+        };
+        v.finish()?;
+        Ok(request)
+    }
+}
+
+#[tauri::command]
+pub async fn create_skill(request: CreateSkillRequest) -> Result<String> {
+    let request = request.validate()?;
+
+    let pool = db::get_db_pool()?;
+    let skill = db::create_skill(&pool, request.name, request.category, request.description).await?;
+    Ok(skill.id)
+}
+
+/// Lists active skills, optionally narrowed to categories and tags; archived and
+/// trashed skills have their own commands
+#[tauri::command]
+pub async fn get_skills(filter: Option<ListFilter>) -> Result<Vec<Skill>> {
+    let pool = db::get_db_pool()?;
+    db::get_skills_filtered(&pool, &filter.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn get_archived_skills() -> Result<Vec<Skill>> {
+    let pool = db::get_db_pool()?;
+    db::get_archived_skills(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_trashed_skills() -> Result<Vec<Skill>> {
+    let pool = db::get_db_pool()?;
+    db::get_trashed_skills(&pool).await
+}
+
+#[tauri::command]
+pub async fn update_skill(request: UpdateSkillRequest) -> Result<Skill> {
+    let request = request.validate()?;
+
+    let update = SkillUpdate {
+        name: request.name,
+        category: request.category,
+        description: request.description,
+        proficiency_level: request.proficiency_level,
+        total_hours: request.total_hours,
+    };
+
+    let pool = db::get_db_pool()?;
+    db::update_skill(&pool, &request.id, update).await
+}
+
+/// Moves a skill to the trash, from where it can be restored for a limited time
+#[tauri::command]
+pub async fn delete_skill(skill_id: String) -> Result<()> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::delete_skill(&pool, &skill_id).await
+}
+
+#[tauri::command]
+pub async fn restore_skill(skill_id: String) -> Result<()> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::restore_skill(&pool, &skill_id).await
+}
+
 #[tauri::command]
-pub fn create_skill(request: CreateSkillRequest) -> Result<String> {
-    // Validate inputs
-    if request.name.is_empty() {
+pub async fn archive_skill(skill_id: String) -> Result<()> {
+    if skill_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
-            "Skill name cannot be empty".to_string(),
+            "Skill ID cannot be empty".to_string(),
         ));
     }
 
-    if request.category.is_empty() {
+    let pool = db::get_db_pool()?;
+    db::set_skill_archived(&pool, &skill_id, true).await
+}
+
+#[tauri::command]
+pub async fn unarchive_skill(skill_id: String) -> Result<()> {
+    if skill_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
-            "Skill category cannot be empty".to_string(),
+            "Skill ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database insertion
-    let skill_id = uuid::Uuid::new_v4().to_string();
-    Ok(skill_id)
+    let pool = db::get_db_pool()?;
+    db::set_skill_archived(&pool, &skill_id, false).await
+}
+
+#[tauri::command]
+pub async fn reorder_skills(skill_ids: Vec<String>) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::reorder_skills(&pool, &skill_ids).await
 }
 
 #[tauri::command]
-pub fn get_skills() -> Result<Vec<String>> {
-    // TODO: Implement database query
-    Ok(vec![])
+pub async fn get_skill_level(skill_id: String) -> Result<SkillLevel> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::get_skill_level(&pool, &skill_id).await
+}
+
+#[tauri::command]
+pub async fn get_skill_levels() -> Result<Vec<SkillLevel>> {
+    let pool = db::get_db_pool()?;
+    db::get_skill_levels(&pool).await
+}
+
+/// `mode` is "manual" to use the entered proficiency, or "computed" to derive it from practice
+#[tauri::command]
+pub async fn set_skill_level_mode(skill_id: String, mode: String) -> Result<()> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::set_skill_level_mode(&pool, &skill_id, &mode).await
 }
 
 #[tauri::command]
-pub fn update_skill(request: UpdateSkillRequest) -> Result<()> {
-    if request.id.is_empty() {
+pub async fn get_skill_progress(skill_id: String) -> Result<Vec<SkillProgress>> {
+    if skill_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
             "Skill ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database update
-    Ok(())
+    let pool = db::get_db_pool()?;
+    db::get_skill_progress_history(&pool, &skill_id).await
 }
 
+/// Moves a skill under another one; pass no `parent_id` to make it a top-level skill
 #[tauri::command]
-pub fn delete_skill(skill_id: String) -> Result<()> {
+pub async fn set_skill_parent(skill_id: String, parent_id: Option<String>) -> Result<()> {
     if skill_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
             "Skill ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database deletion
-    Ok(())
+    let pool = db::get_db_pool()?;
+    db::set_skill_parent(&pool, &skill_id, parent_id.as_deref()).await
 }
 
 #[tauri::command]
-pub fn get_skill_progress(skill_id: String) -> Result<Vec<String>> {
+pub async fn get_skill_tree(skill_id: String) -> Result<SkillTreeNode> {
     if skill_id.is_empty() {
         return Err(crate::error::SkillEdError::ValidationError(
             "Skill ID cannot be empty".to_string(),
         ));
     }
 
-    // TODO: Implement database query
-    Ok(vec![])
+    let pool = db::get_db_pool()?;
+    db::get_skill_tree(&pool, &skill_id).await
+}
+
+#[tauri::command]
+pub async fn add_skill_prerequisite(request: AddPrerequisiteRequest) -> Result<SkillPrerequisite> {
+    if request.skill_id.is_empty() || request.prerequisite_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::add_skill_prerequisite(
+        &pool,
+        &request.skill_id,
+        &request.prerequisite_id,
+        request.min_proficiency.unwrap_or(50),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn remove_skill_prerequisite(skill_id: String, prerequisite_id: String) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::remove_skill_prerequisite(&pool, &skill_id, &prerequisite_id).await
+}
+
+#[tauri::command]
+pub async fn get_skill_prerequisites(skill_id: String) -> Result<Vec<SkillPrerequisite>> {
+    let pool = db::get_db_pool()?;
+    db::get_skill_prerequisites(&pool, &skill_id).await
+}
+
+/// Skills the user has not started yet whose prerequisites are all met
+#[tauri::command]
+pub async fn get_unlockable_skills() -> Result<Vec<Skill>> {
+    let pool = db::get_db_pool()?;
+    db::get_unlockable_skills(&pool).await
 }
@@ -0,0 +1,71 @@
+use crate::db::{
+    self, HabitCompletionRate, HourActivity, ProficiencyVelocity, SkillHoursPoint, TrendPoint, WeekdayStats,
+};
+use crate::error::Result;
+
+// All ranges are inclusive local dates in YYYY-MM-DD form.
+
+const DEFAULT_TREND_WINDOW: usize = 7;
+
+/// `period` is "weekly" or "monthly"; pass a `skill_id` to narrow to one skill
+#[tauri::command]
+pub async fn get_skill_hours(
+    period: String,
+    from: String,
+    to: String,
+    skill_id: Option<String>,
+) -> Result<Vec<SkillHoursPoint>> {
+    let pool = db::get_db_pool()?;
+    db::get_skill_hours_by_period(&pool, &period, &from, &to, skill_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn get_habit_completion_rates(from: String, to: String) -> Result<Vec<HabitCompletionRate>> {
+    let pool = db::get_db_pool()?;
+    db::get_habit_completion_rates(&pool, &from, &to).await
+}
+
+#[tauri::command]
+pub async fn get_weekday_stats(from: String, to: String) -> Result<WeekdayStats> {
+    let pool = db::get_db_pool()?;
+    db::get_weekday_stats(&pool, &from, &to).await
+}
+
+#[tauri::command]
+pub async fn get_time_of_day_stats(from: String, to: String) -> Result<Vec<HourActivity>> {
+    let pool = db::get_db_pool()?;
+    db::get_time_of_day_stats(&pool, &from, &to).await
+}
+
+/// `metric` is "practice_minutes" or "habit_completions"; `window` defaults to 7 days
+#[tauri::command]
+pub async fn get_activity_trend(
+    metric: String,
+    from: String,
+    to: String,
+    window: Option<usize>,
+    skill_id: Option<String>,
+) -> Result<Vec<TrendPoint>> {
+    let pool = db::get_db_pool()?;
+    db::get_activity_trend(
+        &pool,
+        &metric,
+        &from,
+        &to,
+        window.unwrap_or(DEFAULT_TREND_WINDOW),
+        skill_id.as_deref(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_proficiency_velocity(skill_id: String, from: String, to: String) -> Result<ProficiencyVelocity> {
+    if skill_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Skill ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::get_proficiency_velocity(&pool, &skill_id, &from, &to).await
+}
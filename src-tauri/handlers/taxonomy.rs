@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use crate::db::{self, Category, Tag};
+use crate::error::Result;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCategoryRequest {
+    pub name: String,
+    pub color: Option<String>, // hex, e.g. "#4f46e5"
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCategoryRequest {
+    pub id: String,
+    pub name: Option<String>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagRequest {
+    pub entity_type: String, // "skill", "habit", "milestone"
+    pub entity_id: String,
+    pub tag: String, // tag name when adding, tag id when removing
+}
+
+#[tauri::command]
+pub async fn create_category(request: CreateCategoryRequest) -> Result<Category> {
+    let pool = db::get_db_pool()?;
+    db::create_category(&pool, &request.name, request.color, request.icon).await
+}
+
+#[tauri::command]
+pub async fn get_categories() -> Result<Vec<Category>> {
+    let pool = db::get_db_pool()?;
+    db::get_categories(&pool).await
+}
+
+#[tauri::command]
+pub async fn update_category(request: UpdateCategoryRequest) -> Result<Category> {
+    if request.id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Category ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::update_category(&pool, &request.id, request.name.as_deref(), request.color, request.icon).await
+}
+
+/// Moves everything in `source_id` into `target_id` and deletes the source category
+#[tauri::command]
+pub async fn merge_categories(source_id: String, target_id: String) -> Result<Category> {
+    let pool = db::get_db_pool()?;
+    db::merge_categories(&pool, &source_id, &target_id).await
+}
+
+#[tauri::command]
+pub async fn get_tags() -> Result<Vec<Tag>> {
+    let pool = db::get_db_pool()?;
+    db::get_tags(&pool).await
+}
+
+#[tauri::command]
+pub async fn add_tag(request: TagRequest) -> Result<Tag> {
+    if request.entity_id.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Entity ID cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::add_tag(&pool, &request.entity_type, &request.entity_id, &request.tag).await
+}
+
+#[tauri::command]
+pub async fn remove_tag(request: TagRequest) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::remove_tag(&pool, &request.entity_type, &request.entity_id, &request.tag).await
+}
+
+#[tauri::command]
+pub async fn get_entity_tags(entity_type: String, entity_id: String) -> Result<Vec<Tag>> {
+    let pool = db::get_db_pool()?;
+    db::get_entity_tags(&pool, &entity_type, &entity_id).await
+}
+
+#[tauri::command]
+pub async fn rename_tag(tag_id: String, name: String) -> Result<Tag> {
+    let pool = db::get_db_pool()?;
+    db::rename_tag(&pool, &tag_id, &name).await
+}
+
+/// Re-tags everything tagged `source_id` with `target_id` and deletes the source tag
+#[tauri::command]
+pub async fn merge_tags(source_id: String, target_id: String) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    db::merge_tags(&pool, &source_id, &target_id).await
+}
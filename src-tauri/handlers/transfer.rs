@@ -0,0 +1,132 @@
+use crate::db::{self, BackupSchedule, CalendarFeed, CsvFilter, Export, HabitImportReport, ImportSummary};
+use crate::error::Result;
+use crate::state::AppState;
+use crate::tasks;
+use std::path::Path;
+
+/// Writes every data table to a versioned JSON file in the exports directory
+#[tauri::command]
+pub async fn export_data() -> Result<Export> {
+    let pool = db::get_db_pool()?;
+    let data_dir = db::get_app_data_dir()?;
+    db::export_data(&pool, &data_dir).await
+}
+
+/// Writes one entity as CSV to the exports directory. `entity` is one of skills,
+/// skill_progress, habits, habit_completions, milestones, or timeseries for a
+/// long-format table of daily values suited to pivot tables.
+#[tauri::command]
+pub async fn export_csv(entity: String, filters: Option<CsvFilter>) -> Result<Export> {
+    let pool = db::get_db_pool()?;
+    let data_dir = db::get_app_data_dir()?;
+    db::export_csv(&pool, &data_dir, &entity, &filters.unwrap_or_default()).await
+}
+
+/// Writes milestones and habit schedules as an iCalendar file to the exports directory
+#[tauri::command]
+pub async fn export_calendar() -> Result<Export> {
+    let pool = db::get_db_pool()?;
+    let data_dir = db::get_app_data_dir()?;
+    db::export_calendar(&pool, &data_dir).await
+}
+
+#[tauri::command]
+pub async fn get_calendar_feed() -> Result<CalendarFeed> {
+    let pool = db::get_db_pool()?;
+    db::get_calendar_feed(&pool).await
+}
+
+/// Turns the subscribable `.ics` file on or off; while on, it is rewritten after every change
+#[tauri::command]
+pub async fn update_calendar_feed(feed: CalendarFeed) -> Result<CalendarFeed> {
+    let pool = db::get_db_pool()?;
+    let data_dir = db::get_app_data_dir()?;
+    db::update_calendar_feed(&pool, &data_dir, feed).await
+}
+
+/// Imports a JSON export. `mode` is "merge" to keep existing rows and add new
+/// ones, or "replace" to swap the current data for the file's.
+#[tauri::command]
+pub async fn import_data(path: String, mode: String, state: tauri::State<'_, AppState>) -> Result<ImportSummary> {
+    state.require_master_key()?;
+    if path.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Import path cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::import_file(&pool, Path::new(&path), &mode).await
+}
+
+/// Imports habits and their history from another app. `source` is loop_csv
+/// (unzipped export directory or its Checkmarks.csv), loop_sqlite, habitica or
+/// generic_csv. With `dry_run` the report previews the import without saving.
+#[tauri::command]
+pub async fn import_habits(source: String, path: String, dry_run: Option<bool>) -> Result<HabitImportReport> {
+    if path.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Import path cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::import_habits(&pool, &source, Path::new(&path), dry_run.unwrap_or(false)).await
+}
+
+/// Writes an encrypted `.upgem` backup to the exports directory. The passphrase
+/// is independent of the master password.
+#[tauri::command]
+pub async fn create_backup(passphrase: String, state: tauri::State<'_, AppState>) -> Result<Export> {
+    state.require_master_key()?;
+    let pool = db::get_db_pool()?;
+    let data_dir = db::get_app_data_dir()?;
+    db::create_backup(&pool, &data_dir, &passphrase).await
+}
+
+/// Restores a `.upgem` backup; `mode` defaults to "replace". Replacing the
+/// vault needs it unlocked.
+#[tauri::command]
+pub async fn restore_backup(
+    path: String,
+    passphrase: String,
+    mode: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ImportSummary> {
+    let mode = mode.as_deref().unwrap_or("replace");
+    if mode == "replace" {
+        state.require_master_key()?;
+    }
+    if path.is_empty() {
+        return Err(crate::error::SkillEdError::ValidationError(
+            "Backup path cannot be empty".to_string(),
+        ));
+    }
+
+    let pool = db::get_db_pool()?;
+    db::restore_backup(&pool, Path::new(&path), &passphrase, mode).await
+}
+
+#[tauri::command]
+pub async fn get_backup_schedule() -> Result<BackupSchedule> {
+    let pool = db::get_db_pool()?;
+    db::get_backup_schedule(&pool).await
+}
+
+#[tauri::command]
+pub async fn update_backup_schedule(schedule: BackupSchedule) -> Result<BackupSchedule> {
+    let pool = db::get_db_pool()?;
+    db::update_backup_schedule(&pool, schedule).await
+}
+
+/// Sets the passphrase scheduled backups are encrypted with. It is held in
+/// memory for this session only; pass none to stop scheduled backups.
+#[tauri::command]
+pub fn set_backup_passphrase(passphrase: Option<String>) -> Result<()> {
+    if let Some(passphrase) = &passphrase {
+        db::validate_backup_passphrase(passphrase)?;
+    }
+
+    tasks::set_backup_passphrase(passphrase);
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use crate::db::{self, TraySettings};
+use crate::error::Result;
+use crate::state::AppState;
+
+#[tauri::command]
+pub async fn get_tray_settings() -> Result<TraySettings> {
+    let pool = db::get_db_pool()?;
+    db::get_tray_settings(&pool).await
+}
+
+/// Saves the settings and applies them to the running app
+#[tauri::command]
+pub async fn update_tray_settings(settings: TraySettings, state: tauri::State<'_, AppState>) -> Result<TraySettings> {
+    let pool = db::get_db_pool()?;
+    let settings = db::update_tray_settings(&pool, settings).await?;
+    state.set_minimize_to_tray(settings.minimize_to_tray);
+    Ok(settings)
+}
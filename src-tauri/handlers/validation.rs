@@ -0,0 +1,150 @@
+use chrono::{DateTime, NaiveDate};
+use crate::error::{FieldError, Result, SkillEdError};
+
+pub const MAX_NAME_LENGTH: usize = 100;
+pub const MAX_DESCRIPTION_LENGTH: usize = 2000;
+
+/// A request that checks and normalizes its own fields. `validate` reports every
+/// invalid field at once as `ValidationFailed` and returns the cleaned-up request.
+pub trait Validate: Sized {
+    fn validate(self) -> Result<Self>;
+}
+
+/// Collects field errors while normalizing values, so a request can be checked
+/// in one pass and rejected with all of its problems
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// A required single-line value such as a name: trimmed, inner whitespace
+    /// collapsed to single spaces, non-empty and at most `max_length` characters
+    pub fn name(&mut self, field: &str, value: &str, max_length: usize) -> String {
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        if value.is_empty() {
+            self.error(field, "Required");
+        } else if value.chars().count() > max_length {
+            self.error(field, format!("Must be at most {} characters", max_length));
+        }
+        value
+    }
+
+    /// `name` for a field that may be left out of an update
+    pub fn optional_name(&mut self, field: &str, value: Option<String>, max_length: usize) -> Option<String> {
+        value.map(|value| self.name(field, &value, max_length))
+    }
+
+    /// Free text such as a description: trimmed, with a blank value treated as absent
+    pub fn text(&mut self, field: &str, value: Option<String>, max_length: usize) -> Option<String> {
+        let value = value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())?;
+        if value.chars().count() > max_length {
+            self.error(field, format!("Must be at most {} characters", max_length));
+        }
+        Some(value)
+    }
+
+    /// An ID that must be present
+    pub fn id(&mut self, field: &str, value: &str) -> String {
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            self.error(field, "Required");
+        }
+        value
+    }
+
+    /// One of a fixed set of lowercase options; input is matched case-insensitively
+    pub fn choice(&mut self, field: &str, value: &str, options: &[&str]) -> String {
+        let value = value.trim().to_lowercase();
+        if !options.contains(&value.as_str()) {
+            self.error(field, format!("Must be one of: {}", options.join(", ")));
+        }
+        value
+    }
+
+    pub fn range(&mut self, field: &str, value: i32, min: i32, max: i32) {
+        if !(min..=max).contains(&value) {
+            self.error(field, format!("Must be between {} and {}", min, max));
+        }
+    }
+
+    pub fn non_negative(&mut self, field: &str, value: f64) {
+        if !value.is_finite() || value < 0.0 {
+            self.error(field, "Must be a non-negative number");
+        }
+    }
+
+    /// An ISO 8601 date (`YYYY-MM-DD`) or RFC 3339 timestamp, returned as
+    /// `YYYY-MM-DD`; a timestamp gives the date as written, in its own offset.
+    /// Dates that do not exist, such as February 30th, are rejected.
+    pub fn date(&mut self, field: &str, value: &str) -> String {
+        let value = value.trim();
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|t| t.date_naive()));
+
+        match date {
+            Some(date) => date.format("%Y-%m-%d").to_string(),
+            None => {
+                self.error(field, "Must be a valid date (YYYY-MM-DD)");
+                value.to_string()
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SkillEdError::ValidationFailed(self.errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_every_error() {
+        let mut v = Validator::new();
+        assert_eq!(v.name("name", "  Deep   work ", MAX_NAME_LENGTH), "Deep work");
+        v.name("category", "   ", MAX_NAME_LENGTH);
+        v.name("title", &"x".repeat(MAX_NAME_LENGTH + 1), MAX_NAME_LENGTH);
+        assert_eq!(v.choice("frequency", " Weekly", &["daily", "weekly"]), "weekly");
+        v.range("proficiency_level", 101, 0, 100);
+        assert_eq!(v.text("description", Some("  ".to_string()), MAX_DESCRIPTION_LENGTH), None);
+
+        let fields: Vec<String> = match v.finish() {
+            Err(SkillEdError::ValidationFailed(errors)) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(fields, vec!["category", "title", "proficiency_level"]);
+    }
+
+    #[test]
+    fn test_dates() {
+        let mut v = Validator::new();
+        assert_eq!(v.date("target_date", " 2024-03-04 "), "2024-03-04");
+        assert_eq!(v.date("target_date", "2024-03-04T09:00:00Z"), "2024-03-04");
+        // The date as written, not converted to UTC or local time
+        assert_eq!(v.date("target_date", "2024-03-04T23:30:00-05:00"), "2024-03-04");
+        assert!(v.finish().is_ok());
+
+        let mut v = Validator::new();
+        v.date("target_date", "2024-02-30");
+        v.date("target_date", "next week");
+        assert!(matches!(v.finish(), Err(SkillEdError::ValidationFailed(errors)) if errors.len() == 2));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::db::{self, ProfileLevel, SkillLevelConfig, XpConfig, XpEvent};
+use crate::error::Result;
+
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+#[tauri::command]
+pub async fn get_profile_level() -> Result<ProfileLevel> {
+    let pool = db::get_db_pool()?;
+    db::get_profile_level(&pool).await
+}
+
+/// Pages through the XP ledger, newest first
+#[tauri::command]
+pub async fn get_xp_history(
+    skill_id: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<XpEvent>> {
+    let pool = db::get_db_pool()?;
+    db::get_xp_history(
+        &pool,
+        skill_id.as_deref(),
+        limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+        offset.unwrap_or(0),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_xp_config() -> Result<XpConfig> {
+    let pool = db::get_db_pool()?;
+    db::get_xp_config(&pool).await
+}
+
+/// Changes how future XP is earned; existing ledger entries keep their values
+#[tauri::command]
+pub async fn update_xp_config(config: XpConfig) -> Result<XpConfig> {
+    let pool = db::get_db_pool()?;
+    db::update_xp_config(&pool, config).await
+}
+
+#[tauri::command]
+pub async fn get_skill_level_config() -> Result<SkillLevelConfig> {
+    let pool = db::get_db_pool()?;
+    db::get_skill_level_config(&pool).await
+}
+
+#[tauri::command]
+pub async fn update_skill_level_config(config: SkillLevelConfig) -> Result<SkillLevelConfig> {
+    let pool = db::get_db_pool()?;
+    db::update_skill_level_config(&pool, config).await
+}
@@ -6,6 +6,9 @@ mod crypto;
 mod handlers;
 mod state;
 mod error;
+mod tasks;
+mod tray;
+mod shutdown;
 
 use tauri::Manager;
 use log::info;
@@ -14,6 +17,9 @@ fn main() {
     env_logger::init();
     
     tauri::Builder::default()
+        .manage(state::AppState::new())
+        .system_tray(tray::build_tray())
+        .on_system_tray_event(tray::handle_tray_event)
         .setup(|app| {
             info!("Initializing Skill-ED application");
             
@@ -22,37 +28,142 @@ fn main() {
                 .app_data_dir()
                 .expect("Failed to resolve app data directory");
             
-            tokio::runtime::Runtime::new()
+            let tray_settings = tokio::runtime::Runtime::new()
                 .unwrap()
                 .block_on(async {
                     db::init_db(&db_path).await
                         .expect("Failed to initialize database");
-                })
-            ;
+                    db::get_tray_settings(&db::get_db_pool()?).await
+                })?;
+            app.state::<state::AppState>().set_minimize_to_tray(tray_settings.minimize_to_tray);
+
+            // Forward achievement unlocks to the frontend
+            let app_handle = app.handle();
+            db::set_unlock_listener(move |achievement| {
+                if let Err(e) = app_handle.emit_all(db::ACHIEVEMENT_UNLOCKED_EVENT, achievement) {
+                    log::warn!("Failed to emit achievement unlock: {}", e);
+                }
+            });
+
+            tasks::spawn_backup_scheduler(app.handle());
+            tasks::spawn_calendar_feed();
+            tasks::spawn_reminder_scheduler(app.handle());
+            tasks::spawn_tray_updater(app.handle());
             
             Ok(())
         })
+        .on_window_event(|event| {
+            // With minimize-to-tray on, closing the window only hides it
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
+                let window = event.window();
+                if window.state::<state::AppState>().minimize_to_tray() {
+                    api.prevent_close();
+                    if let Err(e) = window.hide() {
+                        log::warn!("Failed to hide window: {}", e);
+                    }
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             handlers::skill::create_skill,
             handlers::skill::get_skills,
             handlers::skill::update_skill,
             handlers::skill::delete_skill,
             handlers::skill::get_skill_progress,
+            handlers::skill::get_archived_skills,
+            handlers::skill::get_trashed_skills,
+            handlers::skill::restore_skill,
+            handlers::skill::archive_skill,
+            handlers::skill::unarchive_skill,
+            handlers::skill::reorder_skills,
+            handlers::skill::get_skill_level,
+            handlers::skill::get_skill_levels,
+            handlers::skill::set_skill_level_mode,
+            handlers::skill::set_skill_parent,
+            handlers::skill::get_skill_tree,
+            handlers::skill::add_skill_prerequisite,
+            handlers::skill::remove_skill_prerequisite,
+            handlers::skill::get_skill_prerequisites,
+            handlers::skill::get_unlockable_skills,
             handlers::habit::create_habit,
+            handlers::habit::update_habit,
             handlers::habit::log_habit_completion,
+            handlers::habit::link_habit_to_skill,
+            handlers::habit::unlink_habit_from_skill,
+            handlers::habit::get_habit_skill_links,
             handlers::habit::get_habit_streak,
             handlers::habit::get_habits,
+            handlers::habit::get_clean_streak_stats,
+            handlers::habit::get_relapse_trend,
+            handlers::habit::get_archived_habits,
+            handlers::habit::get_trashed_habits,
+            handlers::habit::delete_habit,
+            handlers::habit::restore_habit,
+            handlers::habit::archive_habit,
+            handlers::habit::unarchive_habit,
+            handlers::habit::reorder_habits,
+            handlers::session::start_session,
+            handlers::session::stop_session,
+            handlers::session::get_running_session,
+            handlers::session::get_practice_sessions,
+            handlers::taxonomy::create_category,
+            handlers::taxonomy::get_categories,
+            handlers::taxonomy::update_category,
+            handlers::taxonomy::merge_categories,
+            handlers::taxonomy::get_tags,
+            handlers::taxonomy::add_tag,
+            handlers::taxonomy::remove_tag,
+            handlers::taxonomy::get_entity_tags,
+            handlers::taxonomy::rename_tag,
+            handlers::taxonomy::merge_tags,
+            handlers::xp::get_profile_level,
+            handlers::xp::get_xp_history,
+            handlers::xp::get_xp_config,
+            handlers::xp::update_xp_config,
+            handlers::xp::get_skill_level_config,
+            handlers::xp::update_skill_level_config,
+            handlers::achievement::get_achievements,
+            handlers::activity::get_activity_feed,
+            handlers::activity::get_activity_heatmap,
+            handlers::stats::get_skill_hours,
+            handlers::stats::get_habit_completion_rates,
+            handlers::stats::get_weekday_stats,
+            handlers::stats::get_time_of_day_stats,
+            handlers::stats::get_activity_trend,
+            handlers::stats::get_proficiency_velocity,
+            handlers::review::generate_review,
+            handlers::review::get_exports,
+            handlers::transfer::export_data,
+            handlers::transfer::import_data,
+            handlers::transfer::import_habits,
+            handlers::transfer::export_csv,
+            handlers::transfer::export_calendar,
+            handlers::transfer::get_calendar_feed,
+            handlers::transfer::update_calendar_feed,
+            handlers::transfer::create_backup,
+            handlers::transfer::restore_backup,
+            handlers::transfer::get_backup_schedule,
+            handlers::transfer::update_backup_schedule,
+            handlers::transfer::set_backup_passphrase,
+            handlers::milestone::create_milestone,
+            handlers::milestone::get_milestones,
+            handlers::milestone::complete_milestone,
+            handlers::milestone::delete_milestone,
+            handlers::reminder::create_reminder,
+            handlers::reminder::get_reminders,
+            handlers::reminder::update_reminder,
+            handlers::reminder::delete_reminder,
+            handlers::reminder::snooze_reminder,
+            handlers::tray::get_tray_settings,
+            handlers::tray::update_tray_settings,
             handlers::auth::set_master_password,
             handlers::auth::verify_master_password,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
-            match event {
-                tauri::RunEvent::ExitRequested { api, .. } => {
-                    api.prevent_exit();
-                }
-                _ => {}
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(shutdown::shutdown(app_handle));
             }
         });
 }
@@ -0,0 +1,35 @@
+use crate::db;
+use crate::state::AppState;
+use crate::tasks;
+use log::{info, warn};
+use tauri::{AppHandle, Manager};
+
+/// Runs before the process exits: background tasks finish their current step,
+/// the database is checkpointed and closed, and the keys are dropped from memory.
+/// Safe to call more than once.
+pub async fn shutdown(app: &AppHandle) {
+    info!("Shutting down Skill-ED");
+
+    tasks::stop_background_tasks().await;
+
+    if let Err(e) = db::close_db_pool().await {
+        warn!("Failed to close database cleanly: {}", e);
+    }
+
+    clear_secrets(app);
+}
+
+/// Shuts down, then ends the process
+pub fn quit(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        shutdown(&app).await;
+        app.exit(0);
+    });
+}
+
+/// Forgets the master key and the backup passphrase
+pub fn clear_secrets(app: &AppHandle) {
+    app.state::<AppState>().clear_master_key();
+    tasks::set_backup_passphrase(None);
+}
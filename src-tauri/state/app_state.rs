@@ -8,6 +8,8 @@ pub struct AppState {
     pub master_key: Arc<Mutex<Option<[u8; 32]>>>,
     /// Whether user has authenticated
     pub is_authenticated: Arc<Mutex<bool>>,
+    /// Cached copy of the minimize-to-tray setting, read when a window is closed
+    pub minimize_to_tray: Arc<Mutex<bool>>,
 }
 
 impl AppState {
@@ -15,6 +17,7 @@ impl AppState {
         Self {
             master_key: Arc::new(Mutex::new(None)),
             is_authenticated: Arc::new(Mutex::new(false)),
+            minimize_to_tray: Arc::new(Mutex::new(true)),
         }
     }
 
@@ -32,9 +35,22 @@ impl AppState {
         *self.master_key.lock()
     }
 
+    /// The master key, or `VaultLocked` when no one is signed in
+    pub fn require_master_key(&self) -> crate::error::Result<[u8; 32]> {
+        self.get_master_key().ok_or(crate::error::SkillEdError::VaultLocked)
+    }
+
     pub fn is_authenticated(&self) -> bool {
         *self.is_authenticated.lock()
     }
+
+    pub fn set_minimize_to_tray(&self, enabled: bool) {
+        *self.minimize_to_tray.lock() = enabled;
+    }
+
+    pub fn minimize_to_tray(&self) -> bool {
+        *self.minimize_to_tray.lock()
+    }
 }
 
 impl Default for AppState {
@@ -42,3 +58,20 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_vault_is_reported() {
+        let state = AppState::new();
+        assert_eq!(state.require_master_key().unwrap_err().code(), "VAULT_LOCKED");
+
+        state.set_master_key([7; 32]);
+        assert_eq!(state.require_master_key().unwrap(), [7; 32]);
+
+        state.clear_master_key();
+        assert_eq!(state.require_master_key().unwrap_err().code(), "VAULT_LOCKED");
+    }
+}
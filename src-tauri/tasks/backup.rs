@@ -0,0 +1,99 @@
+use crate::db;
+use crate::error::Result;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Event emitted when a scheduled backup fails or cannot run
+pub const BACKUP_WARNING_EVENT: &str = "backup-warning";
+
+/// How often the scheduler checks whether a backup is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+lazy_static! {
+    /// Passphrase for scheduled backups; kept in memory only, like the master key
+    static ref BACKUP_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupWarning {
+    pub message: String,
+    pub occurred_at: String,
+}
+
+pub fn set_backup_passphrase(passphrase: Option<String>) {
+    *BACKUP_PASSPHRASE.lock() = passphrase;
+}
+
+/// Starts the background task that takes scheduled backups
+pub fn spawn_backup_scheduler(app: AppHandle) {
+    super::spawn_tracked(async move {
+        // Repeats of the same warning are only emitted once
+        let mut last_warning: Option<String> = None;
+
+        loop {
+            match check_backup().await {
+                Ok(()) => last_warning = None,
+                Err(e) => {
+                    let message = e.to_string();
+                    warn!("Scheduled backup failed: {}", message);
+                    if last_warning.as_deref() != Some(message.as_str()) {
+                        let warning = BackupWarning {
+                            message: message.clone(),
+                            occurred_at: Utc::now().to_rfc3339(),
+                        };
+                        if let Err(e) = app.emit_all(BACKUP_WARNING_EVENT, warning) {
+                            warn!("Failed to emit backup warning: {}", e);
+                        }
+                        last_warning = Some(message);
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = super::shutdown_requested() => return,
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+async fn check_backup() -> Result<()> {
+    let pool = db::get_db_pool()?;
+    let schedule = db::get_backup_schedule(&pool).await?;
+    if !schedule.enabled {
+        return Ok(());
+    }
+
+    let last_run = db::last_scheduled_backup(&pool).await?;
+    if !db::backup_due(&schedule.cadence, last_run.as_deref(), Utc::now())? {
+        return Ok(());
+    }
+
+    let data_dir = db::get_app_data_dir()?;
+    let passphrase = BACKUP_PASSPHRASE.lock().clone().ok_or_else(|| {
+        crate::error::SkillEdError::AuthError("No backup passphrase set for this session".to_string())
+    })?;
+
+    match db::run_scheduled_backup(&pool, &data_dir, &schedule, &passphrase).await {
+        Ok(run) => {
+            info!(
+                "Scheduled backup written to {} ({} old backups removed)",
+                run.export.file_path,
+                run.removed.len()
+            );
+            Ok(())
+        }
+        Err(e) => {
+            let directory = db::backup_directory(&schedule, &data_dir);
+            if let Err(log_error) = db::record_backup_failure(&pool, &directory).await {
+                warn!("Failed to log backup failure: {}", log_error);
+            }
+            Err(e)
+        }
+    }
+}
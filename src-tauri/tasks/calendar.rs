@@ -0,0 +1,32 @@
+use crate::db;
+use crate::error::Result;
+use log::warn;
+use std::time::Duration;
+
+/// Quiet time after a change before the feed is rewritten, so a burst of changes
+/// produces one write
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Rewrites the feed this often even without a signal, to catch anything missed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Starts the background task that keeps the calendar feed file current
+pub fn spawn_calendar_feed() {
+    super::spawn_debounced_refresh(DEBOUNCE, REFRESH_INTERVAL, || async {
+        if let Err(e) = refresh_calendar_feed().await {
+            warn!("Failed to update calendar feed: {}", e);
+        }
+    });
+}
+
+async fn refresh_calendar_feed() -> Result<()> {
+    let pool = db::get_db_pool()?;
+    let feed = db::get_calendar_feed(&pool).await?;
+    if !feed.enabled {
+        return Ok(());
+    }
+
+    let data_dir = db::get_app_data_dir()?;
+    db::write_calendar_feed(&pool, &data_dir, &feed).await?;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+mod backup;
+mod calendar;
+mod reminders;
+mod tray;
+
+pub use backup::*;
+pub use calendar::*;
+pub use reminders::*;
+pub use tray::*;
+
+use crate::db;
+use lazy_static::lazy_static;
+use log::warn;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::watch;
+
+/// How long tasks get to finish their current step at shutdown before they are aborted
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    /// Set once shutdown begins; task loops watch it between steps
+    static ref SHUTDOWN: watch::Sender<bool> = watch::channel(false).0;
+    static ref RUNNING: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+}
+
+/// Spawns a background task that `stop_background_tasks` will wait for. Once
+/// shutdown has begun, new tasks are not started.
+pub(crate) fn spawn_tracked<F>(task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let mut running = RUNNING.lock();
+    if *SHUTDOWN.borrow() {
+        return;
+    }
+    // Short-lived tasks such as tray actions come and go; forget the finished ones
+    running.retain(|handle| !handle.inner().is_finished());
+    running.push(tauri::async_runtime::spawn(task));
+}
+
+/// Resolves once shutdown has begun. Task loops select on it so they stop
+/// between steps instead of in the middle of a write.
+async fn shutdown_requested() {
+    let mut shutdown = SHUTDOWN.subscribe();
+    let _ = shutdown.wait_for(|stopping| *stopping).await;
+}
+
+/// Asks every background task to stop and waits for them, aborting any that
+/// are still busy after a grace period. Calling it again does nothing.
+pub async fn stop_background_tasks() {
+    SHUTDOWN.send_replace(true);
+
+    let handles = std::mem::take(&mut *RUNNING.lock());
+    let deadline = tokio::time::Instant::now() + SHUTDOWN_GRACE;
+    for mut handle in handles {
+        if tokio::time::timeout_at(deadline, &mut handle).await.is_err() {
+            warn!("Background task did not stop in time; aborting it");
+            handle.abort();
+        }
+    }
+}
+
+/// Starts a task that runs `refresh` now, again once data changes have been
+/// quiet for `debounce` (so a burst of changes refreshes once), and every
+/// `interval` even without a change, until shutdown
+fn spawn_debounced_refresh<F, Fut>(debounce: Duration, interval: Duration, mut refresh: F)
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    spawn_tracked(async move {
+        let mut changes = db::subscribe_changes();
+
+        loop {
+            refresh().await;
+
+            tokio::select! {
+                _ = shutdown_requested() => return,
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    // Let the burst settle before refreshing
+                    loop {
+                        tokio::select! {
+                            changed = changes.changed() => {
+                                if changed.is_err() {
+                                    return;
+                                }
+                            }
+                            _ = shutdown_requested() => return,
+                            _ = tokio::time::sleep(debounce) => break,
+                        }
+                    }
+                }
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    });
+}
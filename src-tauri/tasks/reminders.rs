@@ -0,0 +1,204 @@
+use crate::db::{self, DueReminder};
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager};
+
+/// Event emitted with the `DueReminder` each time a notification is shown
+pub const REMINDER_FIRED_EVENT: &str = "reminder-fired";
+
+/// How often the scheduler looks for due reminders
+const TICK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Source of the current time, so scheduling can be tested without waiting
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Delivers a due reminder to the user
+pub trait Notifier: Send + Sync {
+    fn notify(&self, reminder: &DueReminder) -> Result<()>;
+}
+
+/// Shows OS notifications through Tauri and tells the frontend about them
+pub struct TauriNotifier {
+    app: AppHandle,
+}
+
+impl TauriNotifier {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl Notifier for TauriNotifier {
+    fn notify(&self, reminder: &DueReminder) -> Result<()> {
+        Notification::new(&self.app.config().tauri.bundle.identifier)
+            .title(&reminder.title)
+            .body(&reminder.body)
+            .show()
+            .map_err(|e| crate::error::SkillEdError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+
+        if let Err(e) = self.app.emit_all(REMINDER_FIRED_EVENT, reminder.clone()) {
+            warn!("Failed to emit reminder event: {}", e);
+        }
+        Ok(())
+    }
+}
+
+/// Starts the background task that fires reminders
+pub fn spawn_reminder_scheduler(app: AppHandle) {
+    super::spawn_tracked(async move {
+        let notifier = TauriNotifier::new(app);
+
+        loop {
+            match db::get_db_pool() {
+                Ok(pool) => {
+                    if let Err(e) = run_reminder_tick(&pool, &SystemClock, &notifier).await {
+                        warn!("Reminder check failed: {}", e);
+                    }
+                }
+                Err(e) => warn!("Reminder check skipped: {}", e),
+            }
+
+            tokio::select! {
+                _ = super::shutdown_requested() => return,
+                _ = tokio::time::sleep(TICK_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+/// Notifies every reminder due now and marks it handled. Reminders whose habit is
+/// already done are marked without a notification. Returns the reminders shown.
+pub async fn run_reminder_tick(pool: &SqlitePool, clock: &dyn Clock, notifier: &dyn Notifier) -> Result<Vec<DueReminder>> {
+    let now = clock.now();
+    let mut shown = Vec::new();
+
+    for due in db::due_reminders(pool, now).await? {
+        if !due.suppressed {
+            // A failed notification is not retried, or it would repeat every tick
+            if let Err(e) = notifier.notify(&due) {
+                warn!("Failed to show reminder {}: {}", due.reminder.id, e);
+            }
+        }
+        db::mark_reminder_fired(pool, &due.reminder.id, now).await?;
+        if !due.suppressed {
+            shown.push(due);
+        }
+    }
+
+    Ok(shown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{create_habit, create_reminder, snooze_reminder, Reminder};
+    use chrono::{Local, NaiveDateTime, TimeZone};
+    use parking_lot::Mutex;
+
+    struct MockClock(Mutex<DateTime<Utc>>);
+
+    impl MockClock {
+        fn at(value: &str) -> Self {
+            MockClock(Mutex::new(local(value)))
+        }
+
+        fn set(&self, value: &str) {
+            *self.0.lock() = local(value);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock()
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<String>>);
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, reminder: &DueReminder) -> Result<()> {
+            self.0.lock().push(reminder.body.clone());
+            Ok(())
+        }
+    }
+
+    fn local(value: &str) -> DateTime<Utc> {
+        let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&naive).earliest().unwrap().with_timezone(&Utc)
+    }
+
+    #[tokio::test]
+    async fn test_reminder_fires_once_and_snoozes() {
+        let pool = db::test_pool().await;
+        let habit = create_habit(&pool, "Read".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        let reminder = Reminder::new("habit".to_string(), habit.id.clone(), "08:00".to_string());
+        let reminder = create_reminder(&pool, reminder).await.unwrap();
+
+        let clock = MockClock::at("2024-03-04 07:30");
+        let notifier = RecordingNotifier::default();
+        assert!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().is_empty());
+
+        clock.set("2024-03-04 08:00");
+        assert_eq!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().len(), 1);
+        clock.set("2024-03-04 08:01");
+        assert!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().is_empty());
+
+        snooze_reminder(&pool, &reminder.id, 10, clock.now()).await.unwrap();
+        clock.set("2024-03-04 08:05");
+        assert!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().is_empty());
+        clock.set("2024-03-04 08:11");
+        assert_eq!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().len(), 1);
+        assert_eq!(*notifier.0.lock(), vec!["Time for Read".to_string(); 2]);
+
+        // Survives a restart: the next day's slot fires from the persisted state
+        clock.set("2024-03-05 08:30");
+        assert_eq!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reminder_skipped_when_done() {
+        let pool = db::test_pool().await;
+        let habit = create_habit(&pool, "Stretch".to_string(), "daily".to_string(), "positive".to_string(), None, None)
+            .await
+            .unwrap();
+        create_reminder(&pool, Reminder::new("habit".to_string(), habit.id.clone(), "21:00".to_string()))
+            .await
+            .unwrap();
+        let mut always = Reminder::new("habit".to_string(), habit.id.clone(), "21:00".to_string());
+        always.only_if_not_done = false;
+        create_reminder(&pool, always).await.unwrap();
+
+        sqlx::query("UPDATE habits SET last_completed = ? WHERE id = ?")
+            .bind(local("2024-03-04 12:00").to_rfc3339())
+            .bind(&habit.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let clock = MockClock::at("2024-03-04 21:00");
+        let notifier = RecordingNotifier::default();
+        assert_eq!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().len(), 1);
+        // The suppressed one is still marked, so it does not come back this slot
+        assert!(db::due_reminders(&pool, clock.now()).await.unwrap().is_empty());
+
+        clock.set("2024-03-05 21:00");
+        assert_eq!(run_reminder_tick(&pool, &clock, &notifier).await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,23 @@
+use log::warn;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Quiet time after a change before the menu is rebuilt, so a burst of changes
+/// rebuilds once
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Rebuilds the menu this often even without a signal, so habits come due again
+/// when a new day, week or month starts
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Starts the background task that keeps the tray menu in step with the data
+pub fn spawn_tray_updater(app: AppHandle) {
+    super::spawn_debounced_refresh(DEBOUNCE, REFRESH_INTERVAL, move || {
+        let app = app.clone();
+        async move {
+            if let Err(e) = crate::tray::refresh_tray(&app).await {
+                warn!("Failed to refresh tray: {}", e);
+            }
+        }
+    });
+}
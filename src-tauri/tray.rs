@@ -0,0 +1,151 @@
+use crate::db::{self, TraySummary};
+use crate::error::Result;
+use crate::state::AppState;
+use chrono::{DateTime, Local, Utc};
+use log::warn;
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
+};
+
+/// Event emitted after the vault is locked from the tray, so the frontend shows the lock screen
+pub const VAULT_LOCKED_EVENT: &str = "vault-locked";
+
+const OPEN_ID: &str = "open";
+const LOCK_ID: &str = "lock";
+const QUIT_ID: &str = "quit";
+const TIMER_START_PREFIX: &str = "timer-start:";
+const TIMER_STOP_ID: &str = "timer-stop";
+const HABIT_PREFIX: &str = "habit:";
+
+/// Tray shown at startup, before the database has been read
+pub fn build_tray() -> SystemTray {
+    SystemTray::new().with_menu(build_menu(None))
+}
+
+/// Rebuilds the tray menu from the current data
+pub async fn refresh_tray(app: &AppHandle) -> Result<()> {
+    let pool = db::get_db_pool()?;
+    let summary = db::get_tray_summary(&pool, Utc::now()).await?;
+
+    app.tray_handle()
+        .set_menu(build_menu(Some(&summary)))
+        .map_err(|e| crate::error::SkillEdError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))
+}
+
+fn build_menu(summary: Option<&TraySummary>) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new().add_item(CustomMenuItem::new(OPEN_ID, "Open Skill-ED"));
+
+    if let Some(summary) = summary {
+        menu = menu.add_native_item(SystemTrayMenuItem::Separator);
+        if summary.due_habits.is_empty() {
+            menu = menu.add_item(CustomMenuItem::new("habits-done", "All habits done").disabled());
+        }
+        for habit in &summary.due_habits {
+            menu = menu.add_item(CustomMenuItem::new(
+                format!("{}{}", HABIT_PREFIX, habit.id),
+                format!("Complete: {}", habit.name),
+            ));
+        }
+
+        menu = menu.add_native_item(SystemTrayMenuItem::Separator);
+        menu = match (&summary.running_session, &summary.timer_skill) {
+            (Some(session), skill) => {
+                let name = skill.as_ref().map(|s| s.name.as_str()).unwrap_or("practice");
+                let started = started_at(&session.started_at);
+                menu.add_item(CustomMenuItem::new(
+                    TIMER_STOP_ID,
+                    format!("Stop timer: {} (since {})", name, started),
+                ))
+            }
+            (None, Some(skill)) => menu.add_item(CustomMenuItem::new(
+                format!("{}{}", TIMER_START_PREFIX, skill.id),
+                format!("Start timer: {}", skill.name),
+            )),
+            (None, None) => menu.add_item(CustomMenuItem::new("timer-none", "Start timer").disabled()),
+        };
+    }
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(CustomMenuItem::new(LOCK_ID, "Lock vault"))
+        .add_item(CustomMenuItem::new(QUIT_ID, "Quit"))
+}
+
+/// Local HH:MM of a session start, or the raw value if it does not parse
+fn started_at(value: &str) -> String {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick { .. } => show_main_window(app),
+        SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
+            OPEN_ID => show_main_window(app),
+            LOCK_ID => lock_vault(app),
+            QUIT_ID => crate::shutdown::quit(app),
+            TIMER_STOP_ID => run_action(app, async move {
+                let pool = db::get_db_pool()?;
+                db::stop_session(&pool, None, None).await?;
+                Ok(())
+            }),
+            id => {
+                // Writes from the tray need the vault unlocked, like the window's commands
+                let unlocked = app.state::<AppState>().require_master_key();
+                if let Some(habit_id) = id.strip_prefix(HABIT_PREFIX) {
+                    let habit_id = habit_id.to_string();
+                    run_action(app, async move {
+                        unlocked?;
+                        let pool = db::get_db_pool()?;
+                        db::log_habit_completion(&pool, &habit_id).await?;
+                        Ok(())
+                    });
+                } else if let Some(skill_id) = id.strip_prefix(TIMER_START_PREFIX) {
+                    let skill_id = skill_id.to_string();
+                    run_action(app, async move {
+                        unlocked?;
+                        let pool = db::get_db_pool()?;
+                        db::start_session(&pool, &skill_id, None).await?;
+                        Ok(())
+                    });
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Runs a menu action off the event loop, as a tracked task so shutdown waits
+/// for its write. The menu is refreshed straight away rather than after the
+/// change debounce, so the clicked item disappears at once.
+fn run_action<F>(app: &AppHandle, action: F)
+where
+    F: std::future::Future<Output = Result<()>> + Send + 'static,
+{
+    let app = app.clone();
+    crate::tasks::spawn_tracked(async move {
+        if let Err(e) = action.await {
+            warn!("Tray action failed: {}", e);
+        }
+        if let Err(e) = refresh_tray(&app).await {
+            warn!("Failed to refresh tray: {}", e);
+        }
+    });
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_window("main") {
+        if let Err(e) = window.show().and_then(|_| window.set_focus()) {
+            warn!("Failed to show main window: {}", e);
+        }
+    }
+}
+
+/// Drops the in-memory keys and tells the frontend to show the lock screen
+fn lock_vault(app: &AppHandle) {
+    crate::shutdown::clear_secrets(app);
+
+    if let Err(e) = app.emit_all(VAULT_LOCKED_EVENT, ()) {
+        warn!("Failed to emit vault lock: {}", e);
+    }
+}